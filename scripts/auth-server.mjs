@@ -0,0 +1,97 @@
+// Local server protected by HTTP basic authentication, used to test
+// the login prompt and the credentials file:
+//
+//   node scripts/auth-server.mjs [port]
+//   scripts/run.sh Default http://localhost:8000
+//
+// The login is `carbonyl`/`secret` unless AUTH_USER and AUTH_PASSWORD are set.
+// Pass `--self-test` to check the server answers challenges as expected.
+import http from "http";
+
+const args = process.argv.slice(2);
+const selfTest = args.includes("--self-test");
+const port = Number(args.find((arg) => !arg.startsWith("--")) ?? 8000);
+const user = process.env.AUTH_USER ?? "carbonyl";
+const password = process.env.AUTH_PASSWORD ?? "secret";
+const realm = "Carbonyl test";
+
+function authorized(req) {
+  const [scheme, token] = (req.headers.authorization ?? "").split(" ");
+
+  if (scheme !== "Basic" || !token) {
+    return null;
+  }
+
+  const decoded = Buffer.from(token, "base64").toString("utf-8");
+  const index = decoded.indexOf(":");
+  const login = [decoded.slice(0, index), decoded.slice(index + 1)];
+
+  return index >= 0 && login[0] === user && login[1] === password
+    ? login[0]
+    : null;
+}
+
+function page(title, body) {
+  return `<!doctype html><title>${title}</title><h1>${title}</h1>${body}`;
+}
+
+const server = http.createServer((req, res) => {
+  if (req.url === "/public") {
+    res.writeHead(200, { "content-type": "text/html" });
+    res.end(page("Public", '<p>No login needed. <a href="/">Protected page</a></p>'));
+
+    return;
+  }
+
+  const name = authorized(req);
+
+  console.log(`${req.method} ${req.url} ${name ? `as ${name}` : "unauthorized"}`);
+
+  if (!name) {
+    res.writeHead(401, {
+      "content-type": "text/html",
+      "www-authenticate": `Basic realm="${realm}", charset="UTF-8"`,
+    });
+    res.end(page("Unauthorized", "<p>Wrong or missing login.</p>"));
+
+    return;
+  }
+
+  res.writeHead(200, { "content-type": "text/html" });
+  res.end(page("Logged in", `<p>Welcome ${name}.</p>`));
+});
+
+async function check(login, expected) {
+  const headers = login
+    ? { authorization: `Basic ${Buffer.from(login).toString("base64")}` }
+    : {};
+  const res = await fetch(`http://localhost:${port}/`, { headers });
+
+  if (res.status !== expected) {
+    throw new Error(`expected ${expected} for ${login ?? "no login"}, got ${res.status}`);
+  }
+  if (expected === 401 && !res.headers.get("www-authenticate")?.includes(realm)) {
+    throw new Error("missing challenge");
+  }
+}
+
+server.listen(port, async () => {
+  console.log(`Listening on http://localhost:${port}, login ${user}/${password}`);
+
+  if (!selfTest) {
+    return;
+  }
+
+  try {
+    await check(null, 401);
+    await check(`${user}:wrong`, 401);
+    await check(`${user}:${password}`, 200);
+
+    console.log("Self-test passed");
+  } catch (error) {
+    console.error(`Self-test failed: ${error.message}`);
+    process.exitCode = 1;
+  } finally {
+    server.close();
+  }
+});
@@ -1,3 +1,5 @@
 mod bridge;
+mod control;
 
 pub use bridge::*;
+pub use control::*;
@@ -0,0 +1,85 @@
+#include "carbonyl/src/browser/auth.h"
+
+#include <utility>
+
+#include "base/memory/raw_ptr.h"
+#include "base/no_destructor.h"
+#include "base/strings/utf_string_conversions.h"
+#include "carbonyl/src/browser/renderer.h"
+#include "net/base/auth.h"
+#include "third_party/abseil-cpp/absl/types/optional.h"
+
+namespace carbonyl {
+
+// Pending challenge, forgotten when Chromium doesn't need the login anymore.
+class Auth::Login : public content::LoginDelegate {
+ public:
+  Login(Auth* auth,
+        unsigned int id,
+        content::LoginAuthRequiredCallback callback)
+      : auth_(auth), id_(id), callback_(std::move(callback)) {}
+
+  ~Login() override {
+    auth_->logins_.erase(id_);
+  }
+
+  content::LoginAuthRequiredCallback TakeCallback() {
+    return std::move(callback_);
+  }
+
+ private:
+  raw_ptr<Auth> auth_;
+  unsigned int id_;
+  content::LoginAuthRequiredCallback callback_;
+};
+
+Auth* Auth::GetInstance() {
+  static base::NoDestructor<Auth> instance;
+
+  return instance.get();
+}
+
+Auth::Auth() = default;
+Auth::~Auth() = default;
+
+std::unique_ptr<content::LoginDelegate> Auth::CreateLoginDelegate(
+    const net::AuthChallengeInfo& auth_info,
+    content::LoginAuthRequiredCallback callback) {
+  auto id = next_id_++;
+  auto login = std::make_unique<Login>(this, id, std::move(callback));
+
+  logins_[id] = login.get();
+
+  Renderer::GetCurrent()->AuthChallenge(id, auth_info.challenger.Serialize(),
+                                        auth_info.realm, auth_info.is_proxy);
+
+  return login;
+}
+
+void Auth::OnResult(unsigned int id,
+                    const char* username,
+                    const char* password) {
+  auto it = logins_.find(id);
+
+  if (it == logins_.end()) {
+    return;
+  }
+
+  // Running the callback can destroy the login
+  auto callback = it->second->TakeCallback();
+
+  logins_.erase(it);
+
+  if (!callback) {
+    return;
+  }
+
+  if (username && password) {
+    std::move(callback).Run(net::AuthCredentials(base::UTF8ToUTF16(username),
+                                                 base::UTF8ToUTF16(password)));
+  } else {
+    std::move(callback).Run(absl::nullopt);
+  }
+}
+
+}  // namespace carbonyl
@@ -0,0 +1,48 @@
+#ifndef CARBONYL_SRC_BROWSER_AUTH_H_
+#define CARBONYL_SRC_BROWSER_AUTH_H_
+
+#include <map>
+#include <memory>
+
+#include "carbonyl/src/browser/export.h"
+#include "content/public/browser/content_browser_client.h"
+#include "content/public/browser/login_delegate.h"
+
+namespace net {
+class AuthChallengeInfo;
+}
+
+namespace carbonyl {
+
+// Asks the terminal UI for the logins of HTTP authentication challenges.
+//
+// The headless content browser client asks for a login delegate for each
+// challenge, the challenge is cancelled if Chromium destroys it first.
+class CARBONYL_CONTENTS_EXPORT Auth {
+ public:
+  static Auth* GetInstance();
+
+  Auth();
+  ~Auth();
+
+  // disable copy
+  Auth(const Auth&) = delete;
+  Auth& operator=(const Auth&) = delete;
+
+  // Shows a login prompt for the challenge, answered with `callback`
+  std::unique_ptr<content::LoginDelegate> CreateLoginDelegate(
+      const net::AuthChallengeInfo& auth_info,
+      content::LoginAuthRequiredCallback callback);
+  // Answers the challenge `id`, null pointers cancel it
+  void OnResult(unsigned int id, const char* username, const char* password);
+
+ private:
+  class Login;
+
+  std::map<unsigned int, Login*> logins_;
+  unsigned int next_id_ = 0;
+};
+
+}  // namespace carbonyl
+
+#endif  // CARBONYL_SRC_BROWSER_AUTH_H_
@@ -1,16 +1,35 @@
+// The exported functions are only called by the browser, with pointers it
+// keeps valid for the duration of the call or to the bridge it created.
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
 use std::ffi::{CStr, CString};
 use std::io::Write;
+use std::os::unix::ffi::OsStrExt;
 use std::process::{Command, Stdio};
 use std::sync::{mpsc, Mutex};
-use std::{env, io, thread};
+use std::time::Duration;
+use std::{env, io, ptr, thread};
 
 use libc::{c_char, c_float, c_int, c_uchar, c_uint, c_void, size_t};
 
+use super::control::{ControlServer, Request};
 use crate::cli::{CommandLine, CommandLineProgram, EnvVar};
+use crate::config::Permission;
 use crate::gfx::{Cast, Color, Point, Rect, Size};
-use crate::output::{RenderThread, Window};
-use crate::ui::navigation::NavigationAction;
-use crate::{input, utils::log};
+use crate::input::{self, Key};
+use crate::output::{dump, RenderThread, ScreenshotFormat, TextStyle, Window};
+use crate::terminal;
+use crate::ui::{
+    auth::AuthRequest,
+    dialog::DialogKind,
+    downloads::DownloadState,
+    menu::MenuItem,
+    navigation::NavigationAction,
+    permissions::PermissionAnswer,
+    reader::{Block, BlockKind, Span},
+    status::SecurityState,
+};
+use crate::utils::{json::Value, log};
 
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -43,6 +62,44 @@ pub struct CText {
     text: *const c_char,
     rect: CRect,
     color: CColor,
+    /// CSS font weight, from 100 to 900
+    weight: c_uint,
+    /// Bit flags, 1: italic, 2: underline, 4: line-through, 8: decoration color is set
+    style: c_uchar,
+    decoration_color: CColor,
+    /// Target of the link containing the text, null if none
+    href: *const c_char,
+}
+
+impl CText {
+    fn style(&self) -> TextStyle {
+        let underline = self.style & 2 != 0;
+
+        TextStyle {
+            bold: self.weight >= 600,
+            italic: self.style & 1 != 0,
+            underline,
+            strikethrough: self.style & 4 != 0,
+            underline_color: (underline && self.style & 8 != 0)
+                .then(|| self.decoration_color.into()),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct CReaderSpan {
+    text: *const c_char,
+    /// Link target, null if the span isn't a link
+    href: *const c_char,
+    /// Index of the block containing the span
+    block: c_uint,
+    /// 0: paragraph, 1: heading, 2: list item, 3: ordered list item, 4: code, 5: quote
+    kind: c_uchar,
+    /// Heading level or list nesting depth
+    level: c_uchar,
+    /// Bit flags, 1: bold, 2: italic, 4: inline code
+    style: c_uchar,
 }
 
 #[repr(C)]
@@ -50,6 +107,17 @@ pub struct RendererBridge {
     cmd: CommandLine,
     window: Window,
     renderer: RenderThread,
+    /// Set once the browser listens for events,
+    /// used to answer requests without user input
+    delegate: Option<BrowserDelegate>,
+    /// Whether the page was captured for `--screenshot` or `--dump`
+    captured: bool,
+    /// Socket other programs drive the browser with, see `--control-socket`
+    control: Option<ControlServer>,
+    /// State of the page reported on the control socket
+    url: String,
+    title: String,
+    loading: bool,
 }
 
 unsafe impl Send for RendererBridge {}
@@ -57,6 +125,19 @@ unsafe impl Sync for RendererBridge {}
 
 pub type RendererPtr = *const Mutex<RendererBridge>;
 
+/// Time given to the page to paint after it loaded, before capturing it.
+const CAPTURE_WAIT: u64 = 500;
+/// Capture the page anyway if it takes longer to load.
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct CMenuItem {
+    label: *const c_char,
+    /// Bit flags, 1: disabled, 2: separator, 4: checked
+    flags: c_uchar,
+}
+
 impl<T: Copy> From<CPoint> for Point<T>
 where
     c_uint: Cast<T>,
@@ -101,6 +182,22 @@ pub struct BrowserDelegate {
     mouse_down: extern "C" fn(c_uint, c_uint),
     mouse_move: extern "C" fn(c_uint, c_uint),
     post_task: extern "C" fn(extern "C" fn(*mut c_void), *mut c_void),
+    // Callbacks below were added after the initial bridge,
+    // they're optional so an older runtime leaves them null.
+    hard_refresh: Option<extern "C" fn()>,
+    zoom: Option<extern "C" fn(c_float)>,
+    request_hints: Option<extern "C" fn()>,
+    find: Option<extern "C" fn(*const c_char, bool)>,
+    request_reader: Option<extern "C" fn()>,
+    copy_selection: Option<extern "C" fn()>,
+    paste: Option<extern "C" fn(*const c_char)>,
+    dialog_result: Option<extern "C" fn(bool, *const c_char)>,
+    menu_result: Option<extern "C" fn(c_int)>,
+    file_chooser_result: Option<extern "C" fn(*const *const c_char, size_t)>,
+    download_destination: Option<extern "C" fn(c_uint, *const c_char)>,
+    download_control: Option<extern "C" fn(c_uint, c_uint)>,
+    permission_result: Option<extern "C" fn(c_uint, bool)>,
+    auth_result: Option<extern "C" fn(c_uint, *const c_char, *const c_char)>,
 }
 
 fn main() -> io::Result<Option<i32>> {
@@ -113,7 +210,13 @@ fn main() -> io::Result<Option<i32>> {
         return Ok(None);
     }
 
-    let mut terminal = input::Terminal::setup();
+    let mut terminal = terminal::open(&cmd);
+
+    // Pages are captured and recordings replayed without a terminal
+    if cmd.interactive() {
+        terminal.setup();
+    }
+
     let mut command = Command::new(env::current_exe()?);
 
     if !cmd.bitmap {
@@ -132,6 +235,13 @@ fn main() -> io::Result<Option<i32>> {
 
     terminal.teardown();
 
+    // The browser can't clean up if it crashed or was killed
+    if let Some(ref path) = cmd.control_socket {
+        if let Err(error) = ControlServer::unlink(path) {
+            log::warning!("failed to remove {path}: {error}");
+        }
+    }
+
     let code = output.status.code().unwrap_or(127);
 
     if code != 0 || cmd.debug {
@@ -150,7 +260,14 @@ pub extern "C" fn carbonyl_bridge_main() {
 
 #[no_mangle]
 pub extern "C" fn carbonyl_bridge_bitmap_mode() -> bool {
-    CommandLine::parse().bitmap
+    let cmd = CommandLine::parse();
+    // Images need the text drawn by the browser
+    let screenshot = cmd
+        .screenshot
+        .as_deref()
+        .is_some_and(|path| ScreenshotFormat::from_path(path).is_bitmap());
+
+    cmd.bitmap || screenshot
 }
 
 #[no_mangle]
@@ -164,6 +281,12 @@ pub extern "C" fn carbonyl_renderer_create() -> RendererPtr {
         cmd: CommandLine::parse(),
         window: Window::read(),
         renderer: RenderThread::new(),
+        delegate: None,
+        captured: false,
+        control: None,
+        url: String::new(),
+        title: String::new(),
+        loading: false,
     };
 
     Box::into_raw(Box::new(Mutex::new(bridge)))
@@ -208,9 +331,26 @@ pub extern "C" fn carbonyl_renderer_push_nav(
     let (bridge, url) = unsafe { (bridge.as_ref(), CStr::from_ptr(url)) };
     let (mut bridge, url) = (bridge.unwrap().lock().unwrap(), url.to_owned());
 
+    bridge.url = url.to_string_lossy().into_owned();
+
+    let (control, params) = (
+        bridge.control.clone(),
+        Value::object([
+            ("url", bridge.url.as_str().into()),
+            ("can_go_back", can_go_back.into()),
+            ("can_go_forward", can_go_forward.into()),
+        ]),
+    );
+
     bridge.renderer.render(move |renderer| {
         renderer.push_nav(url.to_str().unwrap(), can_go_back, can_go_forward)
     });
+
+    drop(bridge);
+
+    if let Some(control) = control {
+        control.notify("navigated", params);
+    }
 }
 
 #[no_mangle]
@@ -218,9 +358,22 @@ pub extern "C" fn carbonyl_renderer_set_title(bridge: RendererPtr, title: *const
     let (bridge, title) = unsafe { (bridge.as_ref(), CStr::from_ptr(title)) };
     let (mut bridge, title) = (bridge.unwrap().lock().unwrap(), title.to_owned());
 
+    bridge.title = title.to_string_lossy().into_owned();
+
+    let (control, params) = (
+        bridge.control.clone(),
+        Value::object([("title", bridge.title.as_str().into())]),
+    );
+
     bridge
         .renderer
         .render(move |renderer| renderer.set_title(title.to_str().unwrap()).unwrap());
+
+    drop(bridge);
+
+    if let Some(control) = control {
+        control.notify("title_changed", params);
+    }
 }
 
 #[no_mangle]
@@ -241,19 +394,607 @@ pub extern "C" fn carbonyl_renderer_draw_text(
                 text.rect.origin.into(),
                 text.rect.size.into(),
                 text.color.into(),
+                text.style(),
+                (!text.href.is_null()).then(|| unsafe { c_string(text.href) }),
             )
         })
-        .collect::<Vec<(String, Point, Size, Color)>>();
+        .collect::<Vec<(String, Point, Size, Color, TextStyle, Option<String>)>>();
 
     bridge.renderer.render(move |renderer| {
         renderer.clear_text();
 
-        for (text, origin, size, color) in std::mem::take(&mut vec) {
-            renderer.draw_text(&text, origin, size, color)
+        for (text, origin, size, color, style, link) in std::mem::take(&mut vec) {
+            renderer.draw_text(&text, origin, size, color, style, link.as_deref())
+        }
+    });
+}
+
+/// Called by the browser with the bounds of the clickable elements
+/// in the viewport, in response to `BrowserDelegate::request_hints`.
+#[no_mangle]
+pub extern "C" fn carbonyl_renderer_show_hints(
+    bridge: RendererPtr,
+    rects: *const CRect,
+    rects_size: size_t,
+) {
+    let (bridge, rects) = unsafe {
+        (
+            bridge.as_ref(),
+            std::slice::from_raw_parts(rects, rects_size),
+        )
+    };
+    let mut bridge = bridge.unwrap().lock().unwrap();
+    let (scale, cells) = (bridge.window.scale, bridge.window.cells);
+    let mut targets = rects
+        .iter()
+        .filter(|rect| rect.size.width > 0 && rect.size.height > 0)
+        .map(|rect| {
+            let origin: Point<f32> = rect.origin.into();
+            let size: Size<f32> = rect.size.into();
+            // First row is used by the navigation bar
+            let cell = (origin / (scale.width, scale.height)).floor().cast::<i32>() + (0, 1);
+            let target = (origin + Point::new(size.width, size.height) / 2.0)
+                .floor()
+                .cast();
+
+            (cell, target)
+        })
+        .filter(|(cell, _): &(Point, Point<u32>)| {
+            cell.x < cells.width as i32 && cell.y <= cells.height as i32
+        })
+        .collect::<Vec<_>>();
+
+    bridge
+        .renderer
+        .render(move |renderer| renderer.show_hints(std::mem::take(&mut targets)));
+}
+
+/// Called by the browser with the result of `BrowserDelegate::find`,
+/// `current` is the index of the active match starting at 1.
+#[no_mangle]
+pub extern "C" fn carbonyl_renderer_find_result(
+    bridge: RendererPtr,
+    current: c_uint,
+    total: c_uint,
+) {
+    let bridge = unsafe { bridge.as_ref() };
+    let mut bridge = bridge.unwrap().lock().unwrap();
+
+    bridge
+        .renderer
+        .render(move |renderer| renderer.find_result(current, total));
+}
+
+/// Called by the browser with the main content of the page,
+/// in response to `BrowserDelegate::request_reader`.
+#[no_mangle]
+pub extern "C" fn carbonyl_renderer_show_reader(
+    bridge: RendererPtr,
+    title: *const c_char,
+    spans: *const CReaderSpan,
+    spans_size: size_t,
+) {
+    let (bridge, title, spans) = unsafe {
+        (
+            bridge.as_ref(),
+            CStr::from_ptr(title),
+            std::slice::from_raw_parts(spans, spans_size),
+        )
+    };
+    let (mut bridge, title) = (bridge.unwrap().lock().unwrap(), title.to_owned());
+    let mut blocks = Vec::<Block>::new();
+    let mut index = None;
+
+    for span in spans {
+        let text = unsafe { CStr::from_ptr(span.text) };
+        let link = (!span.href.is_null()).then(|| unsafe { c_string(span.href) });
+        let kind = match (span.kind, span.level) {
+            (1, level) => BlockKind::Heading(level.clamp(1, 6)),
+            (2, depth) => BlockKind::ListItem(depth),
+            (3, depth) => BlockKind::OrderedListItem(depth),
+            (4, _) => BlockKind::Code,
+            (5, _) => BlockKind::Quote,
+            _ => BlockKind::Paragraph,
+        };
+
+        if index != Some(span.block) {
+            index = Some(span.block);
+            blocks.push(Block {
+                kind,
+                spans: Vec::new(),
+            });
+        }
+
+        if let Some(block) = blocks.last_mut() {
+            block.spans.push(Span {
+                text: text.to_string_lossy().into_owned(),
+                bold: span.style & 1 != 0,
+                italic: span.style & 2 != 0,
+                code: span.style & 4 != 0,
+                link,
+            })
+        }
+    }
+
+    // Extracted for `--dump` rather than by the user
+    if let Some(format) = bridge.cmd.dump.filter(|_| bridge.captured) {
+        let delegate = bridge.delegate;
+
+        return bridge.renderer.render(move |_| {
+            print(&dump(&title.to_string_lossy(), &blocks, format));
+            quit(delegate)
+        });
+    }
+
+    bridge.renderer.render(move |renderer| {
+        renderer.show_reader(&title.to_string_lossy(), std::mem::take(&mut blocks))
+    });
+}
+
+/// Called by the browser with text to write to the clipboard of the terminal,
+/// ie. the selection in response to `BrowserDelegate::copy_selection`.
+#[no_mangle]
+pub extern "C" fn carbonyl_renderer_copy(bridge: RendererPtr, text: *const c_char) {
+    let (bridge, text) = unsafe { (bridge.as_ref(), CStr::from_ptr(text)) };
+    let (mut bridge, text) = (bridge.unwrap().lock().unwrap(), text.to_owned());
+
+    bridge.renderer.render(move |renderer| {
+        if let Err(error) = renderer.copy(&text.to_string_lossy()) {
+            log::warning!("failed to copy text: {error}")
         }
     });
 }
 
+/// Called by the browser when the page opens a JavaScript dialog:
+/// 0 for `alert()`, 1 for `confirm()` and 2 for `prompt()`.
+/// The answer is sent back with `BrowserDelegate::dialog_result`.
+#[no_mangle]
+pub extern "C" fn carbonyl_renderer_show_dialog(
+    bridge: RendererPtr,
+    kind: c_uint,
+    message: *const c_char,
+    default_value: *const c_char,
+) {
+    let bridge = unsafe { bridge.as_ref() };
+    let mut bridge = bridge.unwrap().lock().unwrap();
+    let message = unsafe { c_string(message) };
+    let default_value = unsafe { c_string(default_value) };
+    let kind = DialogKind::from_code(kind);
+
+    bridge
+        .renderer
+        .render(move |renderer| renderer.show_dialog(kind, &message, &default_value));
+}
+
+/// Called by the browser to open a `<select>` popup or a context menu
+/// next to `rect`, in browser pixels. `selected` is the highlighted item,
+/// or -1 for none. The choice is sent back with `BrowserDelegate::menu_result`.
+#[no_mangle]
+pub extern "C" fn carbonyl_renderer_show_menu(
+    bridge: RendererPtr,
+    rect: CRect,
+    items: *const CMenuItem,
+    items_size: size_t,
+    selected: c_int,
+) {
+    let (bridge, items) = unsafe {
+        (
+            bridge.as_ref(),
+            std::slice::from_raw_parts(items, items_size),
+        )
+    };
+    let mut bridge = bridge.unwrap().lock().unwrap();
+    let scale = bridge.window.scale;
+    let origin: Point<f32> = rect.origin.into();
+    let size: Size<f32> = rect.size.into();
+    // First row is used by the navigation bar
+    let cell = (origin / (scale.width, scale.height)).floor().cast::<i32>() + (0, 1);
+    let cells = (size / (scale.width, scale.height)).ceil().cast::<u32>();
+    let anchor = Rect::new(cell.x, cell.y, cells.width.max(1), cells.height.max(1));
+    let mut items = items
+        .iter()
+        .map(|item| MenuItem {
+            label: unsafe { c_string(item.label) },
+            enabled: item.flags & 1 == 0,
+            separator: item.flags & 2 != 0,
+            checked: item.flags & 4 != 0,
+        })
+        .collect::<Vec<_>>();
+    let selected = usize::try_from(selected).ok();
+
+    bridge
+        .renderer
+        .render(move |renderer| renderer.show_menu(anchor, std::mem::take(&mut items), selected));
+}
+
+/// Called by the browser for `<input type=file>`, with the initial directory,
+/// or null for the current one, and the value of the `accept` attribute.
+/// The paths are sent back with `BrowserDelegate::file_chooser_result`,
+/// an empty list means the user cancelled.
+#[no_mangle]
+pub extern "C" fn carbonyl_renderer_show_file_chooser(
+    bridge: RendererPtr,
+    dir: *const c_char,
+    accept: *const c_char,
+    multiple: bool,
+) {
+    let bridge = unsafe { bridge.as_ref() };
+    let mut bridge = bridge.unwrap().lock().unwrap();
+    let (dir, accept) = unsafe { (c_string(dir), c_string(accept)) };
+
+    bridge
+        .renderer
+        .render(move |renderer| renderer.show_file_chooser(&dir, &accept, multiple));
+}
+
+/// Called by the browser when the page starts or stops loading,
+/// with a progress between 0 and 1.
+#[no_mangle]
+pub extern "C" fn carbonyl_renderer_set_loading(
+    bridge: RendererPtr,
+    loading: bool,
+    progress: c_float,
+) {
+    let bridge = unsafe { bridge.as_ref() }.unwrap();
+    let mut guard = bridge.lock().unwrap();
+
+    if !loading && guard.cmd.headless() {
+        let wait = guard.cmd.wait.unwrap_or(CAPTURE_WAIT);
+
+        capture(bridge, Duration::from_millis(wait));
+    }
+
+    let changed = std::mem::replace(&mut guard.loading, loading) != loading;
+    let (control, params) = (
+        guard.control.clone().filter(|_| changed),
+        Value::object([("url", guard.url.as_str().into())]),
+    );
+
+    guard
+        .renderer
+        .render(move |renderer| renderer.set_loading(loading, progress));
+
+    drop(guard);
+
+    if let Some(control) = control {
+        let method = match loading {
+            true => "load_started",
+            false => "load_finished",
+        };
+
+        control.notify(method, params);
+    }
+}
+
+/// Called by the browser with the target of the hovered link,
+/// null or empty once the pointer leaves it.
+#[no_mangle]
+pub extern "C" fn carbonyl_renderer_set_hover_url(bridge: RendererPtr, url: *const c_char) {
+    let bridge = unsafe { bridge.as_ref() };
+    let mut bridge = bridge.unwrap().lock().unwrap();
+    let url = unsafe { c_string(url) };
+
+    bridge
+        .renderer
+        .render(move |renderer| renderer.set_hover_url(&url));
+}
+
+/// Called by the browser when the security of the connection changes:
+/// 0 for none, 1 for secure, 2 for insecure and 3 for certificate errors.
+#[no_mangle]
+pub extern "C" fn carbonyl_renderer_set_security_state(bridge: RendererPtr, state: c_uint) {
+    let bridge = unsafe { bridge.as_ref() };
+    let mut bridge = bridge.unwrap().lock().unwrap();
+    let state = SecurityState::from_code(state);
+
+    bridge
+        .renderer
+        .render(move |renderer| renderer.set_security_state(state));
+}
+
+/// Called by the browser when a download starts, with the path it suggests.
+/// The location chosen by the user is sent back with
+/// `BrowserDelegate::download_destination`, an empty path cancels the download.
+#[no_mangle]
+pub extern "C" fn carbonyl_renderer_download_started(
+    bridge: RendererPtr,
+    id: c_uint,
+    url: *const c_char,
+    path: *const c_char,
+) {
+    let bridge = unsafe { bridge.as_ref() };
+    let mut bridge = bridge.unwrap().lock().unwrap();
+    let (url, path) = unsafe { (c_string(url), c_string(path)) };
+
+    bridge
+        .renderer
+        .render(move |renderer| renderer.download_started(id, &url, &path));
+}
+
+/// Called by the browser as a download progresses, `total` is -1 if unknown.
+#[no_mangle]
+pub extern "C" fn carbonyl_renderer_download_progress(
+    bridge: RendererPtr,
+    id: c_uint,
+    received: u64,
+    total: i64,
+    paused: bool,
+) {
+    let bridge = unsafe { bridge.as_ref() };
+    let mut bridge = bridge.unwrap().lock().unwrap();
+    let total = u64::try_from(total).ok();
+
+    bridge
+        .renderer
+        .render(move |renderer| renderer.download_progress(id, received, total, paused));
+}
+
+/// Called by the browser when a download ends: 0 for completed,
+/// 1 for cancelled and 2 for failed, with the path of the saved file.
+#[no_mangle]
+pub extern "C" fn carbonyl_renderer_download_finished(
+    bridge: RendererPtr,
+    id: c_uint,
+    state: c_uint,
+    path: *const c_char,
+) {
+    let bridge = unsafe { bridge.as_ref() };
+    let mut bridge = bridge.unwrap().lock().unwrap();
+    let state = DownloadState::from_code(state);
+    let path = unsafe { c_string(path) };
+
+    bridge
+        .renderer
+        .render(move |renderer| renderer.download_finished(id, state, &path));
+}
+
+/// Called by the browser when a site asks for a permission, with its origin
+/// and the permission: 0 for geolocation, 1 for notifications, 2 for the camera,
+/// 3 for the microphone and 4 for reading the clipboard. The user is asked unless
+/// they already decided, and the answer is sent with `BrowserDelegate::permission_result`.
+#[no_mangle]
+pub extern "C" fn carbonyl_renderer_request_permission(
+    bridge: RendererPtr,
+    id: c_uint,
+    origin: *const c_char,
+    permission: c_uint,
+) {
+    let bridge = unsafe { bridge.as_ref() };
+    let mut bridge = bridge.unwrap().lock().unwrap();
+    let origin = unsafe { c_string(origin) };
+    let delegate = bridge.delegate;
+    let answer = move |allowed: bool| match delegate {
+        Some(delegate) => {
+            if let Some(callback) = delegate.permission_result {
+                unsafe { post_task(delegate.post_task, move || callback(id, allowed)) }
+            }
+        }
+        None => log::warning!("permission request received before listening"),
+    };
+
+    bridge.renderer.render(move |renderer| {
+        // Deny permissions we don't know how to ask for
+        let allowed = match Permission::from_code(permission) {
+            Some(permission) => renderer.request_permission(id, &origin, permission),
+            None => Some(false),
+        };
+
+        if let Some(allowed) = allowed {
+            answer(allowed)
+        }
+    });
+}
+
+/// Called by the browser when a site or a proxy requires HTTP authentication,
+/// with the origin and the realm sent by the server. The login is taken from
+/// the credentials file or asked to the user, and sent with
+/// `BrowserDelegate::auth_result`, null pointers cancel the challenge.
+#[no_mangle]
+pub extern "C" fn carbonyl_renderer_auth_challenge(
+    bridge: RendererPtr,
+    id: c_uint,
+    origin: *const c_char,
+    realm: *const c_char,
+    proxy: bool,
+) {
+    let bridge = unsafe { bridge.as_ref() };
+    let mut bridge = bridge.unwrap().lock().unwrap();
+    let (mut origin, mut realm) = unsafe { (c_string(origin), c_string(realm)) };
+    let delegate = bridge.delegate;
+    let answer = move |username: String, password: String| match delegate {
+        Some(delegate) => {
+            if let Some(callback) = delegate.auth_result {
+                let username = CString::new(username.replace('\0', "")).unwrap();
+                let password = CString::new(password.replace('\0', "")).unwrap();
+
+                unsafe {
+                    post_task(delegate.post_task, move || {
+                        callback(id, username.as_ptr(), password.as_ptr())
+                    })
+                }
+            }
+        }
+        None => log::warning!("authentication challenge received before listening"),
+    };
+
+    bridge.renderer.render(move |renderer| {
+        let request = AuthRequest {
+            id,
+            origin: std::mem::take(&mut origin),
+            realm: std::mem::take(&mut realm),
+            proxy,
+        };
+
+        if let Some((username, password)) = renderer.auth_challenge(request) {
+            answer(username, password)
+        }
+    });
+}
+
+/// Capture the page after a delay for `--screenshot` and `--dump`,
+/// then quit. Only the first call captures the page.
+fn capture(bridge: &'static Mutex<RendererBridge>, delay: Duration) {
+    thread::spawn(move || {
+        thread::sleep(delay);
+
+        let mut bridge = bridge.lock().unwrap();
+
+        if std::mem::replace(&mut bridge.captured, true) {
+            return;
+        }
+
+        let (screenshot, dump) = (bridge.cmd.screenshot.clone(), bridge.cmd.dump);
+        let delegate = bridge.delegate;
+        // The text of the whole document is extracted by the browser if it can,
+        // it's printed once received by `carbonyl_renderer_show_reader`
+        let extract =
+            delegate.and_then(|delegate| Some((delegate.post_task, delegate.request_reader?)));
+
+        bridge.renderer.render(move |renderer| {
+            if let Some(ref path) = screenshot {
+                if let Err(error) = renderer.screenshot(path) {
+                    log::error!("failed to save the screenshot to {path}: {error}");
+
+                    std::process::exit(1)
+                }
+            }
+
+            match (dump, extract) {
+                (Some(_), Some((handle, request_reader))) => unsafe {
+                    post_task(handle, move || request_reader())
+                },
+                // Only the text visible in the window is available
+                (Some(_), None) => {
+                    print(&renderer.page_text());
+                    quit(delegate)
+                }
+                (None, _) => quit(delegate),
+            }
+        });
+    });
+}
+
+/// Compare the screen with the `--golden` file at the end of a replay,
+/// exiting with an error if they differ.
+fn compare_golden(bridge: &Mutex<RendererBridge>) {
+    let (golden, wait) = {
+        let bridge = bridge.lock().unwrap();
+
+        (
+            bridge.cmd.golden.clone(),
+            bridge.cmd.wait.unwrap_or(CAPTURE_WAIT),
+        )
+    };
+    let Some(path) = golden else {
+        return;
+    };
+    let (tx, rx) = mpsc::channel();
+    let golden = path.clone();
+
+    // Give the browser time to draw the result of the last input
+    thread::sleep(Duration::from_millis(wait));
+
+    bridge
+        .lock()
+        .unwrap()
+        .renderer
+        .render(move |renderer| tx.send(renderer.check_golden(&golden)).unwrap());
+
+    match rx.recv().unwrap() {
+        Ok(true) => (),
+        Ok(false) => {
+            log::error!("the screen doesn't match {path}, see {path}.actual");
+
+            std::process::exit(1)
+        }
+        Err(error) => {
+            log::error!("failed to compare the screen with {path}: {error}");
+
+            std::process::exit(1)
+        }
+    }
+}
+
+/// Run a request received on the control socket.
+fn control(bridge: &Mutex<RendererBridge>, request: Request) -> Result<Value, String> {
+    let mut guard = bridge.lock().unwrap();
+    let delegate = guard.delegate.ok_or("the browser isn't ready")?;
+    let scale = guard.window.scale;
+    let action = match request {
+        Request::Navigate(url) => NavigationAction::GoTo(url.replace('\0', "")),
+        Request::Back => NavigationAction::GoBack(),
+        Request::Forward => NavigationAction::GoForward(),
+        Request::Reload { hard: false } => NavigationAction::Refresh(),
+        Request::Reload { hard: true } => NavigationAction::HardRefresh(),
+        Request::Scroll(rows) => NavigationAction::Scroll(rows),
+        Request::Keys(text) => NavigationAction::Keys(
+            text.bytes()
+                .map(|char| Key {
+                    char,
+                    modifiers: Default::default(),
+                })
+                .collect(),
+        ),
+        // Cells are counted from the top left corner of the page
+        Request::ClickCell(col, row) => {
+            let (x, y) = scale
+                .mul((col as f32 + 0.5, row as f32 + 0.5))
+                .floor()
+                .cast()
+                .into();
+
+            NavigationAction::Click(Point::new(x, y))
+        }
+        Request::ClickPixel(x, y) => NavigationAction::Click(Point::new(x, y)),
+        Request::Status => {
+            return Ok(Value::object([
+                ("url", guard.url.as_str().into()),
+                ("title", guard.title.as_str().into()),
+                ("loading", guard.loading.into()),
+            ]))
+        }
+        Request::Screenshot(path) => {
+            let (tx, rx) = mpsc::channel();
+
+            guard
+                .renderer
+                .render(move |renderer| tx.send(renderer.screenshot(&path)).unwrap());
+            drop(guard);
+
+            return match rx.recv().unwrap() {
+                Ok(()) => Ok(Value::Null),
+                Err(error) => Err(format!("failed to save the screenshot: {error}")),
+            };
+        }
+    };
+
+    dispatch(delegate, scale, action);
+
+    Ok(Value::Null)
+}
+
+/// Write captured text to the standard output.
+fn print(text: &str) {
+    let mut stdout = io::stdout();
+
+    if let Err(error) = stdout
+        .write_all(text.as_bytes())
+        .and_then(|_| stdout.flush())
+    {
+        log::error!("failed to print the page: {error}");
+
+        std::process::exit(1)
+    }
+}
+
+/// Shutdown the browser once the page was captured.
+fn quit(delegate: Option<BrowserDelegate>) {
+    match delegate {
+        Some(delegate) => unsafe { post_task(delegate.post_task, move || (delegate.shutdown)()) },
+        None => std::process::exit(0),
+    }
+}
+
 #[derive(Clone, Copy)]
 struct CallbackData(*const c_void);
 
@@ -304,6 +1045,18 @@ pub extern "C" fn carbonyl_renderer_get_size(bridge: RendererPtr) -> CSize {
     bridge.window.browser.into()
 }
 
+/// Copy a string received from the browser, null pointers are empty strings.
+///
+/// # Safety
+///
+/// The pointer must be null or point to a NUL-terminated string.
+unsafe fn c_string(ptr: *const c_char) -> String {
+    match ptr.is_null() {
+        true => String::new(),
+        false => CStr::from_ptr(ptr).to_string_lossy().into_owned(),
+    }
+}
+
 extern "C" fn post_task_handler(callback: *mut c_void) {
     let mut closure = unsafe { Box::from_raw(callback as *mut Box<dyn FnMut()>) };
 
@@ -319,6 +1072,190 @@ where
     handle(post_task_handler, closure as *mut c_void);
 }
 
+/// Post a call to the browser, running an optional closure after it.
+macro_rules! emit {
+    ($delegate:expr, $event:ident($($args:expr),*) => $closure:expr) => {{
+        let delegate = $delegate;
+        let run = move || {
+            (delegate.$event)($($args),*);
+
+            $closure
+        };
+
+        unsafe { post_task(delegate.post_task, run) }
+    }};
+    ($delegate:expr, $event:ident($($args:expr),*)) => {{
+        emit!($delegate, $event($($args),*) => {})
+    }};
+    // Optional callbacks, returns false if not implemented
+    ($delegate:expr, $event:ident?($($args:expr),*)) => {{
+        let delegate = $delegate;
+
+        match delegate.$event {
+            None => false,
+            Some(callback) => {
+                let run = move || callback($($args),*);
+
+                unsafe { post_task(delegate.post_task, run) }
+
+                true
+            }
+        }
+    }};
+}
+
+/// Type a key in the page.
+fn key_press(delegate: BrowserDelegate, key: &Key) {
+    match key.char {
+        // Home, End and Delete don't have a character to type
+        0xf5..=0xf7 => (),
+        char => {
+            // The browser only receives characters, send Alt the way
+            // terminals do, as an escape before the key
+            if key.modifiers.alt {
+                emit!(delegate, key_press(0x1b))
+            }
+
+            emit!(delegate, key_press(char as c_char))
+        }
+    }
+}
+
+/// Run an action of the UI, returns whether the event
+/// causing it should be forwarded to the page.
+fn dispatch(delegate: BrowserDelegate, scale: Size<f32>, action: NavigationAction) -> bool {
+    match action {
+        NavigationAction::Ignore => (),
+        NavigationAction::Forward => return true,
+        NavigationAction::GoBack() => emit!(delegate, go_back()),
+        NavigationAction::GoForward() => emit!(delegate, go_forward()),
+        NavigationAction::Refresh() => emit!(delegate, refresh()),
+        NavigationAction::HardRefresh() => {
+            if !emit!(delegate, hard_refresh?()) {
+                emit!(delegate, refresh())
+            }
+        }
+        NavigationAction::Zoom(factor) => {
+            emit!(delegate, zoom?(factor));
+        }
+        NavigationAction::Scroll(rows) => {
+            emit!(delegate, scroll((rows as f32 * scale.height) as c_int))
+        }
+        NavigationAction::Quit() => emit!(delegate, shutdown()),
+        NavigationAction::RequestHints() => {
+            emit!(delegate, request_hints?());
+        }
+        NavigationAction::Click(point) => {
+            emit!(delegate, mouse_move(point.x, point.y));
+            emit!(delegate, mouse_down(point.x, point.y));
+            emit!(delegate, mouse_up(point.x, point.y))
+        }
+        NavigationAction::Find(query, forward) => {
+            let c_str = CString::new(query).unwrap_or_default();
+
+            emit!(delegate, find?(c_str.as_ptr(), forward));
+        }
+        NavigationAction::RequestReader() => {
+            emit!(delegate, request_reader?());
+        }
+        NavigationAction::RequestCopy() => {
+            emit!(delegate, copy_selection?());
+        }
+        NavigationAction::Paste(text) => {
+            let text = text.replace('\0', "");
+
+            if delegate.paste.is_some() {
+                let c_str = CString::new(text).unwrap();
+
+                emit!(delegate, paste?(c_str.as_ptr()));
+            } else {
+                // Type the text if the browser can't insert it
+                for byte in text.into_bytes() {
+                    emit!(delegate, key_press(byte as c_char))
+                }
+            }
+        }
+        NavigationAction::CloseDialog(accepted, value) => {
+            let c_str = CString::new(value.replace('\0', "")).unwrap();
+
+            emit!(delegate, dialog_result?(accepted, c_str.as_ptr()));
+        }
+        NavigationAction::CloseMenu(index) => {
+            emit!(
+                delegate,
+                menu_result?(index.map_or(-1, |index| index as c_int))
+            );
+        }
+        NavigationAction::CloseFileChooser(paths) => {
+            let paths = paths
+                .iter()
+                .filter_map(|path| CString::new(path.as_os_str().as_bytes()).ok())
+                .collect::<Vec<_>>();
+
+            // Pointers are collected in the task, raw pointers can't be sent
+            emit!(
+                delegate,
+                file_chooser_result?(
+                    paths
+                        .iter()
+                        .map(|path| path.as_ptr())
+                        .collect::<Vec<_>>()
+                        .as_ptr(),
+                    paths.len()
+                )
+            );
+        }
+        NavigationAction::SetDownloadPath(id, path) => {
+            let c_str = CString::new(path.replace('\0', "")).unwrap();
+
+            emit!(delegate, download_destination?(id, c_str.as_ptr()));
+        }
+        NavigationAction::ControlDownload(id, control) => {
+            emit!(delegate, download_control?(id, control as c_uint));
+        }
+        NavigationAction::AnswerPermission(request, answer) => {
+            emit!(
+                delegate,
+                permission_result?(request.id, answer != PermissionAnswer::Deny)
+            );
+        }
+        NavigationAction::AnswerAuth(id, login) => {
+            let login = login.map(|(username, password)| {
+                (
+                    CString::new(username.replace('\0', "")).unwrap(),
+                    CString::new(password.replace('\0', "")).unwrap(),
+                )
+            });
+
+            // Null pointers cancel the challenge
+            emit!(
+                delegate,
+                auth_result?(
+                    id,
+                    login
+                        .as_ref()
+                        .map_or(ptr::null(), |(username, _)| username.as_ptr()),
+                    login
+                        .as_ref()
+                        .map_or(ptr::null(), |(_, password)| password.as_ptr())
+                )
+            );
+        }
+        NavigationAction::Keys(keys) => {
+            for key in keys {
+                key_press(delegate, &key)
+            }
+        }
+        NavigationAction::GoTo(url) => {
+            let c_str = CString::new(url).unwrap();
+
+            emit!(delegate, go_to(c_str.as_ptr()))
+        }
+    };
+
+    false
+}
+
 /// Function called by the C++ code to listen for input events.
 ///
 /// This will block so the calling code should start and own a dedicated thread.
@@ -328,26 +1265,45 @@ pub extern "C" fn carbonyl_renderer_listen(bridge: RendererPtr, delegate: *mut B
     let bridge = unsafe { &*bridge };
     let delegate = unsafe { *delegate };
 
-    use input::*;
+    bridge.lock().unwrap().delegate = Some(delegate);
 
-    thread::spawn(move || {
-        macro_rules! emit {
-            ($event:ident($($args:expr),*) => $closure:expr) => {{
-                let run = move || {
-                    (delegate.$event)($($args),*);
+    // There's no input to read when capturing the page
+    if bridge.lock().unwrap().cmd.headless() {
+        return capture(bridge, CAPTURE_TIMEOUT);
+    }
 
-                    $closure
-                };
+    let control_socket = bridge.lock().unwrap().cmd.control_socket.clone();
 
-                unsafe { post_task(delegate.post_task, run) }
-            }};
-            ($event:ident($($args:expr),*)) => {{
-                emit!($event($($args),*) => {})
-            }};
+    if let Some(path) = control_socket {
+        match ControlServer::bind(&path, move |request| control(bridge, request)) {
+            Ok(server) => bridge.lock().unwrap().control = Some(server),
+            Err(error) => log::error!("failed to listen on {path}: {error}"),
         }
+    }
+
+    use input::*;
+
+    thread::spawn(move || {
+        let (record_input, replay_path, terminal) = {
+            let bridge = bridge.lock().unwrap();
+
+            (
+                bridge.cmd.record_input,
+                bridge.cmd.replay.clone(),
+                terminal::open(&bridge.cmd),
+            )
+        };
+        let handle = |mut events: Vec<Event>, input: &[u8]| {
+            let mut input = match record_input {
+                true => input.to_vec(),
+                false => Vec::new(),
+            };
 
-        listen(|mut events| {
             bridge.lock().unwrap().renderer.render(move |renderer| {
+                if !input.is_empty() {
+                    renderer.record_input(&std::mem::take(&mut input));
+                }
+
                 let get_scale = || bridge.lock().unwrap().window.scale;
                 let scale = |col, row| {
                     let scale = get_scale();
@@ -358,57 +1314,50 @@ pub extern "C" fn carbonyl_renderer_listen(bridge: RendererPtr, delegate: *mut B
                         .cast()
                         .into()
                 };
-                let dispatch = |action| {
-                    match action {
-                        NavigationAction::Ignore => (),
-                        NavigationAction::Forward => return true,
-                        NavigationAction::GoBack() => emit!(go_back()),
-                        NavigationAction::GoForward() => emit!(go_forward()),
-                        NavigationAction::Refresh() => emit!(refresh()),
-                        NavigationAction::GoTo(url) => {
-                            let c_str = CString::new(url).unwrap();
-
-                            emit!(go_to(c_str.as_ptr()))
-                        }
-                    };
-
-                    return false;
-                };
-
                 for event in std::mem::take(&mut events) {
                     use Event::*;
 
                     match event {
                         Exit => (),
                         Scroll { delta } => {
-                            let scale = get_scale();
+                            if dispatch(delegate, get_scale(), renderer.scroll(delta).unwrap()) {
+                                let scale = get_scale();
 
-                            emit!(scroll((delta as f32 * scale.height) as c_int))
+                                emit!(delegate, scroll((delta as f32 * scale.height) as c_int))
+                            }
                         }
                         KeyPress { key } => {
-                            if dispatch(renderer.keypress(&key).unwrap()) {
-                                emit!(key_press(key.char as c_char))
+                            if dispatch(delegate, get_scale(), renderer.keypress(&key).unwrap()) {
+                                key_press(delegate, &key)
                             }
                         }
                         MouseUp { col, row } => {
-                            if dispatch(renderer.mouse_up((col as _, row as _).into()).unwrap()) {
-                                let (width, height) = scale(col, row);
+                            let origin = renderer.layout_point((col as _, row as _).into());
+
+                            if dispatch(delegate, get_scale(), renderer.mouse_up(origin).unwrap()) {
+                                let (width, height) = scale(origin.x, origin.y);
 
-                                emit!(mouse_up(width, height))
+                                emit!(delegate, mouse_up(width, height))
                             }
                         }
                         MouseDown { col, row } => {
-                            if dispatch(renderer.mouse_down((col as _, row as _).into()).unwrap()) {
-                                let (width, height) = scale(col, row);
+                            let origin = renderer.layout_point((col as _, row as _).into());
 
-                                emit!(mouse_down(width, height))
+                            if dispatch(delegate, get_scale(), renderer.mouse_down(origin).unwrap())
+                            {
+                                let (width, height) = scale(origin.x, origin.y);
+
+                                emit!(delegate, mouse_down(width, height))
                             }
                         }
                         MouseMove { col, row } => {
-                            if dispatch(renderer.mouse_move((col as _, row as _).into()).unwrap()) {
-                                let (width, height) = scale(col, row);
+                            let origin = renderer.layout_point((col as _, row as _).into());
+
+                            if dispatch(delegate, get_scale(), renderer.mouse_move(origin).unwrap())
+                            {
+                                let (width, height) = scale(origin.x, origin.y);
 
-                                emit!(mouse_move(width, height))
+                                emit!(delegate, mouse_move(width, height))
                             }
                         }
                         Terminal(terminal) => match terminal {
@@ -419,18 +1368,33 @@ pub extern "C" fn carbonyl_renderer_listen(bridge: RendererPtr, delegate: *mut B
 
                                 renderer.enable_sixel(geometry)
                             }
+                            TerminalEvent::Clipboard(text) => {
+                                dispatch(delegate, get_scale(), renderer.paste(&text).unwrap());
+                            }
                         },
                     }
                 }
             })
-        })
-        .unwrap();
+        };
+
+        match replay_path {
+            Some(ref path) => {
+                if let Err(error) = replay(path, handle) {
+                    log::error!("failed to replay {path}: {error}");
+
+                    std::process::exit(1)
+                }
+
+                compare_golden(bridge);
+            }
+            None => listen(terminal.input(), handle).unwrap(),
+        }
 
         // Setup single-use channel
         let (tx, rx) = mpsc::channel();
 
         // Signal the browser to shutdown and notify our thread
-        emit!(shutdown() => tx.send(()).unwrap());
+        emit!(delegate, shutdown() => tx.send(()).unwrap());
         rx.recv().unwrap();
 
         // Shutdown rendering thread
@@ -0,0 +1,415 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::Shutdown;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::{fs, thread};
+
+use crate::utils::{json::Value, log};
+
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+/// Valid request the browser failed to execute
+const SERVER_ERROR: i32 = -32000;
+/// Number of messages waiting to be written before a client is dropped
+const QUEUE_SIZE: usize = 256;
+
+/// Command received on the control socket.
+#[derive(Clone, Debug)]
+pub enum Request {
+    /// Load a URL, `{"url"}`
+    Navigate(String),
+    Back,
+    Forward,
+    /// Reload the page, bypassing the cache if `{"hard": true}`
+    Reload {
+        hard: bool,
+    },
+    /// Scroll by `{"rows"}`, positive values scroll up
+    Scroll(isize),
+    /// Type `{"text"}` as if it was entered in the terminal
+    Keys(String),
+    /// Click the center of a cell of the window, `{"col", "row"}`
+    ClickCell(u32, u32),
+    /// Click at a point in browser pixels, `{"x", "y"}`
+    ClickPixel(u32, u32),
+    /// URL, title and loading state of the page
+    Status,
+    /// Save the page to `{"path"}`, in the format used by `--screenshot`
+    Screenshot(String),
+}
+
+/// Function executing the requests of every client.
+type Handler = dyn Fn(Request) -> Result<Value, String> + Send + Sync;
+type Clients = Arc<Mutex<Vec<Arc<Client>>>>;
+
+/// Connection of a client, written to by its own thread so a client that
+/// doesn't read its messages never blocks the browser.
+struct Client {
+    messages: SyncSender<String>,
+    stream: UnixStream,
+}
+
+impl Client {
+    fn connect(stream: UnixStream) -> io::Result<(Arc<Client>, UnixStream)> {
+        let (reader, mut writer) = (stream.try_clone()?, stream.try_clone()?);
+        let (messages, queue) = mpsc::sync_channel::<String>(QUEUE_SIZE);
+
+        thread::spawn(move || {
+            for message in queue {
+                if writer.write_all(message.as_bytes()).is_err() {
+                    break;
+                }
+            }
+
+            writer.shutdown(Shutdown::Both).ok();
+        });
+
+        Ok((Arc::new(Client { messages, stream }), reader))
+    }
+
+    /// Queue a message, disconnecting the client if it's too far behind.
+    fn send(&self, message: &Value) {
+        if self.messages.try_send(format!("{message}\n")).is_err() {
+            self.stream.shutdown(Shutdown::Both).ok();
+        }
+    }
+}
+
+struct Error {
+    code: i32,
+    message: String,
+}
+
+impl Request {
+    fn parse(method: &str, params: &Value) -> Result<Request, Error> {
+        let string = |name: &str| params.get(name).and_then(Value::as_str).map(str::to_owned);
+        let number = |name: &str| params.get(name).and_then(Value::as_f64);
+        let missing = |name: &str| Error {
+            code: INVALID_PARAMS,
+            message: format!("missing parameter {name}"),
+        };
+
+        Ok(match method {
+            "navigate" => Request::Navigate(string("url").ok_or_else(|| missing("url"))?),
+            "back" => Request::Back,
+            "forward" => Request::Forward,
+            "reload" => Request::Reload {
+                hard: params.get("hard").and_then(Value::as_bool).unwrap_or(false),
+            },
+            "scroll" => Request::Scroll(number("rows").ok_or_else(|| missing("rows"))? as isize),
+            "keys" => Request::Keys(string("text").ok_or_else(|| missing("text"))?),
+            "click" => match (number("col"), number("row"), number("x"), number("y")) {
+                (Some(col), Some(row), _, _) => Request::ClickCell(col as u32, row as u32),
+                (_, _, Some(x), Some(y)) => Request::ClickPixel(x as u32, y as u32),
+                _ => return Err(missing("col and row, or x and y")),
+            },
+            "status" => Request::Status,
+            "screenshot" => Request::Screenshot(string("path").ok_or_else(|| missing("path"))?),
+            _ => {
+                return Err(Error {
+                    code: METHOD_NOT_FOUND,
+                    message: format!("unknown method {method}"),
+                })
+            }
+        })
+    }
+}
+
+/// Unix socket accepting JSON-RPC 2.0 requests from other programs,
+/// one per line, enabled with `--control-socket`.
+///
+/// Events of the browser are sent to every client as notifications:
+/// `navigated` with the `url`, `title_changed` with the `title`,
+/// and `load_started` and `load_finished` with the `url`.
+#[derive(Clone)]
+pub struct ControlServer {
+    clients: Clients,
+}
+
+impl ControlServer {
+    /// Listen for clients, running the requests with the given handler.
+    pub fn bind<F>(path: &str, handler: F) -> io::Result<ControlServer>
+    where
+        F: Fn(Request) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        // Remove the socket left by a previous session
+        Self::unlink(path)?;
+
+        let listener = UnixListener::bind(path)?;
+
+        // Other users shouldn't be able to drive the browser
+        if let Err(error) = fs::set_permissions(path, fs::Permissions::from_mode(0o600)) {
+            Self::unlink(path)?;
+
+            return Err(error);
+        }
+
+        let clients: Clients = Default::default();
+        let handler: Arc<Handler> = Arc::new(handler);
+        let server = ControlServer {
+            clients: clients.clone(),
+        };
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let (client, reader) = match stream.and_then(Client::connect) {
+                    Ok(client) => client,
+                    Err(error) => {
+                        log::warning!("failed to accept a control client: {error}");
+
+                        continue;
+                    }
+                };
+                let (clients, handler) = (clients.clone(), handler.clone());
+
+                clients.lock().unwrap().push(client.clone());
+
+                thread::spawn(move || {
+                    serve(reader, &client, &*handler);
+
+                    clients
+                        .lock()
+                        .unwrap()
+                        .retain(|other| !Arc::ptr_eq(other, &client));
+                });
+            }
+        });
+
+        Ok(server)
+    }
+
+    /// Remove the socket file at `path`, if any.
+    pub fn unlink(path: &str) -> io::Result<()> {
+        if fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+            fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Send an event to every client.
+    pub fn notify(&self, method: &str, params: Value) {
+        let message = Value::object([
+            ("jsonrpc", "2.0".into()),
+            ("method", method.into()),
+            ("params", params),
+        ]);
+
+        for client in self.clients.lock().unwrap().iter() {
+            client.send(&message);
+        }
+    }
+}
+
+/// Answer the requests of a client until it disconnects.
+fn serve(reader: UnixStream, client: &Client, handler: &Handler) {
+    for line in BufReader::new(reader).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (id, result) = match Value::parse(&line) {
+            None => (
+                Some(Value::Null),
+                Err(Error {
+                    code: PARSE_ERROR,
+                    message: "invalid JSON".to_owned(),
+                }),
+            ),
+            Some(request) => (request.get("id").cloned(), call(&request, handler)),
+        };
+        // Requests without an identifier are notifications, they aren't answered
+        let Some(id) = id else {
+            continue;
+        };
+        let response = match result {
+            Ok(result) => {
+                Value::object([("jsonrpc", "2.0".into()), ("id", id), ("result", result)])
+            }
+            Err(error) => Value::object([
+                ("jsonrpc", "2.0".into()),
+                ("id", id),
+                (
+                    "error",
+                    Value::object([
+                        ("code", (error.code as f64).into()),
+                        ("message", error.message.into()),
+                    ]),
+                ),
+            ]),
+        };
+
+        client.send(&response);
+    }
+}
+
+fn call(request: &Value, handler: &Handler) -> Result<Value, Error> {
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return Err(Error {
+            code: INVALID_REQUEST,
+            message: "missing method".to_owned(),
+        });
+    };
+    let request = Request::parse(method, request.get("params").unwrap_or(&Value::Null))?;
+
+    handler(request).map_err(|message| Error {
+        code: SERVER_ERROR,
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    fn parse(request: &str) -> Result<Request, i32> {
+        let request = Value::parse(request).unwrap();
+        let method = request.get("method").and_then(Value::as_str).unwrap();
+
+        Request::parse(method, request.get("params").unwrap_or(&Value::Null))
+            .map_err(|error| error.code)
+    }
+
+    #[test]
+    fn parses_requests() {
+        assert!(matches!(
+            parse(r#"{"method": "navigate", "params": {"url": "https://a.test"}}"#),
+            Ok(Request::Navigate(url)) if url == "https://a.test"
+        ));
+        assert!(matches!(
+            parse(r#"{"method": "reload"}"#),
+            Ok(Request::Reload { hard: false })
+        ));
+        assert!(matches!(
+            parse(r#"{"method": "reload", "params": {"hard": true}}"#),
+            Ok(Request::Reload { hard: true })
+        ));
+        assert!(matches!(
+            parse(r#"{"method": "scroll", "params": {"rows": -3}}"#),
+            Ok(Request::Scroll(-3))
+        ));
+        assert!(matches!(
+            parse(r#"{"method": "click", "params": {"col": 2, "row": 5, "x": 1, "y": 1}}"#),
+            Ok(Request::ClickCell(2, 5))
+        ));
+        assert!(matches!(
+            parse(r#"{"method": "click", "params": {"x": 10, "y": 20}}"#),
+            Ok(Request::ClickPixel(10, 20))
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        let handler: &Handler = &|_| Ok(Value::Null);
+
+        assert!(matches!(
+            call(&Value::parse(r#"{"id": 1}"#).unwrap(), handler),
+            Err(Error {
+                code: INVALID_REQUEST,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse(r#"{"method": "teleport"}"#),
+            Err(METHOD_NOT_FOUND)
+        ));
+        assert!(matches!(
+            parse(r#"{"method": "navigate", "params": {"url": 1}}"#),
+            Err(INVALID_PARAMS)
+        ));
+        assert!(matches!(
+            parse(r#"{"method": "click", "params": {"col": 1}}"#),
+            Err(INVALID_PARAMS)
+        ));
+    }
+
+    #[test]
+    fn answers_clients() {
+        let path = env::temp_dir().join(format!("carbonyl-control-{}.sock", process::id()));
+        let path = path.to_str().unwrap();
+        let server = ControlServer::bind(path, |request| match request {
+            Request::Status => Ok(Value::object([("url", "about:blank".into())])),
+            Request::Navigate(url) => Err(format!("cannot open {url}")),
+            _ => Ok(Value::Null),
+        })
+        .unwrap();
+
+        assert_eq!(
+            fs::metadata(path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+
+        let mut stream = UnixStream::connect(path).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut response = || {
+            let mut line = String::new();
+
+            reader.read_line(&mut line).unwrap();
+            Value::parse(&line).unwrap()
+        };
+
+        stream
+            .write_all(
+                concat!(
+                    r#"{"jsonrpc": "2.0", "id": 1, "method": "status"}"#,
+                    "\n\nnot json\n",
+                    r#"{"jsonrpc": "2.0", "method": "back"}"#,
+                    "\n",
+                    r#"{"jsonrpc": "2.0", "id": "a", "method": "navigate", "params": {"url": "x"}}"#,
+                    "\n",
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            response(),
+            Value::object([
+                ("jsonrpc", "2.0".into()),
+                ("id", 1.0.into()),
+                ("result", Value::object([("url", "about:blank".into())])),
+            ])
+        );
+
+        let error = |id, code: i32, message: &str| {
+            Value::object([
+                ("jsonrpc", "2.0".into()),
+                ("id", id),
+                (
+                    "error",
+                    Value::object([("code", (code as f64).into()), ("message", message.into())]),
+                ),
+            ])
+        };
+
+        assert_eq!(response(), error(Value::Null, PARSE_ERROR, "invalid JSON"));
+        // The notification isn't answered
+        assert_eq!(response(), error("a".into(), SERVER_ERROR, "cannot open x"));
+
+        server.notify(
+            "navigated",
+            Value::object([("url", "https://a.test".into())]),
+        );
+        assert_eq!(
+            response(),
+            Value::object([
+                ("jsonrpc", "2.0".into()),
+                ("method", "navigated".into()),
+                ("params", Value::object([("url", "https://a.test".into())])),
+            ])
+        );
+
+        ControlServer::unlink(path).unwrap();
+        assert!(fs::symlink_metadata(path).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+#include "carbonyl/src/browser/dialog_manager.h"
+
+#include <utility>
+
+#include "base/no_destructor.h"
+#include "base/strings/utf_string_conversions.h"
+#include "carbonyl/src/browser/renderer.h"
+#include "content/public/common/javascript_dialog_type.h"
+
+namespace carbonyl {
+
+namespace {
+
+constexpr char kBeforeUnloadMessage[] =
+    "Leave this page? Changes you made may not be saved.";
+
+}  // namespace
+
+DialogManager* DialogManager::GetInstance() {
+  static base::NoDestructor<DialogManager> instance;
+
+  return instance.get();
+}
+
+DialogManager::DialogManager() = default;
+DialogManager::~DialogManager() = default;
+
+void DialogManager::OnResult(bool accepted, const std::string& value) {
+  if (!callback_) {
+    return;
+  }
+
+  web_contents_ = nullptr;
+  std::move(callback_).Run(accepted, base::UTF8ToUTF16(value));
+}
+
+void DialogManager::RunJavaScriptDialog(
+    content::WebContents* web_contents,
+    content::RenderFrameHost* render_frame_host,
+    content::JavaScriptDialogType dialog_type,
+    const std::u16string& message_text,
+    const std::u16string& default_prompt_text,
+    DialogClosedCallback callback,
+    bool* did_suppress_message) {
+  if (callback_) {
+    *did_suppress_message = true;
+
+    return;
+  }
+
+  auto kind = DialogKind::kAlert;
+
+  switch (dialog_type) {
+    case content::JAVASCRIPT_DIALOG_TYPE_ALERT:
+      kind = DialogKind::kAlert;
+      break;
+    case content::JAVASCRIPT_DIALOG_TYPE_CONFIRM:
+      kind = DialogKind::kConfirm;
+      break;
+    case content::JAVASCRIPT_DIALOG_TYPE_PROMPT:
+      kind = DialogKind::kPrompt;
+      break;
+  }
+
+  web_contents_ = web_contents;
+  callback_ = std::move(callback);
+
+  Renderer::GetCurrent()->ShowDialog(kind,
+                                     base::UTF16ToUTF8(message_text),
+                                     base::UTF16ToUTF8(default_prompt_text));
+}
+
+void DialogManager::RunBeforeUnloadDialog(
+    content::WebContents* web_contents,
+    content::RenderFrameHost* render_frame_host,
+    bool is_reload,
+    DialogClosedCallback callback) {
+  // Leaving is the only choice if the user can't be asked
+  if (callback_) {
+    std::move(callback).Run(true, std::u16string());
+
+    return;
+  }
+
+  web_contents_ = web_contents;
+  callback_ = std::move(callback);
+
+  Renderer::GetCurrent()->ShowDialog(DialogKind::kConfirm,
+                                     kBeforeUnloadMessage,
+                                     std::string());
+}
+
+void DialogManager::CancelDialogs(content::WebContents* web_contents,
+                                  bool reset_state) {
+  if (web_contents != web_contents_ || !callback_) {
+    return;
+  }
+
+  // The modal stays open, its result is ignored
+  web_contents_ = nullptr;
+  std::move(callback_).Run(false, std::u16string());
+}
+
+}  // namespace carbonyl
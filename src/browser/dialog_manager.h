@@ -0,0 +1,54 @@
+#ifndef CARBONYL_SRC_BROWSER_DIALOG_MANAGER_H_
+#define CARBONYL_SRC_BROWSER_DIALOG_MANAGER_H_
+
+#include <string>
+
+#include "base/memory/raw_ptr.h"
+#include "carbonyl/src/browser/export.h"
+#include "content/public/browser/javascript_dialog_manager.h"
+
+namespace carbonyl {
+
+// Shows the JavaScript dialogs of the pages as modals in the terminal UI.
+//
+// The terminal shows one dialog at a time, dialogs opened while another one
+// is shown are suppressed.
+class CARBONYL_CONTENTS_EXPORT DialogManager
+    : public content::JavaScriptDialogManager {
+ public:
+  static DialogManager* GetInstance();
+
+  DialogManager();
+  ~DialogManager() override;
+
+  // disable copy
+  DialogManager(const DialogManager&) = delete;
+  DialogManager& operator=(const DialogManager&) = delete;
+
+  // Answers the shown dialog with the choice of the user
+  void OnResult(bool accepted, const std::string& value);
+
+  // content::JavaScriptDialogManager
+  void RunJavaScriptDialog(content::WebContents* web_contents,
+                           content::RenderFrameHost* render_frame_host,
+                           content::JavaScriptDialogType dialog_type,
+                           const std::u16string& message_text,
+                           const std::u16string& default_prompt_text,
+                           DialogClosedCallback callback,
+                           bool* did_suppress_message) override;
+  void RunBeforeUnloadDialog(content::WebContents* web_contents,
+                             content::RenderFrameHost* render_frame_host,
+                             bool is_reload,
+                             DialogClosedCallback callback) override;
+  void CancelDialogs(content::WebContents* web_contents,
+                     bool reset_state) override;
+
+ private:
+  // Page showing the dialog
+  raw_ptr<content::WebContents> web_contents_ = nullptr;
+  DialogClosedCallback callback_;
+};
+
+}  // namespace carbonyl
+
+#endif  // CARBONYL_SRC_BROWSER_DIALOG_MANAGER_H_
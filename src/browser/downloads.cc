@@ -0,0 +1,198 @@
+#include "carbonyl/src/browser/downloads.h"
+
+#include <string>
+#include <utility>
+
+#include "base/files/file_util.h"
+#include "base/functional/bind.h"
+#include "base/json/json_writer.h"
+#include "base/no_destructor.h"
+#include "base/task/thread_pool.h"
+#include "base/values.h"
+#include "content/public/browser/browser_context.h"
+#include "content/public/browser/devtools_agent_host.h"
+
+namespace carbonyl {
+
+Downloads* Downloads::GetInstance() {
+  static base::NoDestructor<Downloads> instance;
+
+  return instance.get();
+}
+
+Downloads::Downloads() = default;
+Downloads::~Downloads() = default;
+
+void Downloads::Start(content::DevToolsAgentHost* host) {
+  base::Value::Dict params;
+  base::Value::Dict command;
+  std::string json;
+
+  params.Set("behavior", "allow");
+  params.Set("downloadPath",
+             base::GetHomeDir().Append("Downloads").AsUTF8Unsafe());
+  command.Set("id", 1);
+  command.Set("method", "Browser.setDownloadBehavior");
+  command.Set("params", std::move(params));
+  base::JSONWriter::Write(command, &json);
+
+  // The behavior is reset when the client detaches, stay attached
+  host->AttachClient(this);
+  host->DispatchProtocolMessage(this, base::as_bytes(base::make_span(json)));
+}
+
+void Downloads::Observe(content::BrowserContext* context) {
+  auto* manager = context->GetDownloadManager();
+
+  if (!managers_.IsObservingSource(manager)) {
+    managers_.AddObservation(manager);
+  }
+}
+
+void Downloads::SetDestination(unsigned int id, const base::FilePath& path) {
+  auto it = downloads_.find(id);
+
+  if (it == downloads_.end()) {
+    return;
+  }
+  if (path.empty()) {
+    it->second.item->Cancel(true);
+
+    return;
+  }
+
+  it->second.destination = path;
+
+  if (it->second.completed) {
+    Finish(it->second);
+  }
+}
+
+void Downloads::Control(unsigned int id, DownloadControl control) {
+  auto it = downloads_.find(id);
+
+  if (it == downloads_.end()) {
+    return;
+  }
+
+  auto* item = it->second.item.get();
+
+  switch (control) {
+    case DownloadControl::kPause:
+      item->Pause();
+      break;
+    case DownloadControl::kResume:
+      item->Resume(true);
+      break;
+    case DownloadControl::kCancel:
+      item->Cancel(true);
+      break;
+  }
+}
+
+void Downloads::DispatchProtocolMessage(content::DevToolsAgentHost* host,
+                                        base::span<const uint8_t> message) {}
+
+void Downloads::AgentHostClosed(content::DevToolsAgentHost* host) {}
+
+void Downloads::OnDownloadCreated(content::DownloadManager* manager,
+                                  download::DownloadItem* item) {
+  downloads_[item->GetId()].item = item;
+  item->AddObserver(this);
+  OnDownloadUpdated(item);
+}
+
+void Downloads::ManagerGoingDown(content::DownloadManager* manager) {
+  managers_.RemoveObservation(manager);
+}
+
+void Downloads::OnDownloadUpdated(download::DownloadItem* item) {
+  auto& download = downloads_[item->GetId()];
+  auto* renderer = Renderer::GetCurrent();
+
+  // The path is known once the DevTools delegate picked it
+  if (!download.started) {
+    if (item->GetTargetFilePath().empty()) {
+      return;
+    }
+
+    download.started = true;
+    renderer->DownloadStarted(item->GetId(), item->GetURL().spec(),
+                              item->GetTargetFilePath().AsUTF8Unsafe());
+  }
+
+  switch (item->GetState()) {
+    case download::DownloadItem::IN_PROGRESS:
+      renderer->DownloadProgress(
+          item->GetId(), item->GetReceivedBytes(),
+          item->GetTotalBytes() > 0 ? item->GetTotalBytes() : -1,
+          item->IsPaused());
+      break;
+    case download::DownloadItem::COMPLETE:
+      if (!download.completed) {
+        download.completed = true;
+
+        if (!download.destination.empty()) {
+          Finish(download);
+        }
+      }
+      break;
+    case download::DownloadItem::CANCELLED:
+      renderer->DownloadFinished(item->GetId(), DownloadState::kCancelled,
+                                 std::string());
+      item->RemoveObserver(this);
+      downloads_.erase(item->GetId());
+      break;
+    case download::DownloadItem::INTERRUPTED:
+      renderer->DownloadFinished(item->GetId(), DownloadState::kFailed,
+                                 std::string());
+      item->RemoveObserver(this);
+      downloads_.erase(item->GetId());
+      break;
+    case download::DownloadItem::MAX_DOWNLOAD_STATE:
+      break;
+  }
+}
+
+void Downloads::OnDownloadDestroyed(download::DownloadItem* item) {
+  item->RemoveObserver(this);
+  downloads_.erase(item->GetId());
+}
+
+void Downloads::Finish(Download& download) {
+  auto id = download.item->GetId();
+  auto from = download.item->GetTargetFilePath();
+  auto to = download.destination;
+
+  if (from == to) {
+    OnMoved(id, to, true);
+
+    return;
+  }
+
+  base::ThreadPool::PostTaskAndReplyWithResult(
+      FROM_HERE, { base::MayBlock() },
+      base::BindOnce(&base::Move, from, to),
+      base::BindOnce(&Downloads::OnMoved, weak_ptr_factory_.GetWeakPtr(), id,
+                     to));
+}
+
+void Downloads::OnMoved(unsigned int id, base::FilePath path, bool moved) {
+  auto it = downloads_.find(id);
+
+  if (it == downloads_.end()) {
+    return;
+  }
+
+  auto* item = it->second.item.get();
+
+  // The file is still complete where it was downloaded if it can't be moved
+  Renderer::GetCurrent()->DownloadFinished(
+      id,
+      DownloadState::kCompleted,
+      (moved ? path : item->GetTargetFilePath()).AsUTF8Unsafe());
+  item->RemoveObserver(this);
+  downloads_.erase(it);
+}
+
+}  // namespace carbonyl
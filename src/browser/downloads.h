@@ -0,0 +1,86 @@
+#ifndef CARBONYL_SRC_BROWSER_DOWNLOADS_H_
+#define CARBONYL_SRC_BROWSER_DOWNLOADS_H_
+
+#include <map>
+
+#include "base/files/file_path.h"
+#include "base/memory/raw_ptr.h"
+#include "base/memory/weak_ptr.h"
+#include "base/scoped_multi_source_observation.h"
+#include "carbonyl/src/browser/export.h"
+#include "carbonyl/src/browser/renderer.h"
+#include "components/download/public/common/download_item.h"
+#include "content/public/browser/devtools_agent_host_client.h"
+#include "content/public/browser/download_manager.h"
+
+namespace content {
+class BrowserContext;
+class DevToolsAgentHost;
+}
+
+namespace carbonyl {
+
+// Reports the downloads to the terminal UI and applies its choices.
+//
+// Headless contexts have no download manager delegate, the DevTools one
+// saves the files in ~/Downloads and they are moved to the location chosen
+// in the terminal once completed.
+class CARBONYL_CONTENTS_EXPORT Downloads
+    : public content::DevToolsAgentHostClient,
+      public content::DownloadManager::Observer,
+      public download::DownloadItem::Observer {
+ public:
+  static Downloads* GetInstance();
+
+  Downloads();
+  ~Downloads() override;
+
+  // disable copy
+  Downloads(const Downloads&) = delete;
+  Downloads& operator=(const Downloads&) = delete;
+
+  // Allows the downloads of the default context through the browser `host`
+  void Start(content::DevToolsAgentHost* host);
+  // Reports the downloads of `context`
+  void Observe(content::BrowserContext* context);
+  // Moves the download to `path` once completed, an empty path cancels it
+  void SetDestination(unsigned int id, const base::FilePath& path);
+  void Control(unsigned int id, DownloadControl control);
+
+  // content::DevToolsAgentHostClient
+  void DispatchProtocolMessage(content::DevToolsAgentHost* host,
+                               base::span<const uint8_t> message) override;
+  void AgentHostClosed(content::DevToolsAgentHost* host) override;
+
+  // content::DownloadManager::Observer
+  void OnDownloadCreated(content::DownloadManager* manager,
+                         download::DownloadItem* item) override;
+  void ManagerGoingDown(content::DownloadManager* manager) override;
+
+  // download::DownloadItem::Observer
+  void OnDownloadUpdated(download::DownloadItem* item) override;
+  void OnDownloadDestroyed(download::DownloadItem* item) override;
+
+ private:
+  struct Download {
+    raw_ptr<download::DownloadItem> item;
+    // Location chosen in the terminal, empty until then
+    base::FilePath destination;
+    bool started = false;
+    bool completed = false;
+  };
+
+  // Moves a completed download to its destination
+  void Finish(Download& download);
+  void OnMoved(unsigned int id, base::FilePath path, bool moved);
+
+  std::map<unsigned int, Download> downloads_;
+  base::ScopedMultiSourceObservation<content::DownloadManager,
+                                     content::DownloadManager::Observer>
+      managers_ { this };
+  base::WeakPtrFactory<Downloads> weak_ptr_factory_ { this };
+};
+
+}  // namespace carbonyl
+
+#endif  // CARBONYL_SRC_BROWSER_DOWNLOADS_H_
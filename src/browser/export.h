@@ -82,4 +82,31 @@
 
 #endif
 
+// CARBONYL_CONTENTS_EXPORT
+#if defined(COMPONENT_BUILD)
+
+#if defined(WIN32)
+
+#if defined(CARBONYL_CONTENTS_IMPLEMENTATION)
+#define CARBONYL_CONTENTS_EXPORT __declspec(dllexport)
+#else
+#define CARBONYL_CONTENTS_EXPORT __declspec(dllimport)
+#endif
+
+#else  // !defined(WIN32)
+
+#if defined(CARBONYL_CONTENTS_IMPLEMENTATION)
+#define CARBONYL_CONTENTS_EXPORT __attribute__((visibility("default")))
+#else
+#define CARBONYL_CONTENTS_EXPORT
+#endif
+
+#endif
+
+#else  // !defined(COMPONENT_BUILD)
+
+#define CARBONYL_CONTENTS_EXPORT
+
+#endif
+
 #endif  // CARBONYL_SRC_BROWSER_BRIDGE_EXPORT_H_
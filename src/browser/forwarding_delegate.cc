@@ -0,0 +1,451 @@
+#include "carbonyl/src/browser/forwarding_delegate.h"
+
+#include <utility>
+
+#include "content/public/browser/keyboard_event_processing_result.h"
+#include "content/public/browser/web_contents.h"
+
+namespace carbonyl {
+
+ForwardingDelegate::ForwardingDelegate() = default;
+ForwardingDelegate::~ForwardingDelegate() = default;
+
+content::WebContents* ForwardingDelegate::OpenURLFromTab(
+    content::WebContents* source,
+    const content::OpenURLParams& params) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::OpenURLFromTab(source, params);
+  }
+
+  return delegate_->OpenURLFromTab(source, params);
+}
+
+void ForwardingDelegate::NavigationStateChanged(
+    content::WebContents* source,
+    content::InvalidateTypes changed_flags) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::NavigationStateChanged(source,
+                                                                changed_flags);
+  }
+
+  delegate_->NavigationStateChanged(source, changed_flags);
+}
+
+void ForwardingDelegate::VisibleSecurityStateChanged(
+    content::WebContents* source) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::VisibleSecurityStateChanged(source);
+  }
+
+  delegate_->VisibleSecurityStateChanged(source);
+}
+
+void ForwardingDelegate::AddNewContents(
+    content::WebContents* source,
+    std::unique_ptr<content::WebContents> new_contents,
+    const GURL& target_url,
+    WindowOpenDisposition disposition,
+    const blink::mojom::WindowFeatures& window_features,
+    bool user_gesture,
+    bool* was_blocked) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::AddNewContents(
+        source, std::move(new_contents), target_url, disposition,
+        window_features, user_gesture, was_blocked);
+  }
+
+  delegate_->AddNewContents(
+      source, std::move(new_contents), target_url, disposition, window_features,
+      user_gesture, was_blocked);
+}
+
+void ForwardingDelegate::ActivateContents(content::WebContents* contents) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::ActivateContents(contents);
+  }
+
+  delegate_->ActivateContents(contents);
+}
+
+void ForwardingDelegate::LoadingStateChanged(content::WebContents* source,
+                                             bool should_show_loading_ui) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::LoadingStateChanged(
+        source, should_show_loading_ui);
+  }
+
+  delegate_->LoadingStateChanged(source, should_show_loading_ui);
+}
+
+void ForwardingDelegate::CloseContents(content::WebContents* source) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::CloseContents(source);
+  }
+
+  delegate_->CloseContents(source);
+}
+
+void ForwardingDelegate::SetContentsBounds(content::WebContents* source,
+                                           const gfx::Rect& bounds) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::SetContentsBounds(source, bounds);
+  }
+
+  delegate_->SetContentsBounds(source, bounds);
+}
+
+void ForwardingDelegate::UpdateTargetURL(content::WebContents* source,
+                                         const GURL& url) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::UpdateTargetURL(source, url);
+  }
+
+  delegate_->UpdateTargetURL(source, url);
+}
+
+bool ForwardingDelegate::TakeFocus(content::WebContents* source, bool reverse) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::TakeFocus(source, reverse);
+  }
+
+  return delegate_->TakeFocus(source, reverse);
+}
+
+void ForwardingDelegate::BeforeUnloadFired(content::WebContents* tab,
+                                           bool proceed,
+                                           bool* proceed_to_fire_unload) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::BeforeUnloadFired(
+        tab, proceed, proceed_to_fire_unload);
+  }
+
+  delegate_->BeforeUnloadFired(tab, proceed, proceed_to_fire_unload);
+}
+
+bool ForwardingDelegate::ShouldResumeRequestsForCreatedWindow() {
+  if (!delegate_) {
+    return content::WebContentsDelegate::ShouldResumeRequestsForCreatedWindow();
+  }
+
+  return delegate_->ShouldResumeRequestsForCreatedWindow();
+}
+
+content::KeyboardEventProcessingResult
+ForwardingDelegate::PreHandleKeyboardEvent(
+    content::WebContents* source,
+    const content::NativeWebKeyboardEvent& event) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::PreHandleKeyboardEvent(source, event);
+  }
+
+  return delegate_->PreHandleKeyboardEvent(source, event);
+}
+
+bool ForwardingDelegate::HandleKeyboardEvent(
+    content::WebContents* source,
+    const content::NativeWebKeyboardEvent& event) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::HandleKeyboardEvent(source, event);
+  }
+
+  return delegate_->HandleKeyboardEvent(source, event);
+}
+
+bool ForwardingDelegate::PreHandleGestureEvent(
+    content::WebContents* source,
+    const blink::WebGestureEvent& event) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::PreHandleGestureEvent(source, event);
+  }
+
+  return delegate_->PreHandleGestureEvent(source, event);
+}
+
+bool ForwardingDelegate::IsWebContentsCreationOverridden(
+    content::SiteInstance* source_site_instance,
+    content::mojom::WindowContainerType window_container_type,
+    const GURL& opener_url,
+    const std::string& frame_name,
+    const GURL& target_url) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::IsWebContentsCreationOverridden(
+        source_site_instance, window_container_type, opener_url, frame_name,
+        target_url);
+  }
+
+  return delegate_->IsWebContentsCreationOverridden(
+      source_site_instance, window_container_type, opener_url, frame_name,
+      target_url);
+}
+
+void ForwardingDelegate::WebContentsCreated(
+    content::WebContents* source_contents,
+    int opener_render_process_id,
+    int opener_render_frame_id,
+    const std::string& frame_name,
+    const GURL& target_url,
+    content::WebContents* new_contents) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::WebContentsCreated(
+        source_contents, opener_render_process_id, opener_render_frame_id,
+        frame_name, target_url, new_contents);
+  }
+
+  delegate_->WebContentsCreated(
+      source_contents, opener_render_process_id, opener_render_frame_id,
+      frame_name, target_url, new_contents);
+}
+
+void ForwardingDelegate::RendererUnresponsive(
+    content::WebContents* source,
+    content::RenderWidgetHost* render_widget_host,
+    base::RepeatingClosure hang_monitor_restarter) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::RendererUnresponsive(
+        source, render_widget_host, std::move(hang_monitor_restarter));
+  }
+
+  delegate_->RendererUnresponsive(source, render_widget_host,
+                                  std::move(hang_monitor_restarter));
+}
+
+void ForwardingDelegate::RendererResponsive(
+    content::WebContents* source,
+    content::RenderWidgetHost* render_widget_host) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::RendererResponsive(source,
+                                                            render_widget_host);
+  }
+
+  delegate_->RendererResponsive(source, render_widget_host);
+}
+
+void ForwardingDelegate::DidNavigatePrimaryMainFramePostCommit(
+    content::WebContents* source) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::DidNavigatePrimaryMainFramePostCommit(
+        source);
+  }
+
+  delegate_->DidNavigatePrimaryMainFramePostCommit(source);
+}
+
+content::JavaScriptDialogManager*
+ForwardingDelegate::GetJavaScriptDialogManager(
+    content::WebContents* source) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::GetJavaScriptDialogManager(source);
+  }
+
+  return delegate_->GetJavaScriptDialogManager(source);
+}
+
+bool ForwardingDelegate::HandleContextMenu(
+    content::RenderFrameHost& render_frame_host,
+    const content::ContextMenuParams& params) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::HandleContextMenu(render_frame_host,
+                                                           params);
+  }
+
+  return delegate_->HandleContextMenu(render_frame_host, params);
+}
+
+void ForwardingDelegate::RunFileChooser(
+    content::RenderFrameHost* render_frame_host,
+    scoped_refptr<content::FileSelectListener> listener,
+    const blink::mojom::FileChooserParams& params) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::RunFileChooser(
+        render_frame_host, std::move(listener), params);
+  }
+
+  delegate_->RunFileChooser(render_frame_host, std::move(listener), params);
+}
+
+void ForwardingDelegate::EnumerateDirectory(
+    content::WebContents* web_contents,
+    scoped_refptr<content::FileSelectListener> listener,
+    const base::FilePath& path) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::EnumerateDirectory(
+        web_contents, std::move(listener), path);
+  }
+
+  delegate_->EnumerateDirectory(web_contents, std::move(listener), path);
+}
+
+bool ForwardingDelegate::CanEnterFullscreenModeForTab(
+    content::RenderFrameHost* requesting_frame,
+    const blink::mojom::FullscreenOptions& options) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::CanEnterFullscreenModeForTab(
+        requesting_frame, options);
+  }
+
+  return delegate_->CanEnterFullscreenModeForTab(requesting_frame, options);
+}
+
+void ForwardingDelegate::EnterFullscreenModeForTab(
+    content::RenderFrameHost* requesting_frame,
+    const blink::mojom::FullscreenOptions& options) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::EnterFullscreenModeForTab(
+        requesting_frame, options);
+  }
+
+  delegate_->EnterFullscreenModeForTab(requesting_frame, options);
+}
+
+void ForwardingDelegate::ExitFullscreenModeForTab(
+    content::WebContents* web_contents) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::ExitFullscreenModeForTab(web_contents);
+  }
+
+  delegate_->ExitFullscreenModeForTab(web_contents);
+}
+
+bool ForwardingDelegate::IsFullscreenForTabOrPending(
+    const content::WebContents* web_contents) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::IsFullscreenForTabOrPending(
+        web_contents);
+  }
+
+  return delegate_->IsFullscreenForTabOrPending(web_contents);
+}
+
+blink::mojom::DisplayMode ForwardingDelegate::GetDisplayMode(
+    const content::WebContents* web_contents) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::GetDisplayMode(web_contents);
+  }
+
+  return delegate_->GetDisplayMode(web_contents);
+}
+
+void ForwardingDelegate::FindReply(content::WebContents* web_contents,
+                                   int request_id,
+                                   int number_of_matches,
+                                   const gfx::Rect& selection_rect,
+                                   int active_match_ordinal,
+                                   bool final_update) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::FindReply(
+        web_contents, request_id, number_of_matches, selection_rect,
+        active_match_ordinal, final_update);
+  }
+
+  delegate_->FindReply(web_contents, request_id, number_of_matches,
+                       selection_rect, active_match_ordinal, final_update);
+}
+
+void ForwardingDelegate::RequestToLockMouse(
+    content::WebContents* web_contents,
+    bool user_gesture,
+    bool last_unlocked_by_target) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::RequestToLockMouse(
+        web_contents, user_gesture, last_unlocked_by_target);
+  }
+
+  delegate_->RequestToLockMouse(web_contents, user_gesture,
+                                last_unlocked_by_target);
+}
+
+void ForwardingDelegate::LostMouseLock() {
+  if (!delegate_) {
+    return content::WebContentsDelegate::LostMouseLock();
+  }
+
+  delegate_->LostMouseLock();
+}
+
+void ForwardingDelegate::RequestKeyboardLock(
+    content::WebContents* web_contents,
+    bool esc_key_locked) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::RequestKeyboardLock(web_contents,
+                                                             esc_key_locked);
+  }
+
+  delegate_->RequestKeyboardLock(web_contents, esc_key_locked);
+}
+
+void ForwardingDelegate::CancelKeyboardLockRequest(
+    content::WebContents* web_contents) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::CancelKeyboardLockRequest(
+        web_contents);
+  }
+
+  delegate_->CancelKeyboardLockRequest(web_contents);
+}
+
+void ForwardingDelegate::RequestMediaAccessPermission(
+    content::WebContents* web_contents,
+    const content::MediaStreamRequest& request,
+    content::MediaResponseCallback callback) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::RequestMediaAccessPermission(
+        web_contents, request, std::move(callback));
+  }
+
+  delegate_->RequestMediaAccessPermission(web_contents, request,
+                                          std::move(callback));
+}
+
+bool ForwardingDelegate::CheckMediaAccessPermission(
+    content::RenderFrameHost* render_frame_host,
+    const GURL& security_origin,
+    blink::mojom::MediaStreamType type) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::CheckMediaAccessPermission(
+        render_frame_host, security_origin, type);
+  }
+
+  return delegate_->CheckMediaAccessPermission(render_frame_host,
+                                               security_origin, type);
+}
+
+std::string ForwardingDelegate::GetDefaultMediaDeviceID(
+    content::WebContents* web_contents,
+    blink::mojom::MediaStreamType type) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::GetDefaultMediaDeviceID(web_contents,
+                                                                 type);
+  }
+
+  return delegate_->GetDefaultMediaDeviceID(web_contents, type);
+}
+
+gfx::Size ForwardingDelegate::GetSizeForNewRenderView(
+    content::WebContents* web_contents) {
+  if (!delegate_) {
+    return content::WebContentsDelegate::GetSizeForNewRenderView(web_contents);
+  }
+
+  return delegate_->GetSizeForNewRenderView(web_contents);
+}
+
+bool ForwardingDelegate::IsBackForwardCacheSupported() {
+  if (!delegate_) {
+    return content::WebContentsDelegate::IsBackForwardCacheSupported();
+  }
+
+  return delegate_->IsBackForwardCacheSupported();
+}
+
+void ForwardingDelegate::ReplaceDelegate(content::WebContents* web_contents) {
+  auto* delegate = web_contents->GetDelegate();
+
+  if (delegate == this) {
+    return;
+  }
+
+  delegate_ = delegate;
+  web_contents->SetDelegate(this);
+}
+
+}  // namespace carbonyl
@@ -0,0 +1,142 @@
+#ifndef CARBONYL_SRC_BROWSER_FORWARDING_DELEGATE_H_
+#define CARBONYL_SRC_BROWSER_FORWARDING_DELEGATE_H_
+
+#include <memory>
+#include <string>
+
+#include "base/memory/raw_ptr.h"
+#include "carbonyl/src/browser/export.h"
+#include "content/public/browser/web_contents_delegate.h"
+
+namespace carbonyl {
+
+// Forwards every method of a page delegate to another delegate.
+//
+// Subclasses replace the delegate of a page and override the methods they
+// handle, the other ones keep the behavior of the replaced delegate, or the
+// default one if there was none.
+class CARBONYL_CONTENTS_EXPORT ForwardingDelegate
+    : public content::WebContentsDelegate {
+ public:
+  ForwardingDelegate();
+  ~ForwardingDelegate() override;
+
+  // disable copy
+  ForwardingDelegate(const ForwardingDelegate&) = delete;
+  ForwardingDelegate& operator=(const ForwardingDelegate&) = delete;
+
+  // content::WebContentsDelegate
+  content::WebContents* OpenURLFromTab(
+      content::WebContents* source,
+      const content::OpenURLParams& params) override;
+  void NavigationStateChanged(content::WebContents* source,
+                              content::InvalidateTypes changed_flags) override;
+  void VisibleSecurityStateChanged(content::WebContents* source) override;
+  void AddNewContents(content::WebContents* source,
+                      std::unique_ptr<content::WebContents> new_contents,
+                      const GURL& target_url,
+                      WindowOpenDisposition disposition,
+                      const blink::mojom::WindowFeatures& window_features,
+                      bool user_gesture,
+                      bool* was_blocked) override;
+  void ActivateContents(content::WebContents* contents) override;
+  void LoadingStateChanged(content::WebContents* source,
+                           bool should_show_loading_ui) override;
+  void CloseContents(content::WebContents* source) override;
+  void SetContentsBounds(content::WebContents* source,
+                         const gfx::Rect& bounds) override;
+  void UpdateTargetURL(content::WebContents* source, const GURL& url) override;
+  bool TakeFocus(content::WebContents* source, bool reverse) override;
+  void BeforeUnloadFired(content::WebContents* tab,
+                         bool proceed,
+                         bool* proceed_to_fire_unload) override;
+  bool ShouldResumeRequestsForCreatedWindow() override;
+  content::KeyboardEventProcessingResult PreHandleKeyboardEvent(
+      content::WebContents* source,
+      const content::NativeWebKeyboardEvent& event) override;
+  bool HandleKeyboardEvent(
+      content::WebContents* source,
+      const content::NativeWebKeyboardEvent& event) override;
+  bool PreHandleGestureEvent(content::WebContents* source,
+                             const blink::WebGestureEvent& event) override;
+  bool IsWebContentsCreationOverridden(
+      content::SiteInstance* source_site_instance,
+      content::mojom::WindowContainerType window_container_type,
+      const GURL& opener_url,
+      const std::string& frame_name,
+      const GURL& target_url) override;
+  void WebContentsCreated(content::WebContents* source_contents,
+                          int opener_render_process_id,
+                          int opener_render_frame_id,
+                          const std::string& frame_name,
+                          const GURL& target_url,
+                          content::WebContents* new_contents) override;
+  void RendererUnresponsive(
+      content::WebContents* source,
+      content::RenderWidgetHost* render_widget_host,
+      base::RepeatingClosure hang_monitor_restarter) override;
+  void RendererResponsive(
+      content::WebContents* source,
+      content::RenderWidgetHost* render_widget_host) override;
+  void DidNavigatePrimaryMainFramePostCommit(
+      content::WebContents* source) override;
+  content::JavaScriptDialogManager* GetJavaScriptDialogManager(
+      content::WebContents* source) override;
+  bool HandleContextMenu(content::RenderFrameHost& render_frame_host,
+                         const content::ContextMenuParams& params) override;
+  void RunFileChooser(content::RenderFrameHost* render_frame_host,
+                      scoped_refptr<content::FileSelectListener> listener,
+                      const blink::mojom::FileChooserParams& params) override;
+  void EnumerateDirectory(content::WebContents* web_contents,
+                          scoped_refptr<content::FileSelectListener> listener,
+                          const base::FilePath& path) override;
+  bool CanEnterFullscreenModeForTab(
+      content::RenderFrameHost* requesting_frame,
+      const blink::mojom::FullscreenOptions& options) override;
+  void EnterFullscreenModeForTab(
+      content::RenderFrameHost* requesting_frame,
+      const blink::mojom::FullscreenOptions& options) override;
+  void ExitFullscreenModeForTab(content::WebContents* web_contents) override;
+  bool IsFullscreenForTabOrPending(
+      const content::WebContents* web_contents) override;
+  blink::mojom::DisplayMode GetDisplayMode(
+      const content::WebContents* web_contents) override;
+  void FindReply(content::WebContents* web_contents,
+                 int request_id,
+                 int number_of_matches,
+                 const gfx::Rect& selection_rect,
+                 int active_match_ordinal,
+                 bool final_update) override;
+  void RequestToLockMouse(content::WebContents* web_contents,
+                          bool user_gesture,
+                          bool last_unlocked_by_target) override;
+  void LostMouseLock() override;
+  void RequestKeyboardLock(content::WebContents* web_contents,
+                           bool esc_key_locked) override;
+  void CancelKeyboardLockRequest(content::WebContents* web_contents) override;
+  void RequestMediaAccessPermission(
+      content::WebContents* web_contents,
+      const content::MediaStreamRequest& request,
+      content::MediaResponseCallback callback) override;
+  bool CheckMediaAccessPermission(content::RenderFrameHost* render_frame_host,
+                                  const GURL& security_origin,
+                                  blink::mojom::MediaStreamType type) override;
+  std::string GetDefaultMediaDeviceID(
+      content::WebContents* web_contents,
+      blink::mojom::MediaStreamType type) override;
+  gfx::Size GetSizeForNewRenderView(
+      content::WebContents* web_contents) override;
+  bool IsBackForwardCacheSupported() override;
+
+ protected:
+  // Replaces the delegate of `web_contents` with this one, the methods are
+  // forwarded to the replaced delegate. Does nothing if it's already done.
+  void ReplaceDelegate(content::WebContents* web_contents);
+
+ private:
+  raw_ptr<content::WebContentsDelegate> delegate_ = nullptr;
+};
+
+}  // namespace carbonyl
+
+#endif  // CARBONYL_SRC_BROWSER_FORWARDING_DELEGATE_H_
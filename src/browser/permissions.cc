@@ -0,0 +1,64 @@
+#include "carbonyl/src/browser/permissions.h"
+
+#include <utility>
+
+#include "base/functional/bind.h"
+#include "base/no_destructor.h"
+
+namespace carbonyl {
+
+Permissions* Permissions::GetInstance() {
+  static base::NoDestructor<Permissions> instance;
+
+  return instance.get();
+}
+
+Permissions::Permissions() = default;
+Permissions::~Permissions() = default;
+
+void Permissions::Request(const url::Origin& origin,
+                          std::vector<Permission> permissions,
+                          Callback callback) {
+  if (permissions.empty()) {
+    std::move(callback).Run(true);
+
+    return;
+  }
+
+  auto id = next_id_++;
+  auto permission = permissions.front();
+
+  permissions.erase(permissions.begin());
+  callbacks_[id] = base::BindOnce(
+      [](url::Origin origin, std::vector<Permission> permissions,
+         Callback callback, bool allowed) {
+        if (!allowed) {
+          std::move(callback).Run(false);
+
+          return;
+        }
+
+        Permissions::GetInstance()->Request(origin, std::move(permissions),
+                                            std::move(callback));
+      },
+      origin, std::move(permissions), std::move(callback));
+
+  // The renderer may answer right away if the user already decided
+  Renderer::GetCurrent()->RequestPermission(id, origin.Serialize(),
+                                            permission);
+}
+
+void Permissions::OnResult(unsigned int id, bool allowed) {
+  auto it = callbacks_.find(id);
+
+  if (it == callbacks_.end()) {
+    return;
+  }
+
+  auto callback = std::move(it->second);
+
+  callbacks_.erase(it);
+  std::move(callback).Run(allowed);
+}
+
+}  // namespace carbonyl
@@ -0,0 +1,43 @@
+#ifndef CARBONYL_SRC_BROWSER_PERMISSIONS_H_
+#define CARBONYL_SRC_BROWSER_PERMISSIONS_H_
+
+#include <map>
+#include <vector>
+
+#include "base/functional/callback.h"
+#include "carbonyl/src/browser/export.h"
+#include "carbonyl/src/browser/renderer.h"
+#include "url/origin.h"
+
+namespace carbonyl {
+
+// Asks the terminal UI for the permissions requested by the pages.
+class CARBONYL_CONTENTS_EXPORT Permissions {
+ public:
+  using Callback = base::OnceCallback<void(bool allowed)>;
+
+  static Permissions* GetInstance();
+
+  Permissions();
+  ~Permissions();
+
+  // disable copy
+  Permissions(const Permissions&) = delete;
+  Permissions& operator=(const Permissions&) = delete;
+
+  // Asks for each permission in turn, `callback` gets whether all of them
+  // were allowed
+  void Request(const url::Origin& origin,
+               std::vector<Permission> permissions,
+               Callback callback);
+  // Answers the request `id` with the choice of the user
+  void OnResult(unsigned int id, bool allowed);
+
+ private:
+  std::map<unsigned int, Callback> callbacks_;
+  unsigned int next_id_ = 0;
+};
+
+}  // namespace carbonyl
+
+#endif  // CARBONYL_SRC_BROWSER_PERMISSIONS_H_
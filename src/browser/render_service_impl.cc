@@ -17,7 +17,15 @@ void CarbonylRenderServiceImpl::DrawText(std::vector<mojom::TextDataPtr> data) {
     std::vector<Text> mapped;
 
     for (auto& text: data) {
-        mapped.emplace_back(text->contents, text->bounds, text->color);
+        mapped.emplace_back(
+            text->contents,
+            text->bounds,
+            text->color,
+            text->weight,
+            text->style,
+            text->decoration_color,
+            text->href
+        );
     }
 
     Renderer::GetCurrent()->DrawText(mapped);
@@ -32,6 +32,22 @@ struct carbonyl_renderer_text {
     const char* text;
     carbonyl_renderer_rect rect;
     carbonyl_renderer_color color;
+    unsigned int weight;
+    uint8_t style;
+    carbonyl_renderer_color decoration_color;
+    const char* href;
+};
+struct carbonyl_renderer_reader_span {
+    const char* text;
+    const char* href;
+    unsigned int block;
+    uint8_t kind;
+    uint8_t level;
+    uint8_t style;
+};
+struct carbonyl_renderer_menu_item {
+    const char* label;
+    uint8_t flags;
 };
 
 void carbonyl_bridge_main();
@@ -51,6 +67,77 @@ void carbonyl_renderer_draw_text(
     const struct carbonyl_renderer_text* text,
     size_t text_size
 );
+void carbonyl_renderer_show_hints(
+    struct carbonyl_renderer* renderer,
+    const struct carbonyl_renderer_rect* rects,
+    size_t rects_size
+);
+void carbonyl_renderer_find_result(
+    struct carbonyl_renderer* renderer,
+    unsigned int current,
+    unsigned int total
+);
+void carbonyl_renderer_show_reader(
+    struct carbonyl_renderer* renderer,
+    const char* title,
+    const struct carbonyl_renderer_reader_span* spans,
+    size_t spans_size
+);
+void carbonyl_renderer_copy(struct carbonyl_renderer* renderer, const char* text);
+void carbonyl_renderer_show_dialog(
+    struct carbonyl_renderer* renderer,
+    unsigned int kind,
+    const char* message,
+    const char* default_value
+);
+void carbonyl_renderer_show_menu(
+    struct carbonyl_renderer* renderer,
+    const struct carbonyl_renderer_rect rect,
+    const struct carbonyl_renderer_menu_item* items,
+    size_t items_size,
+    int selected
+);
+void carbonyl_renderer_show_file_chooser(
+    struct carbonyl_renderer* renderer,
+    const char* dir,
+    const char* accept,
+    bool multiple
+);
+void carbonyl_renderer_set_loading(struct carbonyl_renderer* renderer, bool loading, float progress);
+void carbonyl_renderer_set_hover_url(struct carbonyl_renderer* renderer, const char* url);
+void carbonyl_renderer_set_security_state(struct carbonyl_renderer* renderer, unsigned int state);
+void carbonyl_renderer_download_started(
+    struct carbonyl_renderer* renderer,
+    unsigned int id,
+    const char* url,
+    const char* path
+);
+void carbonyl_renderer_download_progress(
+    struct carbonyl_renderer* renderer,
+    unsigned int id,
+    uint64_t received,
+    int64_t total,
+    bool paused
+);
+void carbonyl_renderer_download_finished(
+    struct carbonyl_renderer* renderer,
+    unsigned int id,
+    unsigned int state,
+    const char* path
+);
+void carbonyl_renderer_request_permission(
+    struct carbonyl_renderer* renderer,
+    unsigned int id,
+    const char* origin,
+    unsigned int permission
+);
+void carbonyl_renderer_auth_challenge(
+    struct carbonyl_renderer* renderer,
+    unsigned int id,
+    const char* origin,
+    const char* realm,
+    bool proxy
+);
 void carbonyl_renderer_draw_bitmap(
     struct carbonyl_renderer* renderer,
     const unsigned char* pixels,
@@ -138,11 +225,119 @@ void Renderer::DrawText(const std::vector<Text>& text) {
         data[i].rect.origin.y = text[i].rect.y();
         data[i].rect.size.width = std::ceil(text[i].rect.width());
         data[i].rect.size.height = std::ceil(text[i].rect.height());
+        data[i].weight = text[i].weight;
+        data[i].style = text[i].style;
+        data[i].decoration_color.r = SkColorGetR(text[i].decoration_color);
+        data[i].decoration_color.g = SkColorGetG(text[i].decoration_color);
+        data[i].decoration_color.b = SkColorGetB(text[i].decoration_color);
+        data[i].href = text[i].href.empty() ? nullptr : text[i].href.c_str();
     }
 
     carbonyl_renderer_draw_text(ptr_, data, text.size());
 }
 
+void Renderer::ShowHints(const std::vector<gfx::RectF>& rects) {
+    struct carbonyl_renderer_rect data[rects.size()];
+
+    for (size_t i = 0; i < rects.size(); i++) {
+        data[i].origin.x = std::max(rects[i].x(), 0.0f);
+        data[i].origin.y = std::max(rects[i].y(), 0.0f);
+        data[i].size.width = std::ceil(rects[i].width());
+        data[i].size.height = std::ceil(rects[i].height());
+    }
+
+    carbonyl_renderer_show_hints(ptr_, data, rects.size());
+}
+
+void Renderer::SetFindResult(unsigned int current, unsigned int total) {
+    carbonyl_renderer_find_result(ptr_, current, total);
+}
+
+void Renderer::ShowReader(const std::string& title, const std::vector<ReaderSpan>& spans) {
+    struct carbonyl_renderer_reader_span data[spans.size()];
+
+    for (size_t i = 0; i < spans.size(); i++) {
+        data[i].text = spans[i].text.c_str();
+        data[i].href = spans[i].href.empty() ? nullptr : spans[i].href.c_str();
+        data[i].block = spans[i].block;
+        data[i].kind = spans[i].kind;
+        data[i].level = spans[i].level;
+        data[i].style = spans[i].style;
+    }
+
+    carbonyl_renderer_show_reader(ptr_, title.c_str(), data, spans.size());
+}
+
+void Renderer::Copy(const std::string& text) {
+    carbonyl_renderer_copy(ptr_, text.c_str());
+}
+
+void Renderer::ShowDialog(DialogKind kind, const std::string& message, const std::string& default_value) {
+    carbonyl_renderer_show_dialog(ptr_, static_cast<unsigned int>(kind), message.c_str(), default_value.c_str());
+}
+
+void Renderer::ShowMenu(const gfx::RectF& rect, const std::vector<MenuItem>& items, int selected) {
+    struct carbonyl_renderer_menu_item data[items.size()];
+    struct carbonyl_renderer_rect anchor = {
+        .origin = {
+            .x = (unsigned int)std::max(rect.x(), 0.0f),
+            .y = (unsigned int)std::max(rect.y(), 0.0f),
+        },
+        .size = {
+            .width = (unsigned int)std::ceil(rect.width()),
+            .height = (unsigned int)std::ceil(rect.height()),
+        },
+    };
+
+    for (size_t i = 0; i < items.size(); i++) {
+        data[i].label = items[i].label.c_str();
+        data[i].flags = items[i].flags;
+    }
+
+    carbonyl_renderer_show_menu(ptr_, anchor, data, items.size(), selected);
+}
+
+void Renderer::ShowFileChooser(const std::string& dir, const std::string& accept, bool multiple) {
+    carbonyl_renderer_show_file_chooser(
+        ptr_,
+        dir.empty() ? nullptr : dir.c_str(),
+        accept.c_str(),
+        multiple
+    );
+}
+
+void Renderer::SetLoading(bool loading, float progress) {
+    carbonyl_renderer_set_loading(ptr_, loading, progress);
+}
+
+void Renderer::SetHoverUrl(const std::string& url) {
+    carbonyl_renderer_set_hover_url(ptr_, url.c_str());
+}
+
+void Renderer::SetSecurityState(SecurityState state) {
+    carbonyl_renderer_set_security_state(ptr_, static_cast<unsigned int>(state));
+}
+
+void Renderer::DownloadStarted(unsigned int id, const std::string& url, const std::string& path) {
+    carbonyl_renderer_download_started(ptr_, id, url.c_str(), path.c_str());
+}
+
+void Renderer::DownloadProgress(unsigned int id, uint64_t received, int64_t total, bool paused) {
+    carbonyl_renderer_download_progress(ptr_, id, received, total, paused);
+}
+
+void Renderer::DownloadFinished(unsigned int id, DownloadState state, const std::string& path) {
+    carbonyl_renderer_download_finished(ptr_, id, static_cast<unsigned int>(state), path.c_str());
+}
+
+void Renderer::RequestPermission(unsigned int id, const std::string& origin, Permission permission) {
+    carbonyl_renderer_request_permission(ptr_, id, origin.c_str(), static_cast<unsigned int>(permission));
+}
+
+void Renderer::AuthChallenge(unsigned int id, const std::string& origin, const std::string& realm, bool proxy) {
+    carbonyl_renderer_auth_challenge(ptr_, id, origin.c_str(), realm.c_str(), proxy);
+}
+
 void Renderer::DrawBitmap(
     const unsigned char* pixels,
     const gfx::Size& pixels_size,
@@ -23,6 +23,27 @@ struct carbonyl_renderer_browser_delegate {
     void (*mouse_down) (unsigned int, unsigned int);
     void (*mouse_move) (unsigned int, unsigned int);
     void (*post_task) (void (*)(void*), void*);
+
+    // Optional callbacks, can be left null
+    void (*hard_refresh) ();
+    void (*zoom) (float);
+    void (*request_hints) ();
+    void (*find) (const char*, bool);
+    void (*request_reader) ();
+    void (*copy_selection) ();
+    void (*paste) (const char*);
+    void (*dialog_result) (bool accepted, const char* value);
+    // Index of the picked item, -1 if the menu was dismissed
+    void (*menu_result) (int index);
+    // Chosen paths, none if the file chooser was cancelled
+    void (*file_chooser_result) (const char* const* paths, size_t paths_size);
+    // Location chosen for a download, empty if it was cancelled
+    void (*download_destination) (unsigned int id, const char* path);
+    // One of `DownloadControl`
+    void (*download_control) (unsigned int id, unsigned int control);
+    void (*permission_result) (unsigned int id, bool allowed);
+    // Null pointers if the challenge was cancelled
+    void (*auth_result) (unsigned int id, const char* username, const char* password);
 };
 
 } /* end extern "C" */
@@ -30,19 +51,111 @@ struct carbonyl_renderer_browser_delegate {
 namespace carbonyl {
 
 struct CARBONYL_RENDERER_EXPORT Text {
+    enum Style : uint8_t {
+        kItalic = 1 << 0,
+        kUnderline = 1 << 1,
+        kLineThrough = 1 << 2,
+        kDecorationColor = 1 << 3,
+    };
+
     Text(
         std::string text,
         gfx::RectF rect,
-        uint32_t color
+        uint32_t color,
+        unsigned int weight = 400,
+        uint8_t style = 0,
+        uint32_t decoration_color = 0,
+        std::string href = ""
     ):
         text(text),
         rect(rect),
-        color(color)
+        color(color),
+        weight(weight),
+        style(style),
+        decoration_color(decoration_color),
+        href(href)
     {}
 
     std::string text;
     gfx::RectF rect;
     uint32_t color;
+    // CSS font weight
+    unsigned int weight;
+    uint8_t style;
+    // Only used if the style has `kDecorationColor`
+    uint32_t decoration_color;
+    // Target of the link containing the text, empty if none
+    std::string href;
+};
+
+struct CARBONYL_RENDERER_EXPORT ReaderSpan {
+    enum Kind : uint8_t {
+        kParagraph = 0,
+        kHeading = 1,
+        kListItem = 2,
+        kOrderedListItem = 3,
+        kCode = 4,
+        kQuote = 5,
+    };
+    enum Style : uint8_t {
+        kBold = 1 << 0,
+        kItalic = 1 << 1,
+        kInlineCode = 1 << 2,
+    };
+
+    std::string text;
+    // Empty if the span isn't a link
+    std::string href;
+    // Index of the block containing the span
+    unsigned int block;
+    Kind kind;
+    // Heading level or list nesting depth
+    uint8_t level;
+    uint8_t style;
+};
+
+struct CARBONYL_RENDERER_EXPORT MenuItem {
+    enum Flags : uint8_t {
+        kDisabled = 1 << 0,
+        kSeparator = 1 << 1,
+        kChecked = 1 << 2,
+    };
+
+    std::string label;
+    uint8_t flags = 0;
+};
+
+enum class DialogKind : unsigned int {
+    kAlert = 0,
+    kConfirm = 1,
+    kPrompt = 2,
+};
+
+enum class SecurityState : unsigned int {
+    kNone = 0,
+    kSecure = 1,
+    kInsecure = 2,
+    kCertificateError = 3,
+};
+
+enum class DownloadState : unsigned int {
+    kCompleted = 0,
+    kCancelled = 1,
+    kFailed = 2,
+};
+
+enum class DownloadControl : unsigned int {
+    kPause = 0,
+    kResume = 1,
+    kCancel = 2,
+};
+
+enum class Permission : unsigned int {
+    kGeolocation = 0,
+    kNotifications = 1,
+    kCamera = 2,
+    kMicrophone = 3,
+    kClipboard = 4,
 };
 
 class CARBONYL_RENDERER_EXPORT Renderer {
@@ -58,6 +171,24 @@ public:
     void PushNav(const std::string& url, bool can_go_back, bool can_go_forward);
     void SetTitle(const std::string& title);
     void DrawText(const std::vector<Text>& text);
+    void ShowHints(const std::vector<gfx::RectF>& rects);
+    void SetFindResult(unsigned int current, unsigned int total);
+    void ShowReader(const std::string& title, const std::vector<ReaderSpan>& spans);
+    void Copy(const std::string& text);
+    void ShowDialog(DialogKind kind, const std::string& message, const std::string& default_value);
+    void ShowMenu(const gfx::RectF& rect, const std::vector<MenuItem>& items, int selected);
+    void ShowFileChooser(const std::string& dir, const std::string& accept, bool multiple);
+    void SetLoading(bool loading, float progress);
+    void SetHoverUrl(const std::string& url);
+    void SetSecurityState(SecurityState state);
+    void DownloadStarted(unsigned int id, const std::string& url, const std::string& path);
+    // `total` is -1 if the size is unknown
+    void DownloadProgress(unsigned int id, uint64_t received, int64_t total, bool paused);
+    void DownloadFinished(unsigned int id, DownloadState state, const std::string& path);
+    // Answered with `permission_result`, right away if the user already decided
+    void RequestPermission(unsigned int id, const std::string& origin, Permission permission);
+    // Answered with `auth_result`, right away if the credentials file has a login
+    void AuthChallenge(unsigned int id, const std::string& origin, const std::string& realm, bool proxy);
     void DrawBitmap(
         const unsigned char* pixels,
         const gfx::Size& size,
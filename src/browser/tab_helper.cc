@@ -0,0 +1,702 @@
+#include "carbonyl/src/browser/tab_helper.h"
+
+#include <utility>
+#include <vector>
+
+#include "base/functional/bind.h"
+#include "base/functional/callback_helpers.h"
+#include "base/json/string_escape.h"
+#include "base/strings/string_number_conversions.h"
+#include "base/strings/string_util.h"
+#include "base/strings/utf_string_conversions.h"
+#include "carbonyl/src/browser/dialog_manager.h"
+#include "carbonyl/src/browser/downloads.h"
+#include "carbonyl/src/browser/permissions.h"
+#include "content/public/browser/context_menu_params.h"
+#include "content/public/browser/host_zoom_map.h"
+#include "content/public/browser/navigation_controller.h"
+#include "content/public/browser/navigation_entry.h"
+#include "content/public/browser/navigation_handle.h"
+#include "content/public/browser/page_navigator.h"
+#include "content/public/browser/reload_type.h"
+#include "content/public/browser/render_frame_host.h"
+#include "content/public/browser/ssl_status.h"
+#include "content/public/browser/web_contents.h"
+#include "content/public/common/isolated_world_ids.h"
+#include "content/public/common/referrer.h"
+#include "net/cert/cert_status_flags.h"
+#include "third_party/blink/public/common/mediastream/media_stream_request.h"
+#include "third_party/blink/public/common/page/page_zoom.h"
+#include "third_party/blink/public/mojom/frame/find_in_page.mojom.h"
+#include "ui/base/page_transition_types.h"
+#include "ui/base/window_open_disposition.h"
+#include "url/gurl.h"
+#include "url/origin.h"
+#include "url/url_constants.h"
+
+namespace carbonyl {
+
+namespace {
+
+constexpr int32_t kIsolatedWorldId = content::ISOLATED_WORLD_ID_CONTENT_END + 1;
+
+// Returns the [x, y, width, height] of the visible clickable elements
+constexpr char16_t kHintsScript[] = uR"JS((() => {
+  const selector = [
+    'a[href]', 'area[href]', 'button', 'input:not([type=hidden])', 'select',
+    'textarea', 'summary', 'label[for]', '[onclick]', '[contenteditable]',
+    '[role=button]', '[role=link]', '[role=checkbox]', '[role=radio]',
+    '[role=tab]', '[role=menuitem]', '[role=option]', '[tabindex]:not([tabindex="-1"])',
+  ].join(',');
+  const width = innerWidth;
+  const height = innerHeight;
+  const rects = [];
+
+  for (const element of document.querySelectorAll(selector)) {
+    if (element.disabled || element.closest('[inert], [aria-hidden=true]')) {
+      continue;
+    }
+
+    for (const rect of element.getClientRects()) {
+      if (
+        rect.width < 1 || rect.height < 1 ||
+        rect.right <= 0 || rect.bottom <= 0 ||
+        rect.left >= width || rect.top >= height
+      ) {
+        continue;
+      }
+
+      // Skip elements hidden behind another one
+      const x = (Math.max(rect.left, 0) + Math.min(rect.right, width)) / 2;
+      const y = (Math.max(rect.top, 0) + Math.min(rect.bottom, height)) / 2;
+      const target = document.elementFromPoint(x, y);
+
+      if (target && (element.contains(target) || target.contains(element))) {
+        rects.push([rect.left, rect.top, rect.width, rect.height]);
+
+        break;
+      }
+    }
+  }
+
+  return rects;
+})())JS";
+
+// Returns the title and the text of the main content as
+// [text, href, block, kind, level, style] spans, see `ReaderSpan`
+constexpr char16_t kReaderScript[] = uR"JS((() => {
+  const candidates = [...document.querySelectorAll('article, main, [role=main]')];
+  const root = candidates.sort(
+    (a, b) => b.textContent.length - a.textContent.length
+  )[0] || document.body;
+  const skip = [
+    'nav', 'header', 'footer', 'aside', 'form', 'button', 'dialog', 'script',
+    'style', 'noscript', 'template', 'svg', 'iframe', '[hidden]',
+    '[aria-hidden=true]', '[role=navigation]', '[role=banner]',
+    '[role=contentinfo]', '[role=complementary]',
+  ].join(',');
+  const spans = [];
+  let block = -1;
+  let open = false;
+
+  const depth = (item) => {
+    let depth = -1;
+
+    for (let node = item; node && node !== root; node = node.parentElement) {
+      if (node.localName === 'ul' || node.localName === 'ol') {
+        depth++;
+      }
+    }
+
+    return Math.max(depth, 0);
+  };
+  const walk = (node, kind, level, style, href) => {
+    for (const child of node.childNodes) {
+      if (spans.length >= 10000) {
+        return;
+      }
+
+      if (child.nodeType === Node.TEXT_NODE) {
+        let text = kind === 4 ? child.data : child.data.replace(/\s+/g, ' ');
+
+        if (!open) {
+          if (!text.trim()) {
+            continue;
+          }
+          if (kind !== 4) {
+            text = text.trimStart();
+          }
+
+          block++;
+          open = true;
+        }
+
+        spans.push([text, href, block, kind, level, style]);
+
+        continue;
+      }
+
+      if (child.nodeType !== Node.ELEMENT_NODE || child.matches(skip)) {
+        continue;
+      }
+
+      const css = getComputedStyle(child);
+      const tag = child.localName;
+      const heading = /^h([1-6])$/.exec(tag);
+      let [childKind, childLevel, childStyle, childHref] = [kind, level, style, href];
+      let isBlock = !css.display.startsWith('inline') && css.display !== 'contents';
+
+      if (css.display === 'none' || css.visibility === 'hidden') {
+        continue;
+      }
+
+      if (heading) {
+        [childKind, childLevel] = [1, +heading[1]];
+      } else if (tag === 'li') {
+        childKind = child.parentElement?.localName === 'ol' ? 3 : 2;
+        childLevel = depth(child);
+        isBlock = true;
+      } else if (tag === 'pre') {
+        childKind = 4;
+      } else if (tag === 'blockquote') {
+        childKind = 5;
+      } else if (tag === 'br') {
+        open = false;
+
+        continue;
+      } else if (kind === 2 || kind === 3) {
+        // Paragraphs of a list item stay in the item
+        isBlock = false;
+      }
+
+      if (tag === 'a' && typeof child.href === 'string' && child.href) {
+        childHref = child.href;
+      }
+      if (+css.fontWeight >= 600) {
+        childStyle |= 1;
+      }
+      if (css.fontStyle !== 'normal') {
+        childStyle |= 2;
+      }
+      if (kind !== 4 && /^(code|kbd|samp)$/.test(tag)) {
+        childStyle |= 4;
+      }
+
+      open = open && !isBlock;
+      walk(child, childKind, childLevel, childStyle, childHref);
+      open = open && !isBlock;
+    }
+  };
+
+  walk(root, 0, 0, 0, null);
+
+  return { title: document.title, spans };
+})())JS";
+
+// Returns the selected text, including the selection of text fields
+constexpr char16_t kSelectionScript[] = uR"JS((() => {
+  const element = document.activeElement;
+
+  if (
+    element && typeof element.value === 'string' &&
+    typeof element.selectionStart === 'number' &&
+    element.selectionStart !== element.selectionEnd
+  ) {
+    return element.value.slice(element.selectionStart, element.selectionEnd);
+  }
+
+  return String(getSelection());
+})())JS";
+
+// Keeps the popups of the `<select>` elements closed,
+// their options are shown in the terminal instead
+constexpr char16_t kSelectListenerScript[] = uR"JS(addEventListener(
+  'mousedown',
+  (event) => {
+    const select = event.target.closest?.('select');
+
+    if (select && !select.multiple && select.size <= 1) {
+      event.preventDefault();
+      select.focus();
+    }
+  },
+  true
+))JS";
+
+// Returns the bounds, the [label, flags] options and the selected index
+// of the `<select>` at (x, y), and keeps it for `kSelectResultScript`
+constexpr char16_t kSelectScript[] = uR"JS(((x, y) => {
+  const select = document.elementFromPoint(x, y)?.closest('select');
+
+  if (!select || select.disabled || select.multiple || select.size > 1) {
+    return null;
+  }
+
+  const rect = select.getBoundingClientRect();
+
+  globalThis.carbonylSelect = select;
+
+  return {
+    rect: [rect.left, rect.top, rect.width, rect.height],
+    options: [...select.options].map((option) => [
+      option.label,
+      (option.disabled || option.parentElement.disabled ? 1 : 0) |
+        (option.selected ? 4 : 0),
+    ]),
+    selected: select.selectedIndex,
+  };
+}))JS";
+
+// Selects the option picked in the terminal like the user would
+constexpr char16_t kSelectResultScript[] = uR"JS(((index) => {
+  const select = globalThis.carbonylSelect;
+
+  if (!select || !select.isConnected || select.selectedIndex === index) {
+    return;
+  }
+
+  select.selectedIndex = index;
+  select.dispatchEvent(new Event('input', { bubbles: true, composed: true }));
+  select.dispatchEvent(new Event('change', { bubbles: true }));
+}))JS";
+
+}  // namespace
+
+TabHelper::TabHelper(content::WebContents* web_contents)
+    : content::WebContentsObserver(web_contents),
+      content::WebContentsUserData<TabHelper>(*web_contents) {
+  Downloads::GetInstance()->Observe(web_contents->GetBrowserContext());
+}
+
+TabHelper::~TabHelper() = default;
+
+void TabHelper::RequestHints() {
+  RunScript(kHintsScript, base::BindOnce(&TabHelper::OnHints,
+                                         weak_ptr_factory_.GetWeakPtr()));
+}
+
+void TabHelper::RequestReader() {
+  RunScript(kReaderScript, base::BindOnce(&TabHelper::OnReader,
+                                          weak_ptr_factory_.GetWeakPtr()));
+}
+
+void TabHelper::CopySelection() {
+  RunScript(kSelectionScript, base::BindOnce(&TabHelper::OnSelection,
+                                             weak_ptr_factory_.GetWeakPtr()));
+}
+
+void TabHelper::Paste(const std::string& text) {
+  // Inserted like typed text so that the page gets its input events
+  auto script = "document.execCommand('insertText', false, " +
+                base::GetQuotedJSONString(text) + ")";
+
+  RunScript(base::UTF8ToUTF16(script), base::DoNothing());
+}
+
+void TabHelper::OpenSelect(const gfx::PointF& point) {
+  float zoom = GetZoomFactor();
+  auto script = std::u16string(kSelectScript) + u"(" +
+                base::NumberToString16(point.x() / zoom) + u", " +
+                base::NumberToString16(point.y() / zoom) + u")";
+
+  RunScript(script, base::BindOnce(&TabHelper::OnSelect,
+                                   weak_ptr_factory_.GetWeakPtr()));
+}
+
+void TabHelper::MenuResult(int index) {
+  auto actions = std::move(menu_actions_);
+
+  menu_actions_.clear();
+
+  if (index >= 0 && static_cast<size_t>(index) < actions.size() &&
+      actions[index]) {
+    std::move(actions[index]).Run();
+  }
+}
+
+void TabHelper::FileChooserResult(const std::vector<base::FilePath>& paths) {
+  auto listener = std::move(file_select_listener_);
+
+  if (!listener) {
+    return;
+  }
+  if (paths.empty()) {
+    listener->FileSelectionCanceled();
+
+    return;
+  }
+
+  std::vector<blink::mojom::FileChooserFileInfoPtr> files;
+
+  for (const auto& path: paths) {
+    files.push_back(blink::mojom::FileChooserFileInfo::NewNativeFile(
+        blink::mojom::NativeFileInfo::New(path, std::u16string())));
+  }
+
+  listener->FileSelected(std::move(files), base::FilePath(),
+                         file_chooser_mode_);
+}
+
+void TabHelper::Find(const std::string& text, bool forward) {
+  if (text.empty()) {
+    find_text_.clear();
+    web_contents()->StopFinding(content::STOP_FIND_ACTION_CLEAR_SELECTION);
+
+    return;
+  }
+
+  auto query = base::UTF8ToUTF16(text);
+  auto options = blink::mojom::FindOptions::New();
+
+  options->forward = forward;
+  options->new_session = query != find_text_;
+  find_text_ = query;
+
+  web_contents()->Find(++find_request_id_, query, std::move(options));
+}
+
+void TabHelper::DidStartNavigation(content::NavigationHandle* handle) {
+  // The headless delegate is set after the helpers of the page are created
+  ReplaceDelegate(web_contents());
+}
+
+void TabHelper::DOMContentLoaded(content::RenderFrameHost* render_frame_host) {
+  if (render_frame_host == web_contents()->GetPrimaryMainFrame()) {
+    RunScript(kSelectListenerScript, base::DoNothing());
+  }
+}
+
+void TabHelper::DidFinishNavigation(content::NavigationHandle* handle) {
+  if (handle->IsInPrimaryMainFrame() && handle->HasCommitted()) {
+    Renderer::GetCurrent()->SetHoverUrl(std::string());
+    UpdateSecurityState();
+  }
+}
+
+void TabHelper::DidStartLoading() {
+  Renderer::GetCurrent()->SetLoading(true, 0);
+}
+
+void TabHelper::DidStopLoading() {
+  Renderer::GetCurrent()->SetLoading(false, 1);
+}
+
+void TabHelper::LoadProgressChanged(double progress) {
+  Renderer::GetCurrent()->SetLoading(web_contents()->IsLoading(), progress);
+}
+
+void TabHelper::DidChangeVisibleSecurityState() {
+  UpdateSecurityState();
+}
+
+void TabHelper::FindReply(content::WebContents* web_contents,
+                          int request_id,
+                          int number_of_matches,
+                          const gfx::Rect& selection_rect,
+                          int active_match_ordinal,
+                          bool final_update) {
+  // Replies to a previous query, or to one which was stopped since
+  if (request_id != find_request_id_ || find_text_.empty()) {
+    return;
+  }
+
+  // Partial replies report -1 for values which aren't known yet
+  if (number_of_matches < 0 || active_match_ordinal < 0) {
+    return;
+  }
+
+  Renderer::GetCurrent()->SetFindResult(active_match_ordinal,
+                                        number_of_matches);
+}
+
+void TabHelper::UpdateTargetURL(content::WebContents* source,
+                                const GURL& url) {
+  Renderer::GetCurrent()->SetHoverUrl(url.is_valid() ? url.spec()
+                                                     : std::string());
+}
+
+content::JavaScriptDialogManager* TabHelper::GetJavaScriptDialogManager(
+    content::WebContents* source) {
+  return DialogManager::GetInstance();
+}
+
+bool TabHelper::HandleContextMenu(content::RenderFrameHost& render_frame_host,
+                                  const content::ContextMenuParams& params) {
+  auto& controller = web_contents()->GetController();
+  auto* renderer = Renderer::GetCurrent();
+  std::vector<MenuItem> items;
+  std::vector<base::OnceClosure> actions;
+  auto add = [&](std::string label, base::OnceClosure action) {
+    items.push_back({
+      std::move(label),
+      static_cast<uint8_t>(action ? 0 : MenuItem::kDisabled)
+    });
+    actions.push_back(std::move(action));
+  };
+
+  if (params.link_url.is_valid()) {
+    content::OpenURLParams open(params.link_url, content::Referrer(),
+                                WindowOpenDisposition::CURRENT_TAB,
+                                ui::PAGE_TRANSITION_LINK, false);
+
+    add("Open link", base::BindOnce(
+        base::IgnoreResult(&content::WebContents::OpenURL),
+        base::Unretained(web_contents()), open));
+    add("Copy link address", base::BindOnce(
+        &Renderer::Copy, base::Unretained(renderer), params.link_url.spec()));
+  }
+  if (params.src_url.is_valid() && params.has_image_contents) {
+    add("Copy image address", base::BindOnce(
+        &Renderer::Copy, base::Unretained(renderer), params.src_url.spec()));
+  }
+  if (!params.selection_text.empty()) {
+    add("Copy", base::BindOnce(
+        &Renderer::Copy, base::Unretained(renderer),
+        base::UTF16ToUTF8(params.selection_text)));
+  }
+  if (params.is_editable) {
+    add("Select all", base::BindOnce(
+        &content::WebContents::SelectAll, base::Unretained(web_contents())));
+  }
+  if (!items.empty()) {
+    items.push_back({ std::string(), MenuItem::kSeparator });
+    actions.emplace_back();
+  }
+
+  add("Back", controller.CanGoBack()
+      ? base::BindOnce(&content::NavigationController::GoBack,
+                       base::Unretained(&controller))
+      : base::OnceClosure());
+  add("Forward", controller.CanGoForward()
+      ? base::BindOnce(&content::NavigationController::GoForward,
+                       base::Unretained(&controller))
+      : base::OnceClosure());
+  add("Reload", base::BindOnce(
+      &content::NavigationController::Reload, base::Unretained(&controller),
+      content::ReloadType::NORMAL, true));
+
+  ShowMenu(gfx::RectF(params.x, params.y, 0, 0), std::move(items),
+           std::move(actions), -1);
+
+  return true;
+}
+
+void TabHelper::RunFileChooser(
+    content::RenderFrameHost* render_frame_host,
+    scoped_refptr<content::FileSelectListener> listener,
+    const blink::mojom::FileChooserParams& params) {
+  // The terminal shows one file chooser at a time
+  if (file_select_listener_) {
+    file_select_listener_->FileSelectionCanceled();
+  }
+
+  file_select_listener_ = std::move(listener);
+  file_chooser_mode_ = params.mode;
+
+  Renderer::GetCurrent()->ShowFileChooser(
+      params.default_file_name.empty()
+          ? std::string()
+          : params.default_file_name.DirName().AsUTF8Unsafe(),
+      base::UTF16ToUTF8(base::JoinString(params.accept_types, u",")),
+      params.mode == blink::mojom::FileChooserParams::Mode::kOpenMultiple);
+}
+
+void TabHelper::RequestMediaAccessPermission(
+    content::WebContents* web_contents,
+    const content::MediaStreamRequest& request,
+    content::MediaResponseCallback callback) {
+  std::vector<Permission> permissions;
+
+  if (request.video_type != blink::mojom::MediaStreamType::NO_SERVICE) {
+    permissions.push_back(Permission::kCamera);
+  }
+  if (request.audio_type != blink::mojom::MediaStreamType::NO_SERVICE) {
+    permissions.push_back(Permission::kMicrophone);
+  }
+
+  Permissions::GetInstance()->Request(
+      url::Origin::Create(request.security_origin), std::move(permissions),
+      base::BindOnce(&TabHelper::OnMediaAccessPermission,
+                     weak_ptr_factory_.GetWeakPtr(), request,
+                     std::move(callback)));
+}
+
+void TabHelper::RunScript(const std::u16string& script,
+                          base::OnceCallback<void(base::Value)> callback) {
+  web_contents()->GetPrimaryMainFrame()->ExecuteJavaScriptInIsolatedWorld(
+    script,
+    std::move(callback),
+    kIsolatedWorldId
+  );
+}
+
+float TabHelper::GetZoomFactor() {
+  return blink::PageZoomLevelToZoomFactor(
+    content::HostZoomMap::GetZoomLevel(web_contents())
+  );
+}
+
+void TabHelper::UpdateSecurityState() {
+  auto* entry = web_contents()->GetController().GetLastCommittedEntry();
+  auto state = SecurityState::kNone;
+
+  if (entry) {
+    const auto& url = entry->GetURL();
+    const auto& ssl = entry->GetSSL();
+
+    if (url.SchemeIsCryptographic()) {
+      if (ssl.initialized && net::IsCertStatusError(ssl.cert_status)) {
+        state = SecurityState::kCertificateError;
+      } else if (ssl.content_status &
+                 content::SSLStatus::RAN_INSECURE_CONTENT) {
+        state = SecurityState::kInsecure;
+      } else {
+        state = SecurityState::kSecure;
+      }
+    } else if (url.SchemeIs(url::kHttpScheme)) {
+      state = SecurityState::kInsecure;
+    }
+  }
+
+  Renderer::GetCurrent()->SetSecurityState(state);
+}
+
+void TabHelper::OnHints(base::Value result) {
+  std::vector<gfx::RectF> rects;
+  float zoom = GetZoomFactor();
+
+  if (result.is_list()) {
+    for (const auto& value: result.GetList()) {
+      const auto* rect = value.GetIfList();
+
+      if (!rect || rect->size() != 4) {
+        continue;
+      }
+
+      rects.emplace_back(
+        (*rect)[0].GetDouble() * zoom,
+        (*rect)[1].GetDouble() * zoom,
+        (*rect)[2].GetDouble() * zoom,
+        (*rect)[3].GetDouble() * zoom
+      );
+    }
+  }
+
+  Renderer::GetCurrent()->ShowHints(rects);
+}
+
+void TabHelper::OnReader(base::Value result) {
+  std::vector<ReaderSpan> spans;
+  const auto* dict = result.GetIfDict();
+  const auto* title = dict ? dict->FindString("title") : nullptr;
+  const auto* list = dict ? dict->FindList("spans") : nullptr;
+
+  if (list) {
+    for (const auto& value: *list) {
+      const auto* span = value.GetIfList();
+
+      if (!span || span->size() != 6 || !(*span)[0].is_string()) {
+        continue;
+      }
+
+      const auto* href = (*span)[1].GetIfString();
+      ReaderSpan item;
+
+      item.text = (*span)[0].GetString();
+      item.href = href ? *href : std::string();
+      item.block = (*span)[2].GetIfInt().value_or(0);
+      item.kind = static_cast<ReaderSpan::Kind>((*span)[3].GetIfInt().value_or(0));
+      item.level = (*span)[4].GetIfInt().value_or(0);
+      item.style = (*span)[5].GetIfInt().value_or(0);
+
+      spans.push_back(std::move(item));
+    }
+  }
+
+  Renderer::GetCurrent()->ShowReader(title ? *title : std::string(), spans);
+}
+
+void TabHelper::OnSelection(base::Value result) {
+  const auto* text = result.GetIfString();
+
+  if (text && !text->empty()) {
+    Renderer::GetCurrent()->Copy(*text);
+  }
+}
+
+void TabHelper::OnSelect(base::Value result) {
+  const auto* dict = result.GetIfDict();
+  const auto* rect = dict ? dict->FindList("rect") : nullptr;
+  const auto* options = dict ? dict->FindList("options") : nullptr;
+
+  if (!rect || rect->size() != 4 || !options) {
+    return;
+  }
+
+  float zoom = GetZoomFactor();
+  std::vector<MenuItem> items;
+  std::vector<base::OnceClosure> actions;
+
+  for (const auto& value: *options) {
+    const auto* option = value.GetIfList();
+    const auto* label = option && option->size() == 2
+        ? (*option)[0].GetIfString()
+        : nullptr;
+    uint8_t flags = label ? (*option)[1].GetIfInt().value_or(0) : 0;
+    int index = static_cast<int>(actions.size());
+
+    items.push_back({ label ? *label : std::string(), flags });
+    actions.push_back(
+      flags & MenuItem::kDisabled
+        ? base::OnceClosure()
+        : base::BindOnce(
+            &TabHelper::RunScript, base::Unretained(this),
+            std::u16string(kSelectResultScript) + u"(" +
+              base::NumberToString16(index) + u")",
+            base::DoNothingAs<void(base::Value)>()
+          )
+    );
+  }
+
+  ShowMenu(
+    gfx::RectF(
+      (*rect)[0].GetDouble() * zoom,
+      (*rect)[1].GetDouble() * zoom,
+      (*rect)[2].GetDouble() * zoom,
+      (*rect)[3].GetDouble() * zoom
+    ),
+    std::move(items),
+    std::move(actions),
+    dict->FindInt("selected").value_or(-1)
+  );
+}
+
+void TabHelper::OnMediaAccessPermission(
+    const content::MediaStreamRequest& request,
+    content::MediaResponseCallback callback,
+    bool allowed) {
+  if (!allowed) {
+    std::move(callback).Run(
+        blink::mojom::StreamDevicesSet(),
+        blink::mojom::MediaStreamRequestResult::PERMISSION_DENIED, nullptr);
+
+    return;
+  }
+
+  // The headless delegate picks the capture devices, if it has any
+  ForwardingDelegate::RequestMediaAccessPermission(web_contents(), request,
+                                                   std::move(callback));
+}
+
+void TabHelper::ShowMenu(const gfx::RectF& rect,
+                         std::vector<MenuItem> items,
+                         std::vector<base::OnceClosure> actions,
+                         int selected) {
+  menu_actions_ = std::move(actions);
+
+  Renderer::GetCurrent()->ShowMenu(rect, items, selected);
+}
+
+WEB_CONTENTS_USER_DATA_KEY_IMPL(TabHelper);
+
+}  // namespace carbonyl
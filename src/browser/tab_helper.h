@@ -0,0 +1,128 @@
+#ifndef CARBONYL_SRC_BROWSER_TAB_HELPER_H_
+#define CARBONYL_SRC_BROWSER_TAB_HELPER_H_
+
+#include <memory>
+#include <string>
+#include <vector>
+
+#include "base/files/file_path.h"
+#include "base/functional/callback.h"
+#include "base/memory/weak_ptr.h"
+#include "base/values.h"
+#include "carbonyl/src/browser/export.h"
+#include "carbonyl/src/browser/forwarding_delegate.h"
+#include "carbonyl/src/browser/renderer.h"
+#include "content/public/browser/file_select_listener.h"
+#include "content/public/browser/media_stream_request.h"
+#include "content/public/browser/web_contents_observer.h"
+#include "content/public/browser/web_contents_user_data.h"
+#include "third_party/blink/public/mojom/choosers/file_chooser.mojom.h"
+#include "ui/gfx/geometry/point_f.h"
+#include "ui/gfx/geometry/rect_f.h"
+
+namespace carbonyl {
+
+// Answers the requests of the terminal UI about a page.
+//
+// It also replaces the delegate of the page to get the events the headless
+// delegate ignores, the other ones still go to the headless delegate.
+class CARBONYL_CONTENTS_EXPORT TabHelper
+    : public content::WebContentsObserver,
+      public ForwardingDelegate,
+      public content::WebContentsUserData<TabHelper> {
+ public:
+  ~TabHelper() override;
+
+  // disable copy
+  TabHelper(const TabHelper&) = delete;
+  TabHelper& operator=(const TabHelper&) = delete;
+
+  // Sends the clickable elements in the viewport to the renderer
+  void RequestHints();
+  // Searches for `text` in the page, an empty text stops the search
+  void Find(const std::string& text, bool forward);
+  // Sends the main content of the page to the renderer
+  void RequestReader();
+  // Sends the selected text to the renderer
+  void CopySelection();
+  // Inserts `text` in the focused element
+  void Paste(const std::string& text);
+  // Shows the options of the `<select>` at `point` in a menu, if any
+  void OpenSelect(const gfx::PointF& point);
+  // Runs the action of the picked item of the shown menu
+  void MenuResult(int index);
+  // Answers the shown file chooser, no paths if it was cancelled
+  void FileChooserResult(const std::vector<base::FilePath>& paths);
+
+  // content::WebContentsObserver
+  void DidStartNavigation(content::NavigationHandle* handle) override;
+  void DOMContentLoaded(content::RenderFrameHost* render_frame_host) override;
+  void DidFinishNavigation(content::NavigationHandle* handle) override;
+  void DidStartLoading() override;
+  void DidStopLoading() override;
+  void LoadProgressChanged(double progress) override;
+  void DidChangeVisibleSecurityState() override;
+
+  // content::WebContentsDelegate
+  void FindReply(content::WebContents* web_contents,
+                 int request_id,
+                 int number_of_matches,
+                 const gfx::Rect& selection_rect,
+                 int active_match_ordinal,
+                 bool final_update) override;
+  void UpdateTargetURL(content::WebContents* source, const GURL& url) override;
+  content::JavaScriptDialogManager* GetJavaScriptDialogManager(
+      content::WebContents* source) override;
+  bool HandleContextMenu(content::RenderFrameHost& render_frame_host,
+                         const content::ContextMenuParams& params) override;
+  void RunFileChooser(content::RenderFrameHost* render_frame_host,
+                      scoped_refptr<content::FileSelectListener> listener,
+                      const blink::mojom::FileChooserParams& params) override;
+  void RequestMediaAccessPermission(
+      content::WebContents* web_contents,
+      const content::MediaStreamRequest& request,
+      content::MediaResponseCallback callback) override;
+
+ private:
+  friend class content::WebContentsUserData<TabHelper>;
+
+  explicit TabHelper(content::WebContents* web_contents);
+
+  // Runs a script in an isolated world of the main frame,
+  // the page can't see the script nor change what it returns
+  void RunScript(const std::u16string& script,
+                 base::OnceCallback<void(base::Value)> callback);
+  // Scripts measure in CSS pixels, the renderer in browser pixels
+  float GetZoomFactor();
+  // Sends the security state of the committed page to the renderer
+  void UpdateSecurityState();
+
+  void OnHints(base::Value result);
+  void OnReader(base::Value result);
+  void OnSelection(base::Value result);
+  void OnSelect(base::Value result);
+  void OnMediaAccessPermission(const content::MediaStreamRequest& request,
+                               content::MediaResponseCallback callback,
+                               bool allowed);
+  // Items without an action are disabled
+  void ShowMenu(const gfx::RectF& rect,
+                std::vector<MenuItem> items,
+                std::vector<base::OnceClosure> actions,
+                int selected);
+
+  std::u16string find_text_;
+  int find_request_id_ = 0;
+  // Actions of the items of the shown menu
+  std::vector<base::OnceClosure> menu_actions_;
+  // Listener of the shown file chooser
+  scoped_refptr<content::FileSelectListener> file_select_listener_;
+  blink::mojom::FileChooserParams::Mode file_chooser_mode_ =
+      blink::mojom::FileChooserParams::Mode::kOpen;
+  base::WeakPtrFactory<TabHelper> weak_ptr_factory_ { this };
+
+  WEB_CONTENTS_USER_DATA_KEY_DECL();
+};
+
+}  // namespace carbonyl
+
+#endif  // CARBONYL_SRC_BROWSER_TAB_HELPER_H_
@@ -1,6 +1,7 @@
 use std::{env, ffi::OsStr};
 
 use super::CommandLineProgram;
+use crate::{gfx::Size, output::DumpFormat, terminal};
 
 #[derive(Clone, Debug)]
 pub struct CommandLine {
@@ -10,6 +11,33 @@ pub struct CommandLine {
     pub debug: bool,
     pub bitmap: bool,
     pub sixel_only: bool,
+    pub status_bar: bool,
+    /// Permissions allowed without asking, as `origin:permission`
+    pub grants: Vec<String>,
+    /// Path of the file holding logins for HTTP authentication
+    pub credentials: Option<String>,
+    /// File the page is saved to once loaded, without a terminal
+    pub screenshot: Option<String>,
+    /// Print the text of the page once loaded, without a terminal
+    pub dump: Option<DumpFormat>,
+    /// File the output is recorded to, in the asciicast format
+    pub record: Option<String>,
+    /// Whether the input is recorded as well
+    pub record_input: bool,
+    /// Asciicast file the input is read from instead of the terminal
+    pub replay: Option<String>,
+    /// File the screen is compared with at the end of the replay
+    pub golden: Option<String>,
+    /// Milliseconds to wait after the page loaded before the screenshot
+    pub wait: Option<u64>,
+    /// Size of the window in cells, instead of the size of the terminal
+    pub size: Option<Size>,
+    /// Run without a TTY, set with `--virtual-terminal` or if there's none
+    pub virtual_terminal: bool,
+    /// Size of a cell of the virtual terminal in pixels
+    pub cell_size: Option<Size>,
+    /// Unix socket accepting JSON-RPC commands to drive the browser
+    pub control_socket: Option<String>,
     pub program: CommandLineProgram,
     pub shell_mode: bool,
 }
@@ -19,6 +47,7 @@ pub enum EnvVar {
     Bitmap,
     SixelOnly,
     ShellMode,
+    VirtualTerminal,
 }
 
 impl EnvVar {
@@ -28,6 +57,7 @@ impl EnvVar {
             EnvVar::Bitmap => "CARBONYL_ENV_BITMAP",
             EnvVar::SixelOnly => "CARBONYL_ENV_SIXEL_ONLY",
             EnvVar::ShellMode => "CARBONYL_ENV_SHELL_MODE",
+            EnvVar::VirtualTerminal => "CARBONYL_ENV_VIRTUAL_TERMINAL",
         }
     }
 }
@@ -39,12 +69,37 @@ impl AsRef<OsStr> for EnvVar {
 }
 
 impl CommandLine {
+    /// Whether the page is captured without a terminal, then the browser quits.
+    pub fn headless(&self) -> bool {
+        self.screenshot.is_some() || self.dump.is_some()
+    }
+
+    /// Whether the terminal is used for input and output, rather than
+    /// capturing the page, replaying a recording or using a virtual terminal.
+    pub fn interactive(&self) -> bool {
+        !self.headless() && self.replay.is_none() && !self.virtual_terminal
+    }
+
     pub fn parse() -> CommandLine {
         let mut fps = 60.0;
         let mut zoom = 1.0;
         let mut debug = false;
         let mut bitmap = false;
         let mut sixel_only = true;
+        let mut status_bar = false;
+        let mut grants = Vec::new();
+        let mut credentials = None;
+        let mut screenshot = None;
+        let mut dump = None;
+        let mut wait = None;
+        let mut record = None;
+        let mut record_input = false;
+        let mut replay = None;
+        let mut golden = None;
+        let mut size = None;
+        let mut virtual_terminal = false;
+        let mut cell_size = None;
+        let mut control_socket = None;
         let mut shell_mode = false;
         let mut program = CommandLineProgram::Main;
         let args = env::args().skip(1).collect::<Vec<String>>();
@@ -82,6 +137,30 @@ impl CommandLine {
                 "-d" | "--debug" => set!(debug, Debug),
                 "-b" | "--bitmap" => set!(bitmap, Bitmap),
                 "--sixel-only" => set!(sixel_only, SixelOnly),
+                "--status-bar" => status_bar = true,
+                "--credentials" => {
+                    credentials = arg.split_once('=').map(|(_, value)| value.to_owned())
+                }
+                "--screenshot" => {
+                    screenshot = arg.split_once('=').map(|(_, value)| value.to_owned())
+                }
+                "--dump" => dump = DumpFormat::parse(value.unwrap_or(&"")),
+                "--record" => record = arg.split_once('=').map(|(_, value)| value.to_owned()),
+                "--record-input" => record_input = true,
+                "--replay" => replay = arg.split_once('=').map(|(_, value)| value.to_owned()),
+                "--golden" => golden = arg.split_once('=').map(|(_, value)| value.to_owned()),
+                "--wait" => wait = value.and_then(|value| value.parse().ok()),
+                "--size" => size = value.and_then(|value| parse_size(value)),
+                "--virtual-terminal" => set!(virtual_terminal, VirtualTerminal),
+                "--cell-size" => cell_size = value.and_then(|value| parse_size(value)),
+                "--control-socket" => {
+                    control_socket = arg.split_once('=').map(|(_, value)| value.to_owned())
+                }
+                "--grant" => {
+                    if let Some(value) = arg.split_once('=').map(|(_, value)| value) {
+                        grants.push(value.to_owned());
+                    }
+                }
                 "--legacy-text" => {
                     sixel_only = false;
 
@@ -114,6 +193,16 @@ impl CommandLine {
             shell_mode = true;
         }
 
+        // Only the main process looks for a terminal, the browser
+        // and its subprocesses get the result from the environment
+        if env::var(EnvVar::VirtualTerminal).is_ok() {
+            virtual_terminal = true;
+        } else if !shell_mode && !terminal::attached() {
+            virtual_terminal = true;
+
+            env::set_var(EnvVar::VirtualTerminal, "1");
+        }
+
         CommandLine {
             args,
             fps,
@@ -121,8 +210,30 @@ impl CommandLine {
             debug,
             bitmap,
             sixel_only,
+            status_bar,
+            grants,
+            credentials,
+            screenshot,
+            dump,
+            record,
+            record_input,
+            replay,
+            golden,
+            wait,
+            size,
+            virtual_terminal,
+            cell_size,
+            control_socket,
             program,
             shell_mode,
         }
     }
 }
+
+/// Parse a size given as `<width>x<height>`.
+fn parse_size(value: &str) -> Option<Size> {
+    let (width, height) = value.split_once('x')?;
+    let size = Size::new(width.parse().ok()?, height.parse().ok()?);
+
+    (size.width > 0 && size.height > 0).then_some(size)
+}
@@ -0,0 +1,86 @@
+mod credentials;
+mod ini;
+mod permissions;
+
+pub use credentials::*;
+pub use ini::*;
+pub use permissions::*;
+
+use std::{env, fs, io, path::PathBuf};
+
+use crate::{
+    ui::{keymap::Keymap, theme::Theme},
+    utils::log,
+};
+
+/// User configuration, read from `~/.config/carbonyl/config.ini`.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub search: SearchConfig,
+    pub keymap: Keymap,
+    pub theme: Theme,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchConfig {
+    /// URL template of the search engine, `%s` is replaced by the query
+    pub engine: String,
+    /// Keyword shortcuts, ie. `gh foo` with `gh = https://github.com/search?q=%s`
+    pub keywords: Vec<(String, String)>,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            engine: "https://duckduckgo.com/?q=%s".to_owned(),
+            keywords: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Read the configuration file, falling back to defaults
+    /// if it is missing or cannot be read.
+    pub fn read() -> Config {
+        let ini = match Self::dir().map(|dir| fs::read_to_string(dir.join("config.ini"))) {
+            Some(Ok(text)) => Ini::parse(&text),
+            Some(Err(error)) if error.kind() != io::ErrorKind::NotFound => {
+                log::warning!("failed to read config file: {error}");
+
+                Ini::default()
+            }
+            _ => Ini::default(),
+        };
+
+        Self::from_ini(&ini)
+    }
+
+    /// Directory holding the configuration files,
+    /// `$XDG_CONFIG_HOME/carbonyl` or `~/.config/carbonyl`.
+    pub fn dir() -> Option<PathBuf> {
+        let base = match env::var_os("XDG_CONFIG_HOME") {
+            Some(path) if !path.is_empty() => PathBuf::from(path),
+            _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+        };
+
+        Some(base.join("carbonyl"))
+    }
+
+    pub fn from_ini(ini: &Ini) -> Config {
+        let mut search = SearchConfig::default();
+
+        if let Some(engine) = ini.get("search", "engine") {
+            search.engine = engine.to_owned();
+        }
+
+        for (keyword, template) in ini.section("keywords") {
+            search.keywords.push((keyword.to_owned(), template.to_owned()));
+        }
+
+        Config {
+            search,
+            keymap: Keymap::from_ini(ini),
+            theme: Theme::from_ini(ini),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::{fs, io, path::PathBuf};
+
+use super::{Config, Ini};
+use crate::utils::log;
+
+#[derive(Clone, Debug)]
+struct Credentials {
+    /// Serialized origin, ie. `https://intranet.example.com`
+    origin: String,
+    /// Only answer challenges for this realm if set
+    realm: Option<String>,
+    username: String,
+    password: String,
+}
+
+/// Logins used to answer HTTP authentication challenges without asking,
+/// read from `credentials.ini` in the configuration directory or from the
+/// file given with `--credentials`, with a section per origin:
+///
+/// ```ini
+/// [https://intranet.example.com]
+/// username = alice
+/// password = secret
+/// # Optional, only use this login for a realm
+/// realm = Staff only
+/// ```
+///
+/// Each login is only tried once per session, the user is asked
+/// if the server rejects it.
+#[derive(Clone, Debug, Default)]
+pub struct CredentialStore {
+    entries: Vec<Credentials>,
+    /// Origins we already answered for
+    used: Vec<String>,
+}
+
+impl CredentialStore {
+    /// Read the credentials file, starting empty if there is none.
+    pub fn read(path: Option<&str>) -> CredentialStore {
+        let mut store = CredentialStore::default();
+        let Some(path) = path
+            .map(PathBuf::from)
+            .or_else(|| Config::dir().map(|dir| dir.join("credentials.ini")))
+        else {
+            return store;
+        };
+        let ini = match fs::read_to_string(&path) {
+            Ok(text) => Ini::parse(&text),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return store,
+            Err(error) => {
+                log::warning!("failed to read credentials file: {error}");
+
+                return store;
+            }
+        };
+
+        if let Ok(metadata) = fs::metadata(&path) {
+            use std::os::unix::fs::PermissionsExt;
+
+            if metadata.permissions().mode() & 0o077 != 0 {
+                log::warning!("{} is readable by other users", path.display());
+            }
+        }
+
+        for origin in ini.sections() {
+            match (ini.get(origin, "username"), ini.get(origin, "password")) {
+                (Some(username), Some(password)) => store.entries.push(Credentials {
+                    origin: origin.to_owned(),
+                    realm: ini.get(origin, "realm").map(str::to_owned),
+                    username: username.to_owned(),
+                    password: password.to_owned(),
+                }),
+                _ => log::warning!("missing username or password for {origin}"),
+            }
+        }
+
+        store
+    }
+
+    /// Login for a challenge, `None` if there's none or it was already tried.
+    pub fn take(&mut self, origin: &str, realm: &str) -> Option<(String, String)> {
+        if self.used.iter().any(|used| used == origin) {
+            return None;
+        }
+
+        let entry = self.entries.iter().find(|entry| {
+            entry.origin == origin && entry.realm.as_ref().is_none_or(|name| name == realm)
+        })?;
+
+        self.used.push(origin.to_owned());
+
+        Some((entry.username.clone(), entry.password.clone()))
+    }
+}
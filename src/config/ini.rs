@@ -0,0 +1,99 @@
+use std::fmt;
+
+/// Minimal INI document: `[section]` headers followed by `key = value` pairs.
+/// Lines starting with `#` or `;` are comments, keys are case-sensitive
+/// and declaration order is preserved.
+#[derive(Clone, Debug, Default)]
+pub struct Ini {
+    sections: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl Ini {
+    pub fn parse(text: &str) -> Ini {
+        let mut ini = Ini::default();
+        let mut current = String::new();
+
+        for line in text.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                current = name.trim().to_owned();
+
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                let value = value.trim();
+                let value = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+                    Some(quoted) => quoted,
+                    None => value,
+                };
+
+                ini.push(&current, key.trim(), value);
+            }
+        }
+
+        ini
+    }
+
+    pub fn push(&mut self, section: &str, key: &str, value: &str) {
+        let entry = (key.to_owned(), value.to_owned());
+
+        match self.sections.iter_mut().find(|(name, _)| name == section) {
+            Some((_, entries)) => entries.push(entry),
+            None => self.sections.push((section.to_owned(), vec![entry])),
+        }
+    }
+
+    /// Names of the sections, in declaration order.
+    pub fn sections(&self) -> impl Iterator<Item = &str> {
+        self.sections.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Iterate over the entries of a section.
+    pub fn section<'a, 'b>(
+        &'a self,
+        name: &'b str,
+    ) -> impl Iterator<Item = (&'a str, &'a str)> + 'b
+    where
+        'a: 'b,
+    {
+        self.sections
+            .iter()
+            .filter(move |(section, _)| section == name)
+            .flat_map(|(_, entries)| entries.iter())
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Get the last value set for a key.
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.section(section)
+            .filter(|(name, _)| *name == key)
+            .map(|(_, value)| value)
+            .last()
+    }
+}
+
+/// Serialize the document, comments of a parsed file are not kept.
+impl fmt::Display for Ini {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (name, entries)) in self.sections.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            if !name.is_empty() {
+                writeln!(f, "[{name}]")?;
+            }
+
+            for (key, value) in entries {
+                writeln!(f, "{key} = {value}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,327 @@
+use std::{fs, io};
+
+use super::{Config, Ini};
+use crate::utils::log;
+
+const FILE: &str = "permissions.ini";
+
+/// Features a page has to ask the user for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Permission {
+    Geolocation,
+    Notifications,
+    Camera,
+    Microphone,
+    Clipboard,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 5] = [
+        Permission::Geolocation,
+        Permission::Notifications,
+        Permission::Camera,
+        Permission::Microphone,
+        Permission::Clipboard,
+    ];
+
+    /// Permission requested by the browser: 0 for geolocation, 1 for notifications,
+    /// 2 for the camera, 3 for the microphone and 4 for reading the clipboard.
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL.get(code as usize).copied()
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|permission| permission.name() == name)
+    }
+
+    /// Name used in the permissions file and on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Permission::Geolocation => "geolocation",
+            Permission::Notifications => "notifications",
+            Permission::Camera => "camera",
+            Permission::Microphone => "microphone",
+            Permission::Clipboard => "clipboard",
+        }
+    }
+
+    /// What the page wants to do, ie. `example.com wants to use your location`.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Permission::Geolocation => "use your location",
+            Permission::Notifications => "show notifications",
+            Permission::Camera => "use your camera",
+            Permission::Microphone => "use your microphone",
+            Permission::Clipboard => "read your clipboard",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Decision {
+    /// Serialized origin, ie. `https://example.com`, or `*` for every origin
+    origin: String,
+    permission: Permission,
+    allowed: bool,
+}
+
+/// Permissions the user allowed or denied for each origin.
+///
+/// Permanent decisions are saved in `permissions.ini`,
+/// in the configuration directory, with a section per origin:
+///
+/// ```ini
+/// [https://maps.example.com]
+/// geolocation = allow
+/// notifications = deny
+/// ```
+///
+/// Denied requests and permissions granted on the command line
+/// only last for the session.
+#[derive(Clone, Debug, Default)]
+pub struct PermissionStore {
+    saved: Vec<Decision>,
+    session: Vec<Decision>,
+}
+
+impl PermissionStore {
+    /// Read the saved decisions, starting empty if there are none.
+    pub fn read() -> PermissionStore {
+        match Config::dir().map(|dir| fs::read_to_string(dir.join(FILE))) {
+            Some(Ok(text)) => PermissionStore::from_ini(&Ini::parse(&text)),
+            Some(Err(error)) if error.kind() != io::ErrorKind::NotFound => {
+                log::warning!("failed to read permissions file: {error}");
+
+                PermissionStore::default()
+            }
+            _ => PermissionStore::default(),
+        }
+    }
+
+    /// Saved decisions from the sections of a permissions file.
+    fn from_ini(ini: &Ini) -> PermissionStore {
+        let mut store = PermissionStore::default();
+
+        for origin in ini.sections() {
+            for (name, value) in ini.section(origin) {
+                let allowed = match value {
+                    "allow" => true,
+                    "deny" => false,
+                    _ => {
+                        log::warning!("invalid decision for {name} in {origin}: {value}");
+
+                        continue;
+                    }
+                };
+
+                match Permission::parse(name) {
+                    Some(permission) => store.saved.push(Decision {
+                        origin: origin.to_owned(),
+                        permission,
+                        allowed,
+                    }),
+                    None => log::warning!("unknown permission in {origin}: {name}"),
+                }
+            }
+        }
+
+        store
+    }
+
+    /// Allow a permission for the session from an `origin:permission` pair,
+    /// the origin can be `*` to allow it everywhere.
+    pub fn grant(&mut self, spec: &str) {
+        let parsed = spec
+            .rsplit_once(':')
+            .and_then(|(origin, name)| Some((origin, Permission::parse(name)?)));
+
+        match parsed {
+            Some((origin, permission)) if !origin.is_empty() => {
+                self.remember(origin, permission, true, false)
+            }
+            _ => log::warning!("invalid permission grant: {spec}"),
+        }
+    }
+
+    /// Decision taken for a permission, `None` if the user should be asked.
+    pub fn get(&self, origin: &str, permission: Permission) -> Option<bool> {
+        let find = |origin: &str| {
+            // Session decisions take precedence, the latest one wins
+            self.session
+                .iter()
+                .rev()
+                .chain(self.saved.iter().rev())
+                .find(|decision| decision.origin == origin && decision.permission == permission)
+                .map(|decision| decision.allowed)
+        };
+
+        find(origin).or_else(|| find("*"))
+    }
+
+    /// Decisions applying to an origin, in the order of `Permission::ALL`.
+    pub fn decisions(&self, origin: &str) -> Vec<(Permission, bool)> {
+        Permission::ALL
+            .into_iter()
+            .filter_map(|permission| Some((permission, self.get(origin, permission)?)))
+            .collect()
+    }
+
+    /// Record a decision, saving it to the permissions file if `persist` is set.
+    pub fn remember(&mut self, origin: &str, permission: Permission, allowed: bool, persist: bool) {
+        let decision = Decision {
+            origin: origin.to_owned(),
+            permission,
+            allowed,
+        };
+        let same = |other: &Decision| other.origin == origin && other.permission == permission;
+
+        self.session.retain(|other| !same(other));
+
+        if persist {
+            self.saved.retain(|other| !same(other));
+            self.saved.push(decision);
+
+            if let Err(error) = self.save() {
+                log::warning!("failed to save permissions: {error}")
+            }
+        } else {
+            self.session.push(decision);
+        }
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(dir) = Config::dir() else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no configuration directory",
+            ));
+        };
+
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(FILE), self.to_ini().to_string())
+    }
+
+    fn to_ini(&self) -> Ini {
+        let mut ini = Ini::default();
+
+        for decision in &self.saved {
+            let value = if decision.allowed { "allow" } else { "deny" };
+
+            ini.push(&decision.origin, decision.permission.name(), value);
+        }
+
+        ini
+    }
+}
+
+/// Origin of a URL as serialized by the browser, ie. `https://example.com:8080`,
+/// `None` for URLs without a host like `about:blank`.
+pub fn origin(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    // Drop credentials
+    let host = authority.rsplit('@').next()?.to_lowercase();
+    let scheme = scheme.to_lowercase();
+    let host = match (scheme.as_str(), host.rsplit_once(':')) {
+        ("http", Some((host, "80"))) | ("https", Some((host, "443"))) => host.to_owned(),
+        _ => host,
+    };
+
+    (!host.is_empty()).then(|| format!("{scheme}://{host}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "\
+[https://maps.example.com]
+geolocation = allow
+notifications = deny
+camera = maybe
+teleport = allow
+
+[*]
+clipboard = deny
+";
+
+    #[test]
+    fn round_trips_saved_decisions() {
+        let store = PermissionStore::from_ini(&Ini::parse(FILE));
+
+        assert_eq!(
+            store.to_ini().to_string(),
+            "\
+[https://maps.example.com]
+geolocation = allow
+notifications = deny
+
+[*]
+clipboard = deny
+"
+        );
+        assert_eq!(
+            PermissionStore::from_ini(&store.to_ini()).decisions("https://maps.example.com"),
+            [
+                (Permission::Geolocation, true),
+                (Permission::Notifications, false),
+                (Permission::Clipboard, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn session_decisions_take_precedence() {
+        let mut store = PermissionStore::from_ini(&Ini::parse(FILE));
+        let maps = "https://maps.example.com";
+
+        store.remember(maps, Permission::Geolocation, false, false);
+        store.grant("*:clipboard");
+        store.grant("https://maps.example.com:camera");
+        store.grant("camera");
+        store.grant(":camera");
+
+        assert_eq!(store.get(maps, Permission::Geolocation), Some(false));
+        assert_eq!(store.get(maps, Permission::Clipboard), Some(true));
+        assert_eq!(store.get(maps, Permission::Camera), Some(true));
+        assert_eq!(store.get("https://other.test", Permission::Camera), None);
+        assert_eq!(
+            store.get("https://other.test", Permission::Clipboard),
+            Some(true)
+        );
+
+        // Session decisions are not saved
+        assert!(store.to_ini().to_string().contains("geolocation = allow"));
+        assert!(!store.to_ini().to_string().contains("camera"));
+    }
+
+    #[test]
+    fn serializes_origins() {
+        assert_eq!(
+            origin("HTTPS://User:pw@Example.com:443/a?b#c"),
+            Some("https://example.com".into())
+        );
+        assert_eq!(
+            origin("http://localhost:8080/"),
+            Some("http://localhost:8080".into())
+        );
+        assert_eq!(
+            origin("http://example.com:80"),
+            Some("http://example.com".into())
+        );
+        assert_eq!(origin("about:blank"), None);
+        assert_eq!(origin("file:///tmp/a.html"), None);
+    }
+
+    #[test]
+    fn parses_permissions() {
+        assert_eq!(Permission::from_code(2), Some(Permission::Camera));
+        assert_eq!(Permission::from_code(5), None);
+
+        for permission in Permission::ALL {
+            assert_eq!(Permission::parse(permission.name()), Some(permission));
+        }
+    }
+}
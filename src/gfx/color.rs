@@ -21,6 +21,28 @@ impl Color {
     pub fn black() -> Color {
         Color::<u8>::new(0, 0, 0)
     }
+
+    /// Parse a color written as `#rrggbb` or `#rgb`.
+    pub fn from_hex(text: &str) -> Option<Color> {
+        let hex = text.strip_prefix('#')?;
+
+        if !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let digit =
+            |index: usize, len: usize| u8::from_str_radix(hex.get(index..index + len)?, 16).ok();
+
+        match hex.len() {
+            6 => Some(Color::new(digit(0, 2)?, digit(2, 2)?, digit(4, 2)?)),
+            3 => Some(Color::new(
+                digit(0, 1)? * 17,
+                digit(1, 1)? * 17,
+                digit(2, 1)? * 17,
+            )),
+            _ => None,
+        }
+    }
 }
 
 impl_vector_overload!(Color r g b);
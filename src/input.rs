@@ -3,12 +3,12 @@ mod graphics;
 mod keyboard;
 mod listen;
 mod mouse;
+mod osc;
 mod parser;
-mod tty;
 
 pub use dcs::*;
 pub use keyboard::*;
 pub use listen::*;
 pub use mouse::*;
+pub use osc::*;
 pub use parser::*;
-pub use tty::*;
@@ -6,13 +6,13 @@ pub struct Keyboard {
     state: State,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Key {
     pub char: u8,
     pub modifiers: KeyModifiers,
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct KeyModifiers {
     pub alt: bool,
     pub meta: bool,
@@ -21,14 +21,14 @@ pub struct KeyModifiers {
 }
 
 enum State {
-    Separator,
-    Modifier(u8),
+    Code(u8),
+    Modifier(u8, u8),
 }
 
 impl Keyboard {
-    pub fn new() -> Self {
+    pub fn new(code: u8) -> Self {
         Self {
-            state: State::Separator,
+            state: State::Code(code),
         }
     }
     pub fn key(key: u8, modifiers: u8) -> Option<Event> {
@@ -42,6 +42,10 @@ impl Keyboard {
             b'C' => 0x13,
             // Left
             b'D' => 0x14,
+            // Home, End and Delete use bytes that can't appear in UTF-8 text
+            // so they aren't confused with Ctrl+A, Ctrl+E and Ctrl+D
+            b'H' => 0xf5,
+            b'F' => 0xf6,
             _ => return None,
         };
 
@@ -50,15 +54,53 @@ impl Keyboard {
         })
     }
 
+    /// Keys sent as `CSI <code> ; <modifiers> ~`
+    pub fn tilde(code: u8, modifiers: u8) -> Option<Event> {
+        match code {
+            1 | 7 => Self::key(b'H', modifiers),
+            4 | 8 => Self::key(b'F', modifiers),
+            // Delete
+            3 => Some(Event::KeyPress {
+                key: Key {
+                    char: 0xf7,
+                    modifiers: KeyModifiers::parse(modifiers),
+                },
+            }),
+            _ => None,
+        }
+    }
+
+    /// Keys sent as `CSI <code> ; <modifiers> u`, used by some terminals
+    /// to report modifiers on keys like Return (ie. Shift+Return)
+    pub fn unicode(code: u8, modifiers: u8) -> Option<Event> {
+        match code {
+            0x01..=0x7f => Some(Event::KeyPress {
+                key: Key {
+                    char: code,
+                    modifiers: KeyModifiers::parse(modifiers),
+                },
+            }),
+            _ => None,
+        }
+    }
+
     pub fn parse(&mut self, key: u8) -> ParseControlFlow {
         self.state = match self.state {
-            State::Separator => match key {
-                b';' => State::Modifier(0),
-                _ => control_flow!(break)?,
+            State::Code(code) => match key {
+                b'0'..=b'9' => State::Code(code.saturating_mul(10).saturating_add(key - b'0')),
+                b';' => State::Modifier(code, 0),
+                b'~' => control_flow!(break Self::tilde(code, 0))?,
+                b'u' => control_flow!(break Self::unicode(code, 0))?,
+                key => control_flow!(break Self::key(key, 0))?,
             },
-            State::Modifier(code) => match key {
-                b'0'..=b'9' => State::Modifier(code * 10 + key - b'0'),
-                key => control_flow!(break Self::key(key, code))?,
+            State::Modifier(code, modifiers) => match key {
+                b'0'..=b'9' => State::Modifier(
+                    code,
+                    modifiers.saturating_mul(10).saturating_add(key - b'0'),
+                ),
+                b'~' => control_flow!(break Self::tilde(code, modifiers))?,
+                b'u' => control_flow!(break Self::unicode(code, modifiers))?,
+                key => control_flow!(break Self::key(key, modifiers))?,
             },
         };
 
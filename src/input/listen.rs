@@ -1,36 +1,133 @@
-use std::io::{self, Read};
+use std::{
+    fs,
+    io::{self, Read},
+    thread,
+    time::{Duration, Instant},
+};
 
 use crate::input::*;
+use crate::utils::json::Value;
 
-/// Listen for input events in stdin.
+/// Listen for input events from the terminal, the callback also receives the bytes read.
 /// This will block, so it should run from a dedicated thread.
-pub fn listen<F>(mut callback: F) -> io::Result<()>
+pub fn listen<F>(mut input: impl Read, mut callback: F) -> io::Result<()>
 where
-    F: FnMut(Vec<Event>),
+    F: FnMut(Vec<Event>, &[u8]),
 {
     let mut buf = [0u8; 1024];
-    let mut stdin = io::stdin();
     let mut parser = Parser::new();
 
     loop {
         // Wait for some input
-        let size = stdin.read(&mut buf)?;
-        let read = parser.parse(&buf[0..size]);
-        let mut scroll = 0;
-        let mut events = Vec::with_capacity(read.len());
-
-        for event in read {
-            match event {
-                Event::Exit => return Ok(()),
-                Event::Scroll { delta } => scroll += delta,
-                event => events.push(event),
-            }
+        let size = input.read(&mut buf)?;
+
+        match group(parser.parse(&buf[0..size])) {
+            Some(events) => callback(events, &buf[0..size]),
+            None => return Ok(()),
         }
+    }
+}
 
-        if scroll != 0 {
-            events.push(Event::Scroll { delta: scroll })
+/// Read input events from an asciicast file recorded with `--record-input`
+/// instead of stdin, with the timings of the recording.
+/// This will block until the end of the recording.
+pub fn replay<F>(path: &str, callback: F) -> io::Result<()>
+where
+    F: FnMut(Vec<Event>, &[u8]),
+{
+    play(&fs::read_to_string(path)?, callback)
+}
+
+/// Send the input events of an asciicast recording to the callback.
+fn play<F>(recording: &str, mut callback: F) -> io::Result<()>
+where
+    F: FnMut(Vec<Event>, &[u8]),
+{
+    let start = Instant::now();
+    let mut parser = Parser::new();
+
+    // The first line is the header
+    for (index, line) in recording.lines().enumerate().skip(1) {
+        let event = Value::parse(line).and_then(|event| match event.as_array()? {
+            [time, kind, data] => Some((
+                time.as_f64()?,
+                kind.as_str()? == "i",
+                data.as_str()?.to_owned(),
+            )),
+            _ => None,
+        });
+        let Some((time, input, data)) = event else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid event on line {}", index + 1),
+            ));
+        };
+
+        // Output and resize events are ignored
+        if !input {
+            continue;
+        }
+
+        // Skip events we can't wait for, ie. at `1e999` seconds
+        let Ok(time) = Duration::try_from_secs_f64(time.max(0.0)) else {
+            continue;
+        };
+
+        thread::sleep(time.saturating_sub(start.elapsed()));
+
+        match group(parser.parse(data.as_bytes())) {
+            Some(events) => callback(events, data.as_bytes()),
+            None => return Ok(()),
         }
+    }
+
+    Ok(())
+}
+
+/// Merge the scroll events of a read, `None` if the input should stop.
+fn group(read: Vec<Event>) -> Option<Vec<Event>> {
+    let mut scroll = 0;
+    let mut events = Vec::with_capacity(read.len());
+
+    for event in read {
+        match event {
+            Event::Exit => return None,
+            Event::Scroll { delta } => scroll += delta,
+            event => events.push(event),
+        }
+    }
+
+    if scroll != 0 {
+        events.push(Event::Scroll { delta: scroll })
+    }
+
+    Some(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn play_skips_invalid_times() {
+        let recording = [
+            r#"{"version": 2, "width": 80, "height": 24}"#,
+            r#"[1e999, "i", "a"]"#,
+            r#"[0, "o", "b"]"#,
+            r#"[-1, "i", "c"]"#,
+        ]
+        .join("\n");
+        let mut read = Vec::new();
+
+        play(&recording, |_, bytes| read.extend_from_slice(bytes)).unwrap();
+
+        assert_eq!(read, b"c");
+    }
+
+    #[test]
+    fn play_rejects_invalid_events() {
+        let recording = "{}\n[0, \"i\"]";
 
-        callback(events)
+        assert!(play(recording, |_, _| ()).is_err());
     }
 }
@@ -0,0 +1,114 @@
+use crate::{control_flow, utils::base64};
+
+use super::{Event, ParseControlFlow, TerminalEvent};
+
+/// Ignore replies larger than this, clipboards can be huge.
+const MAX_LENGTH: usize = 4 * 1024 * 1024;
+
+/// Operating System Command sent by the terminal, `ESC ] <code> ; <data> ST`.
+#[derive(Default, Clone, Debug)]
+pub struct OperatingSystemCommand {
+    buffer: Vec<u8>,
+    /// Whether the previous byte was an Escape, the start of `ST`
+    escape: bool,
+    overflow: bool,
+}
+
+impl OperatingSystemCommand {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty() && !self.escape
+    }
+
+    pub fn parse(&mut self, key: u8) -> ParseControlFlow {
+        match key {
+            // BEL
+            0x07 => return control_flow!(break self.event()),
+            // ST is `ESC \`
+            b'\\' if self.escape => return control_flow!(break self.event()),
+            0x1b => self.escape = true,
+            _ if self.escape => return control_flow!(break),
+            _ if self.buffer.len() >= MAX_LENGTH => self.overflow = true,
+            key => self.buffer.push(key),
+        }
+
+        control_flow!(continue)
+    }
+
+    fn event(&mut self) -> Option<Event> {
+        if self.overflow {
+            return None;
+        }
+
+        let buffer = std::str::from_utf8(&self.buffer).ok()?;
+        let (code, data) = buffer.split_once(';')?;
+
+        match code {
+            // Clipboard content, `52 ; <selection> ; <base64>`
+            "52" => {
+                let (_, data) = data.split_once(';')?;
+                let bytes = base64::decode(data)?;
+
+                Some(Event::Terminal(TerminalEvent::Clipboard(
+                    String::from_utf8_lossy(&bytes).into_owned(),
+                )))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use super::*;
+
+    /// Parse what follows `ESC ]`, returns the clipboard if the sequence is complete.
+    fn clipboard(input: &[u8]) -> Option<String> {
+        let mut osc = OperatingSystemCommand::new();
+
+        for &byte in input {
+            match osc.parse(byte) {
+                ControlFlow::Break(Some(Event::Terminal(TerminalEvent::Clipboard(text)))) => {
+                    return Some(text)
+                }
+                ControlFlow::Break(_) => return None,
+                ControlFlow::Continue(_) => (),
+            }
+        }
+
+        None
+    }
+
+    #[test]
+    fn clipboard_reply() {
+        assert_eq!(clipboard(b"52;c;aGVsbG8=\x07").as_deref(), Some("hello"));
+        assert_eq!(clipboard(b"52;c;w6k=\x1b\\").as_deref(), Some("é"));
+        assert_eq!(clipboard(b"52;;\x07").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn other_sequences() {
+        // Other codes, invalid base64, a missing selection and an incomplete sequence
+        assert_eq!(clipboard(b"10;rgb:0000/0000/0000\x07"), None);
+        assert_eq!(clipboard(b"52;c;a?b\x07"), None);
+        assert_eq!(clipboard(b"52;aGk=\x07"), None);
+        assert_eq!(clipboard(b"52;c;aGk="), None);
+        // An escape not followed by `\` ends the sequence
+        assert_eq!(clipboard(b"52;c;aGk=\x1b[A"), None);
+    }
+
+    #[test]
+    fn oversized_reply() {
+        let mut input = b"52;c;".to_vec();
+
+        input.resize(MAX_LENGTH + 16, b'A');
+        input.push(0x07);
+
+        assert_eq!(clipboard(&input), None);
+    }
+}
@@ -18,6 +18,7 @@ enum Sequence {
     Mouse(Mouse),
     Keyboard(Keyboard),
     DeviceControl(DeviceControl),
+    OperatingSystem(OperatingSystemCommand),
     Graphics(Graphics),
 }
 
@@ -25,7 +26,12 @@ enum Sequence {
 pub enum TerminalEvent {
     Name(String),
     TrueColorSupported,
-    SixelSupported { width: u32, height: u32 },
+    SixelSupported {
+        width: u32,
+        height: u32,
+    },
+    /// Content of the clipboard, in reply to an OSC 52 query
+    Clipboard(String),
 }
 
 #[derive(Clone, Debug)]
@@ -86,7 +92,18 @@ impl Parser {
                 Sequence::Escape => match key {
                     b'[' => Sequence::Control,
                     b'P' => Sequence::DeviceControl(DeviceControl::new()),
+                    b']' => Sequence::OperatingSystem(OperatingSystemCommand::new()),
                     0x1b => emit!(Event::KeyPress { key: 0x1b.into() }; continue),
+                    // Terminals send Alt + key as Escape followed by the key
+                    0x20..=0x7e => emit!(Event::KeyPress {
+                        key: Key {
+                            char: key,
+                            modifiers: KeyModifiers {
+                                alt: true,
+                                ..KeyModifiers::default()
+                            },
+                        },
+                    }),
                     key => {
                         emit!(Event::KeyPress { key: 0x1b.into() });
                         emit!(Event::KeyPress { key: key.into() })
@@ -95,17 +112,33 @@ impl Parser {
                 Sequence::Control => match key {
                     b'<' => Sequence::Mouse(Mouse::new()),
                     b'?' => Sequence::Graphics(Graphics::new()),
-                    b'1' => Sequence::Keyboard(Keyboard::new()),
+                    b'0'..=b'9' => Sequence::Keyboard(Keyboard::new(key - b'0')),
                     key => emit!(Keyboard::key(key, 0)),
                 },
                 Sequence::Mouse(ref mut mouse) => parse!(mouse, key),
                 Sequence::Keyboard(ref mut keyboard) => parse!(keyboard, key),
                 Sequence::DeviceControl(ref mut dcs) => parse!(dcs, key),
+                Sequence::OperatingSystem(ref mut osc) => parse!(osc, key),
                 Sequence::Graphics(ref mut graphics) => parse!(graphics, key),
             }
         }
 
-        self.sequence = sequence;
+        // A lone Escape at the end of the input is the Escape key,
+        // terminals send escape sequences in a single write.
+        self.sequence = match sequence {
+            Sequence::Escape => emit!(Event::KeyPress { key: 0x1b.into() }),
+            // Nothing after `ESC ]` is Alt + ]
+            Sequence::OperatingSystem(osc) if osc.is_empty() => emit!(Event::KeyPress {
+                key: Key {
+                    char: b']',
+                    modifiers: KeyModifiers {
+                        alt: true,
+                        ..KeyModifiers::default()
+                    },
+                },
+            }),
+            sequence => sequence,
+        };
 
         std::mem::take(&mut self.events)
     }
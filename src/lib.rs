@@ -1,8 +1,10 @@
 pub mod browser;
 pub mod cli;
+pub mod config;
 pub mod gfx;
 pub mod input;
 pub mod output;
+pub mod terminal;
 pub mod ui;
 
 mod utils;
@@ -1,19 +1,27 @@
 // mod kd_tree;
 // mod quantizer;
 mod cell;
+mod dump;
 mod frame_sync;
 mod painter;
 mod quad;
+mod recorder;
 mod render_thread;
 mod renderer;
+mod screenshot;
+mod selection;
 mod sixel;
 mod window;
 mod xterm;
 
 pub use cell::*;
+pub use dump::*;
 pub use frame_sync::*;
 pub use painter::*;
 pub use quad::*;
+pub use recorder::*;
 pub use render_thread::*;
 pub use renderer::*;
+pub use screenshot::*;
+pub use selection::*;
 pub use window::*;
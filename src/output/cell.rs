@@ -9,16 +9,32 @@ pub struct Grapheme {
     pub index: usize,
     pub width: usize,
     pub color: Color,
+    pub style: TextStyle,
+    /// Target of the link containing the text, if any
+    pub link: Option<Rc<str>>,
+}
+
+/// Text attributes rendered using SGR sequences.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikethrough: bool,
+    /// Color of the underline, same as the text if `None`
+    pub underline_color: Option<Color>,
 }
 
 /// Terminal cell with `height = width * 2`
-#[derive(PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct Cell {
     pub cursor: Point<u32>,
     /// Text grapheme if any
     pub grapheme: Option<Rc<Grapheme>>,
     pub quadrant: (Color, Color, Color, Color),
     pub image: bool,
+    /// Highlighted by a mouse selection
+    pub selected: bool,
 }
 
 impl Cell {
@@ -33,6 +49,7 @@ impl Cell {
                 Color::black(),
             ),
             image: false,
+            selected: false,
         }
     }
 }
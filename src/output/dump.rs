@@ -0,0 +1,326 @@
+use crate::ui::reader::{Block, BlockKind, Span};
+
+/// Format of the text printed by `--dump`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DumpFormat {
+    /// Plain text, links are numbered and listed at the end
+    Text,
+    /// Markdown with reference-style links
+    Markdown,
+}
+
+impl DumpFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "" | "text" | "txt" => Some(DumpFormat::Text),
+            "markdown" | "md" => Some(DumpFormat::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// Content of a page extracted by the browser, as text in reading order.
+pub fn dump(title: &str, blocks: &[Block], format: DumpFormat) -> String {
+    let markdown = format == DumpFormat::Markdown;
+    let title = Block {
+        kind: BlockKind::Heading(1),
+        spans: vec![Span {
+            text: title.to_owned(),
+            ..Default::default()
+        }],
+    };
+    let mut output = String::new();
+    let mut links = Vec::new();
+    // Item numbers of the ordered lists, by depth
+    let mut counters = Vec::<usize>::new();
+    let mut previous = None;
+
+    for block in std::iter::once(&title).chain(blocks) {
+        if block.spans.iter().all(|span| span.text.trim().is_empty()) {
+            continue;
+        }
+
+        let list = |kind| {
+            matches!(
+                kind,
+                Some(BlockKind::ListItem(_) | BlockKind::OrderedListItem(_))
+            )
+        };
+
+        // Separate blocks with an empty line, except for items of the same list
+        if previous.is_some() && !(list(previous) && list(Some(block.kind))) {
+            output.push('\n');
+        }
+
+        previous = Some(block.kind);
+
+        let prefix = match block.kind {
+            BlockKind::Heading(level) if markdown => format!("{} ", "#".repeat(level as usize)),
+            BlockKind::ListItem(depth) => {
+                counters.truncate(depth as usize);
+
+                format!(
+                    "{}{} ",
+                    "  ".repeat(depth as usize),
+                    if markdown { '-' } else { '*' }
+                )
+            }
+            BlockKind::OrderedListItem(depth) => {
+                let depth = depth as usize;
+
+                counters.resize(depth + 1, 0);
+                counters[depth] += 1;
+
+                format!("{}{}. ", "   ".repeat(depth), counters[depth])
+            }
+            BlockKind::Quote => "> ".to_owned(),
+            _ => {
+                counters.clear();
+
+                String::new()
+            }
+        };
+
+        if block.kind == BlockKind::Code {
+            let text = block
+                .spans
+                .iter()
+                .map(|span| span.text.as_str())
+                .collect::<String>();
+            let text = text.trim_end_matches('\n');
+
+            if markdown {
+                output += &format!("```\n{text}\n```\n");
+            } else {
+                for line in text.lines() {
+                    output += &format!("    {line}\n");
+                }
+            }
+        } else {
+            output += &format!("{prefix}{}\n", inline(&block.spans, format, &mut links));
+        }
+    }
+
+    if !links.is_empty() {
+        output += if markdown { "\n" } else { "\nReferences\n\n" };
+
+        for (index, link) in links.iter().enumerate() {
+            match markdown {
+                true => output += &format!("[{}]: {link}\n", index + 1),
+                false => output += &format!("{:>4}. {link}\n", index + 1),
+            }
+        }
+    }
+
+    output
+}
+
+/// Text of a block, with the emphasis in Markdown, and links numbered
+/// in the order they first appear.
+fn inline(spans: &[Span], format: DumpFormat, links: &mut Vec<String>) -> String {
+    let markdown = format == DumpFormat::Markdown;
+    let mut output = String::new();
+
+    // Links can be split into several spans, ie. when part of it is bold
+    for group in spans.chunk_by(|a, b| a.link == b.link) {
+        let mut text = String::new();
+
+        for span in group {
+            let content = span.text.replace('\n', " ");
+
+            text += &match markdown {
+                false => content,
+                true if span.code => surround(&content, "`", "`"),
+                true => {
+                    let mut content = escape(&content);
+
+                    if span.bold {
+                        content = surround(&content, "**", "**");
+                    }
+                    if span.italic {
+                        content = surround(&content, "*", "*");
+                    }
+
+                    content
+                }
+            };
+        }
+
+        let Some(ref link) = group[0].link else {
+            output += &text;
+
+            continue;
+        };
+        let number = match links.iter().position(|other| other == link) {
+            Some(index) => index + 1,
+            None => {
+                links.push(link.clone());
+                links.len()
+            }
+        };
+
+        output += &match markdown {
+            true => surround(&text, "[", &format!("][{number}]")),
+            false => surround(&text, "", &format!("[{number}]")),
+        };
+    }
+
+    output.trim().to_owned()
+}
+
+/// Wrap text in delimiters, leaving the surrounding spaces outside
+/// since Markdown ignores emphasis starting or ending with a space.
+fn surround(text: &str, before: &str, after: &str) -> String {
+    let trimmed = text.trim();
+
+    if trimmed.is_empty() {
+        return text.to_owned();
+    }
+
+    let start = text.len() - text.trim_start().len();
+    let end = start + trimmed.len();
+
+    format!("{}{before}{trimmed}{after}{}", &text[..start], &text[end..])
+}
+
+/// Escape the characters Markdown would interpret in page text.
+fn escape(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        if matches!(char, '\\' | '*' | '_' | '`' | '[' | ']') {
+            output.push('\\');
+        }
+
+        output.push(char);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(text: &str) -> Span {
+        Span {
+            text: text.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    fn link(text: &str, url: &str) -> Span {
+        Span {
+            link: Some(url.to_owned()),
+            ..span(text)
+        }
+    }
+
+    fn block(kind: BlockKind, spans: Vec<Span>) -> Block {
+        Block { kind, spans }
+    }
+
+    fn page() -> Vec<Block> {
+        vec![
+            block(BlockKind::Heading(2), vec![span("Intro")]),
+            block(
+                BlockKind::Paragraph,
+                vec![
+                    span("Read "),
+                    Span {
+                        bold: true,
+                        ..link("the docs", "https://docs.rs")
+                    },
+                    span(" or "),
+                    link("docs", "https://docs.rs"),
+                    span(", not a_b*c."),
+                ],
+            ),
+            block(BlockKind::OrderedListItem(0), vec![span("one")]),
+            block(BlockKind::ListItem(1), vec![span("nested")]),
+            block(BlockKind::OrderedListItem(0), vec![span("two")]),
+            block(BlockKind::Quote, vec![span("quoted\ntext")]),
+            block(BlockKind::Code, vec![span("let a = 1;\n")]),
+            block(BlockKind::Paragraph, vec![span("  ")]),
+        ]
+    }
+
+    #[test]
+    fn markdown() {
+        assert_eq!(
+            dump("Title", &page(), DumpFormat::Markdown),
+            "\
+# Title
+
+## Intro
+
+Read [**the docs**][1] or [docs][1], not a\\_b\\*c.
+
+1. one
+  - nested
+2. two
+
+> quoted text
+
+```
+let a = 1;
+```
+
+[1]: https://docs.rs
+"
+        );
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(
+            dump("Title", &page(), DumpFormat::Text),
+            "\
+Title
+
+Intro
+
+Read the docs[1] or docs[1], not a_b*c.
+
+1. one
+  * nested
+2. two
+
+> quoted text
+
+    let a = 1;
+
+References
+
+   1. https://docs.rs
+"
+        );
+    }
+
+    #[test]
+    fn emphasis_outside_spaces() {
+        let spans = [
+            Span {
+                italic: true,
+                ..span(" word ")
+            },
+            Span {
+                code: true,
+                ..span("a*b")
+            },
+        ];
+
+        assert_eq!(
+            inline(&spans, DumpFormat::Markdown, &mut Vec::new()),
+            "*word* `a*b`"
+        );
+        assert_eq!(surround("  ", "*", "*"), "  ");
+    }
+
+    #[test]
+    fn parses_formats() {
+        assert_eq!(DumpFormat::parse(""), Some(DumpFormat::Text));
+        assert_eq!(DumpFormat::parse("md"), Some(DumpFormat::Markdown));
+        assert_eq!(DumpFormat::parse("html"), None);
+    }
+}
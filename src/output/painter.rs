@@ -1,6 +1,7 @@
 use std::{
     env,
-    io::{self, Stdout, Write},
+    io::{self, Write},
+    rc::Rc,
 };
 
 use crate::gfx::{Color, Point, Size};
@@ -10,11 +11,11 @@ use sixel_bytes::DiffusionMethod;
 use super::{
     binarize_quandrant,
     sixel::{Error as SixelError, Frame},
-    Cell,
+    Cell, Recorder, TextStyle,
 };
 
 pub struct Painter {
-    output: Stdout,
+    output: Box<dyn Write>,
     buffer: Vec<u8>,
     cursor: Option<Point<u32>>,
     true_color: bool,
@@ -22,8 +23,13 @@ pub struct Painter {
     foreground: Option<Color>,
     background_code: Option<u8>,
     foreground_code: Option<u8>,
+    style: TextStyle,
+    /// Target of the OSC 8 hyperlink being written
+    link: Option<Rc<str>>,
     sixel: Option<SixelState>,
     sixel_only: bool,
+    /// Copy of the output saved with `--record`
+    recorder: Option<Recorder>,
 }
 
 struct SixelState {
@@ -39,17 +45,20 @@ impl Painter {
         Painter {
             buffer: Vec::new(),
             cursor: None,
-            output: io::stdout(),
+            output: Box::new(io::stdout()),
             background: None,
             foreground: None,
             background_code: None,
             foreground_code: None,
+            style: TextStyle::default(),
+            link: None,
             sixel: None,
             true_color: match std::env::var("COLORTERM").unwrap_or_default().as_str() {
                 "truecolor" | "24bit" => true,
                 _ => false,
             },
             sixel_only: false,
+            recorder: None,
         }
     }
 
@@ -65,6 +74,19 @@ impl Painter {
         self.sixel_only = sixel_only;
     }
 
+    /// Write somewhere else than the standard output.
+    pub fn set_output(&mut self, output: Box<dyn Write>) {
+        self.output = output
+    }
+
+    pub fn record(&mut self, recorder: Recorder) {
+        self.recorder = Some(recorder)
+    }
+
+    pub fn recorder(&mut self) -> Option<&mut Recorder> {
+        self.recorder.as_mut()
+    }
+
     pub fn enable_sixel(&mut self, geometry: Size<u32>) {
         let state = self.sixel.get_or_insert_with(|| {
             let scrolling = env::var("CARBONYL_SIXEL_SCROLL")
@@ -129,8 +151,8 @@ impl Painter {
             return false;
         }
 
-        let expected = (state.geometry.width > 0 && state.geometry.height > 0)
-            .then_some(state.geometry);
+        let expected =
+            (state.geometry.width > 0 && state.geometry.height > 0).then_some(state.geometry);
 
         if let Some(target) = expected {
             if size != target {
@@ -177,6 +199,12 @@ impl Painter {
         self.sixel.is_some()
     }
 
+    /// Write an escape sequence to the terminal right away, outside of a frame.
+    pub fn write_sequence(&mut self, sequence: &str) -> io::Result<()> {
+        self.output.write_all(sequence.as_bytes())?;
+        self.output.flush()
+    }
+
     pub fn begin(&mut self) -> io::Result<()> {
         write!(self.buffer, "\x1b[?25l\x1b[?12l")?;
 
@@ -202,6 +230,9 @@ impl Painter {
     }
 
     pub fn end(&mut self, cursor: Option<Point>) -> io::Result<()> {
+        // Don't let a link span over output written by someone else
+        self.write_link(None)?;
+
         if let Some(cursor) = cursor {
             write!(
                 self.buffer,
@@ -215,18 +246,54 @@ impl Painter {
 
         self.output.write(self.buffer.as_slice())?;
         self.output.flush()?;
+
+        if let Some(ref mut recorder) = self.recorder {
+            if let Err(error) = recorder.output(&self.buffer) {
+                log::error!("failed to record the output: {error}");
+
+                self.recorder = None;
+            }
+        }
+
         self.buffer.clear();
         self.cursor = None;
 
         Ok(())
     }
 
+    /// Paint rows of cells to a buffer rather than the terminal, for files:
+    /// rows end with a line feed instead of moving the cursor.
+    pub fn export(&mut self, rows: &[Vec<Cell>]) -> io::Result<Vec<u8>> {
+        for row in rows {
+            for cell in row {
+                // Cells are painted in order, the cursor is already there
+                self.cursor = Some(cell.cursor);
+                self.paint(cell)?;
+            }
+
+            self.write_link(None)?;
+            // Reset the colors so they don't spill past the end of the line
+            writeln!(self.buffer, "\x1b[0m")?;
+
+            self.background = None;
+            self.foreground = None;
+            self.background_code = None;
+            self.foreground_code = None;
+            self.style = TextStyle::default();
+        }
+
+        self.cursor = None;
+
+        Ok(std::mem::take(&mut self.buffer))
+    }
+
     pub fn paint(&mut self, cell: &Cell) -> io::Result<()> {
         let &Cell {
             cursor,
             quadrant,
             ref grapheme,
             image,
+            selected,
         } = cell;
 
         if self.sixel_only && self.sixel_enabled() {
@@ -237,7 +304,7 @@ impl Painter {
             return Ok(());
         }
 
-        let (char, background, foreground, width) = if let Some(grapheme) = grapheme {
+        let (char, background, foreground, width, style, link) = if let Some(grapheme) = grapheme {
             if grapheme.index > 0 {
                 return Ok(());
             }
@@ -251,11 +318,20 @@ impl Painter {
                     .avg_with(quadrant.3),
                 grapheme.color,
                 grapheme.width as u32,
+                grapheme.style,
+                grapheme.link.as_ref(),
             )
         } else {
             let (char, background, foreground) = binarize_quandrant(quadrant);
 
-            (char, background, foreground, 1)
+            (char, background, foreground, 1, TextStyle::default(), None)
+        };
+
+        // Selected cells are displayed with inverted colors
+        let (background, foreground) = if selected {
+            (foreground, background)
+        } else {
+            (background, foreground)
         };
 
         if self.cursor != Some(cursor) {
@@ -304,8 +380,64 @@ impl Painter {
             }
         }
 
+        if self.style != style {
+            self.write_style(style)?;
+        }
+
+        if self.link.as_ref() != link {
+            self.write_link(link.cloned())?;
+        }
+
         self.buffer.write_all(char.as_bytes())?;
 
         Ok(())
     }
+
+    /// Start or end an OSC 8 hyperlink.
+    fn write_link(&mut self, link: Option<Rc<str>>) -> io::Result<()> {
+        if self.link == link {
+            return Ok(());
+        }
+
+        match link {
+            // Control characters would terminate the sequence early
+            Some(ref url) if !url.chars().any(char::is_control) => {
+                write!(self.buffer, "\x1b]8;;{url}\x1b\\")?
+            }
+            _ => write!(self.buffer, "\x1b]8;;\x1b\\")?,
+        }
+
+        self.link = link;
+
+        Ok(())
+    }
+
+    /// Only emit the attributes that changed since the last cell.
+    fn write_style(&mut self, style: TextStyle) -> io::Result<()> {
+        let previous = self.style;
+        let mut codes = [
+            (style.bold, previous.bold, "1", "22"),
+            (style.italic, previous.italic, "3", "23"),
+            (style.underline, previous.underline, "4", "24"),
+            (style.strikethrough, previous.strikethrough, "9", "29"),
+        ]
+        .into_iter()
+        .filter(|(current, previous, _, _)| current != previous)
+        .map(|(current, _, set, reset)| if current { set } else { reset }.to_owned())
+        .collect::<Vec<_>>();
+
+        if style.underline_color != previous.underline_color {
+            codes.push(match style.underline_color {
+                None => "59".to_owned(),
+                Some(color) if self.true_color => {
+                    format!("58;2;{};{};{}", color.r, color.g, color.b)
+                }
+                Some(color) => format!("58;5;{}", color.to_xterm()),
+            })
+        }
+
+        self.style = style;
+
+        write!(self.buffer, "\x1b[{}m", codes.join(";"))
+    }
 }
@@ -0,0 +1,152 @@
+use std::{
+    env,
+    fs::File,
+    io::{self, Write},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{gfx::Size, utils::json};
+
+/// Recording of what is written to the terminal in the asciicast v2 format,
+/// which can be replayed with `asciinema play`.
+///
+/// The file starts with a header giving the terminal size, followed by
+/// one event per line: `[seconds, "o", output]` for the output,
+/// `[seconds, "i", input]` for the input and `[seconds, "r", "COLSxROWS"]`
+/// when the terminal is resized.
+pub struct Recorder {
+    file: File,
+    start: Instant,
+    /// Size of the terminal, the header is written once it's known
+    size: Option<Size>,
+}
+
+impl Recorder {
+    pub fn create(path: &str) -> io::Result<Recorder> {
+        Ok(Recorder {
+            file: File::create(path)?,
+            start: Instant::now(),
+            size: None,
+        })
+    }
+
+    /// Record the size of the terminal in cells.
+    pub fn resize(&mut self, size: Size) -> io::Result<()> {
+        match self.size.replace(size) {
+            Some(previous) if previous == size => Ok(()),
+            Some(_) => self.event("r", &format!("{}x{}", size.width, size.height)),
+            None => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |duration| duration.as_secs());
+                let term = env::var("TERM").unwrap_or_else(|_| "xterm-256color".to_owned());
+
+                self.start = Instant::now();
+
+                writeln!(
+                    self.file,
+                    r#"{{"version": 2, "width": {}, "height": {}, "timestamp": {timestamp}, "title": "Carbonyl", "env": {{"TERM": {}}}}}"#,
+                    size.width,
+                    size.height,
+                    json::quote(&term)
+                )
+            }
+        }
+    }
+
+    /// Record bytes written to the terminal.
+    pub fn output(&mut self, data: &[u8]) -> io::Result<()> {
+        self.event("o", &String::from_utf8_lossy(data))
+    }
+
+    /// Record bytes read from the terminal.
+    pub fn input(&mut self, data: &[u8]) -> io::Result<()> {
+        self.event("i", &String::from_utf8_lossy(data))
+    }
+
+    fn event(&mut self, kind: &str, data: &str) -> io::Result<()> {
+        // Events before the header would make the file invalid
+        if self.size.is_none() || data.is_empty() {
+            return Ok(());
+        }
+
+        let time = self.start.elapsed().as_secs_f64();
+
+        // Written at once, this way the file is usable if the browser crashes
+        self.file.write_all(
+            format!(
+                "[{time:.6}, {}, {}]\n",
+                json::quote(kind),
+                json::quote(data)
+            )
+            .as_bytes(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, process};
+
+    use super::*;
+    use crate::utils::json::Value;
+
+    /// Lines of a recording, parsed as JSON.
+    fn record(name: &str, events: impl FnOnce(&mut Recorder) -> io::Result<()>) -> Vec<Value> {
+        let path = env::temp_dir().join(format!("carbonyl-{name}-{}.cast", process::id()));
+        let mut recorder = Recorder::create(path.to_str().unwrap()).unwrap();
+
+        events(&mut recorder).unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+
+        fs::remove_file(&path).unwrap();
+        text.lines()
+            .map(|line| Value::parse(line).expect("invalid JSON line"))
+            .collect()
+    }
+
+    #[test]
+    fn writes_the_header_first() {
+        let lines = record("recorder-header", |recorder| {
+            recorder.output(b"lost")?;
+            recorder.resize(Size::new(80, 24))?;
+            recorder.resize(Size::new(80, 24))
+        });
+
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].get("version").and_then(Value::as_f64), Some(2.0));
+        assert_eq!(lines[0].get("width").and_then(Value::as_f64), Some(80.0));
+        assert_eq!(lines[0].get("height").and_then(Value::as_f64), Some(24.0));
+    }
+
+    #[test]
+    fn encodes_events() {
+        let lines = record("recorder-events", |recorder| {
+            recorder.resize(Size::new(80, 24))?;
+            recorder.output(b"\x1b[1m\"hi\"\n")?;
+            recorder.output(b"")?;
+            recorder.input(&[b'a', 0xff])?;
+            recorder.resize(Size::new(100, 30))
+        });
+        let events = lines[1..]
+            .iter()
+            .map(|line| {
+                let event = line.as_array().unwrap();
+
+                assert!(event[0].as_f64().unwrap() >= 0.0);
+
+                (event[1].as_str().unwrap(), event[2].as_str().unwrap())
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            events,
+            [
+                ("o", "\x1b[1m\"hi\"\n"),
+                ("i", "a\u{fffd}"),
+                ("r", "100x30")
+            ]
+        );
+    }
+}
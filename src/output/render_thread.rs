@@ -4,7 +4,7 @@ use std::{
     time::Instant,
 };
 
-use crate::cli::CommandLine;
+use crate::{cli::CommandLine, config::Config};
 
 use super::{FrameSync, Renderer};
 
@@ -59,7 +59,7 @@ impl RenderThread {
     fn boot(rx: Receiver<Message>) {
         let cmd = CommandLine::parse();
         let mut sync = FrameSync::new(cmd.fps);
-        let mut renderer = Renderer::new(cmd.sixel_only);
+        let mut renderer = Renderer::new(&cmd, Config::read());
         let mut needs_render = false;
 
         loop {
@@ -1,37 +1,133 @@
-use std::{
-    io::{self, Write},
-    rc::Rc,
-};
+use std::{fs, io, rc::Rc};
+
+use sixel_bytes::DiffusionMethod;
 
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 use crate::{
+    cli::CommandLine,
+    config::{self, Config, CredentialStore, Permission, PermissionStore},
     gfx::{Color, Point, Rect, Size},
     input::Key,
-    ui::navigation::{Navigation, NavigationAction},
-    utils::log,
+    terminal,
+    ui::{
+        auth::{AuthPrompt, AuthRequest},
+        dialog::{Dialog, DialogKind},
+        downloads::{DownloadState, Downloads},
+        file_chooser::FileChooser,
+        find::FindBar,
+        hints::Hints,
+        keymap::{Command, Keymap, KeymapResult},
+        menu::{Menu, MenuItem},
+        navigation::{Navigation, NavigationAction, NavigationElement},
+        permissions::{PermissionAnswer, PermissionPrompt, PermissionRequest},
+        reader::{Block, Reader},
+        status::{SecurityState, StatusBar},
+        theme::{BarPosition, Theme},
+    },
+    utils::{base64, log, png},
+};
+
+use super::{
+    sixel::Frame, Cell, Grapheme, Painter, Recorder, ScreenshotFormat, Selection, TextStyle,
 };
 
-use super::{Cell, Grapheme, Painter};
+/// Zoom factors used when zooming in or out, same as Chromium.
+const ZOOM_LEVELS: [f32; 17] = [
+    0.25, 0.33, 0.5, 0.67, 0.75, 0.8, 0.9, 1.0, 1.1, 1.25, 1.5, 1.75, 2.0, 2.5, 3.0, 4.0, 5.0,
+];
 
 pub struct Renderer {
     nav: Navigation,
+    keymap: Keymap,
+    theme: Theme,
+    hints: Option<Hints>,
+    /// JavaScript dialog waiting for an answer
+    dialog: Option<Dialog>,
+    /// HTTP authentication challenges waiting for a login
+    auth: AuthPrompt,
+    credentials: CredentialStore,
+    /// Permission requests waiting for an answer
+    permission_prompt: PermissionPrompt,
+    permissions: PermissionStore,
+    /// Popup opened by a `<select>` or a context menu
+    menu: Option<Menu>,
+    /// Files being picked for an `<input type=file>`
+    file_chooser: Option<FileChooser>,
+    find: FindBar,
+    downloads: Downloads,
+    reader: Option<Reader>,
+    /// Bottom line, only when enabled on the command line
+    status: Option<StatusBar>,
+    /// Whether the next drag selects text
+    selecting: bool,
+    selection: Option<Selection>,
     cells: Vec<(Cell, Cell)>,
     painter: Painter,
     size: Size,
+    zoom: f32,
+    /// Capturing the page, nothing is written to the terminal
+    headless: bool,
+    /// Whether the terminal is used, see `CommandLine::interactive`
+    terminal: bool,
+    /// Whether the frames are kept for `screenshot`, when capturing
+    /// the page or when it can be requested on the control socket
+    screenshots: bool,
+    /// Last frame drawn by the browser and its size, kept for the screenshot
+    frame: Option<(Vec<u8>, Size)>,
 }
 
 impl Renderer {
-    pub fn new(sixel_only: bool) -> Renderer {
+    pub fn new(cmd: &CommandLine, config: Config) -> Renderer {
         let mut painter = Painter::new();
-        painter.set_sixel_only(sixel_only);
+        painter.set_sixel_only(cmd.sixel_only);
+
+        // Replays don't need a terminal, the screen is kept in the cells
+        match cmd.replay {
+            Some(_) => painter.set_output(Box::new(io::sink())),
+            None => painter.set_output(terminal::open(cmd).output()),
+        }
+
+        if let Some(ref path) = cmd.record {
+            match Recorder::create(path) {
+                Ok(recorder) => painter.record(recorder),
+                Err(error) => log::error!("failed to create recording {path}: {error}"),
+            }
+        }
+
+        let mut permissions = PermissionStore::read();
+
+        for grant in &cmd.grants {
+            permissions.grant(grant);
+        }
 
         Renderer {
-            nav: Navigation::new(),
+            nav: Navigation::new(config.search),
+            keymap: config.keymap,
+            theme: config.theme,
+            hints: None,
+            dialog: None,
+            auth: AuthPrompt::new(),
+            credentials: CredentialStore::read(cmd.credentials.as_deref()),
+            permission_prompt: PermissionPrompt::new(),
+            permissions,
+            menu: None,
+            file_chooser: None,
+            find: FindBar::new(),
+            downloads: Downloads::new(),
+            reader: None,
+            status: cmd.status_bar.then(StatusBar::new),
+            selecting: false,
+            selection: None,
             cells: Vec::with_capacity(0),
             painter,
             size: Size::new(0, 0),
+            zoom: cmd.zoom,
+            headless: cmd.headless(),
+            terminal: cmd.interactive(),
+            screenshots: cmd.headless() || cmd.control_socket.is_some(),
+            frame: None,
         }
     }
 
@@ -48,46 +144,638 @@ impl Renderer {
     }
 
     pub fn keypress(&mut self, key: &Key) -> io::Result<NavigationAction> {
-        let action = self.nav.keypress(key);
+        self.selection = None;
+
+        if self.selecting {
+            self.selecting = false;
+
+            // Escape only leaves selection mode
+            if key.char == 0x1b {
+                return Ok(NavigationAction::Ignore);
+            }
+        }
+
+        // Dialogs are modal, the page doesn't get any input until they're closed
+        if let Some(dialog) = &mut self.dialog {
+            let action = dialog.keypress(key);
+
+            if let NavigationAction::CloseDialog(..) = action {
+                self.dialog = None;
+            }
+
+            return Ok(action);
+        }
+
+        if self.auth.is_open() {
+            return Ok(self.auth.keypress(key));
+        }
+
+        if self.permission_prompt.is_open() {
+            let action = self.permission_prompt.keypress(key);
+
+            return Ok(self.answer_permission(action));
+        }
+
+        if let Some(chooser) = &mut self.file_chooser {
+            let action = chooser.keypress(key);
+
+            if let NavigationAction::CloseFileChooser(_) = action {
+                self.file_chooser = None;
+            }
+
+            return Ok(action);
+        }
+
+        if let Some(menu) = &mut self.menu {
+            let action = menu.keypress(key);
+
+            if let NavigationAction::CloseMenu(_) = action {
+                self.menu = None;
+            }
+
+            return Ok(action);
+        }
+
+        if self.downloads.prompting() {
+            return Ok(self.downloads.prompt_keypress(key));
+        }
+
+        if let Some(hints) = &mut self.hints {
+            let action = hints.keypress(key);
+
+            if hints.done() {
+                self.hints = None;
+            }
+
+            return Ok(action);
+        }
+
+        if self.nav.focused() {
+            return Ok(self.nav.keypress(key));
+        }
+
+        if self.find.is_open() {
+            return Ok(self.find.keypress(key));
+        }
+
+        if self.downloads.is_open() {
+            let action = match self.keymap.feed(key) {
+                KeymapResult::Command(Command::Downloads) => {
+                    self.downloads.toggle();
+
+                    NavigationAction::Ignore
+                }
+                KeymapResult::Command(Command::Quit) => NavigationAction::Quit(),
+                _ => self.downloads.keypress(key),
+            };
+
+            return Ok(action);
+        }
+
+        if let Some(reader) = &mut self.reader {
+            if reader.keypress(key) {
+                if reader.done() {
+                    self.reader = None;
+                }
+
+                return Ok(NavigationAction::Ignore);
+            }
+        }
+
+        let action = match self.keymap.feed(key) {
+            KeymapResult::Command(command) => self.run(command),
+            KeymapResult::Pending | KeymapResult::Cancelled => NavigationAction::Ignore,
+            // The page is hidden in reader mode
+            KeymapResult::Unbound(_) if self.reader.is_some() => NavigationAction::Ignore,
+            KeymapResult::Unbound(keys) if keys.len() == 1 => self.nav.keypress(key),
+            KeymapResult::Unbound(keys) => NavigationAction::Keys(keys),
+        };
+
+        Ok(action)
+    }
+
+    /// Run a command bound to a key.
+    pub fn run(&mut self, command: Command) -> NavigationAction {
+        // Keep two rows of context when scrolling by pages
+        let page = (self.size.height as isize - 2).max(1);
+
+        match command {
+            Command::Back => NavigationAction::GoBack(),
+            Command::Forward => NavigationAction::GoForward(),
+            Command::Reload => NavigationAction::Refresh(),
+            Command::HardReload => NavigationAction::HardRefresh(),
+            // There's no bar to focus in kiosk mode
+            Command::FocusUrlBar | Command::Find if self.theme.bar == BarPosition::Hidden => {
+                NavigationAction::Ignore
+            }
+            Command::FocusUrlBar => {
+                self.nav.focus();
+
+                if self.find.is_open() {
+                    self.find.close()
+                } else {
+                    NavigationAction::Ignore
+                }
+            }
+            Command::ZoomIn => self.zoom(1),
+            Command::ZoomOut => self.zoom(-1),
+            Command::ZoomReset => self.zoom(0),
+            Command::ScrollUp => self.scroll_by(2),
+            Command::ScrollDown => self.scroll_by(-2),
+            Command::PageUp => self.scroll_by(page),
+            Command::PageDown => self.scroll_by(-page),
+            Command::Quit => NavigationAction::Quit(),
+            Command::Hints => NavigationAction::RequestHints(),
+            Command::Find => {
+                self.find.open();
+
+                NavigationAction::Ignore
+            }
+            Command::ReaderMode => match self.reader.take() {
+                Some(_) => NavigationAction::Ignore,
+                None => NavigationAction::RequestReader(),
+            },
+            Command::CopyUrl => {
+                let url = self.nav.display_url().to_owned();
+
+                if let Err(error) = self.copy(&url) {
+                    log::warning!("failed to copy the URL: {error}")
+                }
+
+                NavigationAction::Ignore
+            }
+            Command::CopySelection => NavigationAction::RequestCopy(),
+            Command::Paste => {
+                if let Err(error) = self.request_paste() {
+                    log::warning!("failed to read the clipboard: {error}")
+                }
+
+                NavigationAction::Ignore
+            }
+            Command::Downloads => {
+                self.downloads.toggle();
+
+                NavigationAction::Ignore
+            }
+            Command::Permissions => {
+                if let Some(origin) = config::origin(self.nav.display_url()) {
+                    let decisions = self.permissions.decisions(&origin);
+
+                    self.permission_prompt.show(&origin, decisions);
+                }
+
+                NavigationAction::Ignore
+            }
+            Command::SelectionMode => {
+                self.selecting = true;
+
+                NavigationAction::Ignore
+            }
+            Command::InsertMode | Command::NormalMode => NavigationAction::Ignore,
+        }
+    }
+
+    /// Scroll the reader if it's open, or the page.
+    fn scroll_by(&mut self, rows: isize) -> NavigationAction {
+        match self.reader {
+            Some(ref mut reader) => {
+                reader.scroll_by(rows);
+
+                NavigationAction::Ignore
+            }
+            None => NavigationAction::Scroll(rows),
+        }
+    }
+
+    /// Step through the zoom levels, or reset the zoom if `direction` is zero.
+    fn zoom(&mut self, direction: i32) -> NavigationAction {
+        let current = self.zoom;
+
+        self.zoom = match direction {
+            0 => 1.0,
+            1.. => ZOOM_LEVELS
+                .into_iter()
+                .find(|&level| level > current + 0.001)
+                .unwrap_or(current),
+            _ => ZOOM_LEVELS
+                .into_iter()
+                .rev()
+                .find(|&level| level < current - 0.001)
+                .unwrap_or(current),
+        };
+
+        NavigationAction::Zoom(self.zoom)
+    }
+    pub fn scroll(&mut self, delta: isize) -> io::Result<NavigationAction> {
+        if self.dialog.is_some()
+            || self.auth.is_open()
+            || self.permission_prompt.is_open()
+            || self.downloads.is_open()
+        {
+            return Ok(NavigationAction::Ignore);
+        }
+
+        if let Some(ref mut chooser) = self.file_chooser {
+            chooser.scroll_by(delta);
+
+            return Ok(NavigationAction::Ignore);
+        }
+
+        if let Some(ref mut menu) = self.menu {
+            menu.scroll_by(delta);
+
+            return Ok(NavigationAction::Ignore);
+        }
+
+        let action = match self.scroll_by(delta) {
+            NavigationAction::Scroll(_) => NavigationAction::Forward,
+            action => action,
+        };
 
         Ok(action)
     }
     pub fn mouse_up(&mut self, origin: Point) -> io::Result<NavigationAction> {
+        if self.dialog.is_some()
+            || self.auth.is_open()
+            || self.permission_prompt.is_open()
+            || self.menu.is_some()
+            || self.file_chooser.is_some()
+            || self.on_status_bar(origin)
+        {
+            return Ok(NavigationAction::Ignore);
+        }
+
+        if let Some(ref mut selection) = self.selection {
+            if selection.dragging() {
+                selection.release(origin);
+                self.selecting = false;
+                self.copy_selection();
+
+                return Ok(NavigationAction::Ignore);
+            }
+        }
+
+        if (self.reader.is_some() || self.downloads.is_open()) && origin.y != 0 {
+            return Ok(NavigationAction::Ignore);
+        }
+
         let action = self.nav.mouse_up(origin);
 
         Ok(action)
     }
     pub fn mouse_down(&mut self, origin: Point) -> io::Result<NavigationAction> {
-        let action = self.nav.mouse_down(origin);
+        // Clicking anywhere exits hint mode
+        self.hints = None;
+        self.selection = None;
+
+        if let Some(dialog) = &mut self.dialog {
+            let action = dialog.mouse_down(origin);
+
+            if let NavigationAction::CloseDialog(..) = action {
+                self.dialog = None;
+            }
+
+            return Ok(action);
+        }
+
+        if self.auth.is_open() {
+            return Ok(self.auth.mouse_down(origin));
+        }
+
+        if self.permission_prompt.is_open() {
+            let action = self.permission_prompt.mouse_down(origin);
+
+            return Ok(self.answer_permission(action));
+        }
+
+        if let Some(chooser) = &mut self.file_chooser {
+            let action = chooser.mouse_down(origin);
+
+            if let NavigationAction::CloseFileChooser(_) = action {
+                self.file_chooser = None;
+            }
+
+            return Ok(action);
+        }
+
+        if let Some(menu) = &mut self.menu {
+            let action = menu.mouse_down(origin);
+
+            if let NavigationAction::CloseMenu(_) = action {
+                self.menu = None;
+            }
+
+            return Ok(action);
+        }
+
+        // The save location prompt replaces the navigation bar
+        if self.on_status_bar(origin) || (self.downloads.prompting() && origin.y == 0) {
+            return Ok(NavigationAction::Ignore);
+        }
+
+        if self.downloads.is_open() && origin.y > 0 {
+            return Ok(self.downloads.mouse_down(origin));
+        }
+
+        if self.selecting && origin.y > 0 {
+            self.selection = Some(Selection::new(origin));
+
+            return Ok(NavigationAction::Ignore);
+        }
+
+        // The find bar replaces the navigation bar while it's open
+        let action = if self.find.is_open() && origin.y == 0 {
+            self.find.mouse_down(origin)
+        } else {
+            match (self.nav.mouse_down(origin), &mut self.reader) {
+                (NavigationAction::Forward, Some(reader)) => reader.mouse_down(origin),
+                (action, _) => action,
+            }
+        };
 
         Ok(action)
     }
+
     pub fn mouse_move(&mut self, origin: Point) -> io::Result<NavigationAction> {
+        if let Some(ref mut selection) = self.selection {
+            if selection.dragging() {
+                selection.extend(origin);
+
+                return Ok(NavigationAction::Ignore);
+            }
+        }
+
+        if let Some(ref mut menu) = self.menu {
+            menu.mouse_move(origin);
+
+            return Ok(NavigationAction::Ignore);
+        }
+
+        if self.reader.is_some()
+            || self.dialog.is_some()
+            || self.auth.is_open()
+            || self.permission_prompt.is_open()
+            || self.file_chooser.is_some()
+            || self.downloads.is_open()
+            || self.on_status_bar(origin)
+        {
+            return Ok(NavigationAction::Ignore);
+        }
+
         let action = self.nav.mouse_move(origin);
 
         Ok(action)
     }
 
+    /// Convert a terminal position to the layout of the cells,
+    /// where the navigation bar is always the first row.
+    pub fn layout_point(&self, origin: Point) -> Point {
+        let height = self.size.height as i32;
+        let y = match self.theme.bar {
+            BarPosition::Top => origin.y,
+            BarPosition::Bottom if origin.y == height => 0,
+            BarPosition::Bottom if origin.y < height => origin.y + 1,
+            BarPosition::Bottom => origin.y,
+            BarPosition::Hidden => origin.y + 1,
+        };
+
+        Point::new(origin.x, y)
+    }
+
+    /// Terminal row where a row of the layout is displayed.
+    fn screen_row(&self, row: u32) -> u32 {
+        let height = self.size.height;
+
+        match self.theme.bar {
+            BarPosition::Top => row,
+            BarPosition::Bottom if row == 0 => height,
+            BarPosition::Bottom if row <= height => row - 1,
+            BarPosition::Bottom => row,
+            // The first row isn't displayed
+            BarPosition::Hidden => row.saturating_sub(1),
+        }
+    }
+
+    /// Whether a cell is on the status bar, below the page.
+    fn on_status_bar(&self, origin: Point) -> bool {
+        self.status.is_some() && origin.y > self.size.height as i32
+    }
+
+    /// Enter hint mode with the clickable elements of the page,
+    /// given as `(cell, target)` pairs.
+    pub fn show_hints(&mut self, targets: Vec<(Point, Point<u32>)>) {
+        let hints = Hints::new(targets);
+
+        self.hints = if hints.is_empty() { None } else { Some(hints) };
+    }
+
+    /// Enter reader mode with the article content extracted by the browser.
+    pub fn show_reader(&mut self, title: &str, blocks: Vec<Block>) {
+        let mut reader = Reader::new(title, blocks, &self.theme);
+
+        reader.set_size(self.size);
+        self.hints = None;
+        self.reader = Some(reader);
+    }
+
+    /// Display a JavaScript dialog, answered with `NavigationAction::CloseDialog`.
+    pub fn show_dialog(&mut self, kind: DialogKind, message: &str, default: &str) {
+        let mut dialog = Dialog::new(kind, message, default);
+
+        dialog.set_size(self.size);
+        self.hints = None;
+        self.selection = None;
+        self.selecting = false;
+        self.dialog = Some(dialog);
+    }
+
+    /// Ask whether a site can use a permission, unless the user already decided.
+    /// Returns the decision, or `None` if the request is answered later with
+    /// `NavigationAction::AnswerPermission`.
+    pub fn request_permission(
+        &mut self,
+        id: u32,
+        origin: &str,
+        permission: Permission,
+    ) -> Option<bool> {
+        if let Some(allowed) = self.permissions.get(origin, permission) {
+            return Some(allowed);
+        }
+
+        self.permission_prompt.set_size(self.size);
+        self.permission_prompt.request(PermissionRequest {
+            id,
+            origin: origin.to_owned(),
+            permission,
+        });
+        self.hints = None;
+        self.selection = None;
+        self.selecting = false;
+
+        None
+    }
+
+    /// Ask for a username and password, unless the credentials file has them.
+    /// Returns the login, or `None` if the challenge is answered later with
+    /// `NavigationAction::AnswerAuth`.
+    pub fn auth_challenge(&mut self, request: AuthRequest) -> Option<(String, String)> {
+        if !request.proxy {
+            if let Some(login) = self.credentials.take(&request.origin, &request.realm) {
+                return Some(login);
+            }
+        }
+
+        self.auth.set_size(self.size);
+        self.auth.request(request);
+        self.hints = None;
+        self.selection = None;
+        self.selecting = false;
+
+        None
+    }
+
+    /// Remember the answer to a permission request.
+    fn answer_permission(&mut self, action: NavigationAction) -> NavigationAction {
+        if let NavigationAction::AnswerPermission(ref request, answer) = action {
+            let (origin, permission) = (&request.origin, request.permission);
+
+            match answer {
+                PermissionAnswer::AllowOnce => (),
+                PermissionAnswer::AllowAlways => {
+                    self.permissions.remember(origin, permission, true, true)
+                }
+                PermissionAnswer::Deny => {
+                    self.permissions.remember(origin, permission, false, false)
+                }
+            }
+        }
+
+        action
+    }
+
+    /// Open a popup menu next to the cells of an element,
+    /// answered with `NavigationAction::CloseMenu`.
+    pub fn show_menu(&mut self, anchor: Rect, items: Vec<MenuItem>, selected: Option<usize>) {
+        let mut menu = Menu::new(anchor, items, selected);
+
+        menu.set_size(self.size);
+        self.hints = None;
+        self.menu = Some(menu);
+    }
+
+    /// Browse the local files for an `<input type=file>`,
+    /// answered with `NavigationAction::CloseFileChooser`.
+    pub fn show_file_chooser(&mut self, dir: &str, accept: &str, multiple: bool) {
+        let mut chooser = FileChooser::new(dir, accept, multiple);
+
+        chooser.set_size(self.size);
+        self.hints = None;
+        self.file_chooser = Some(chooser);
+    }
+
+    /// Ask where to save a new download, answered with `NavigationAction::SetDownloadPath`.
+    pub fn download_started(&mut self, id: u32, url: &str, path: &str) {
+        self.downloads.start(id, url, path)
+    }
+
+    pub fn download_progress(&mut self, id: u32, received: u64, total: Option<u64>, paused: bool) {
+        self.downloads.progress(id, received, total, paused)
+    }
+
+    pub fn download_finished(&mut self, id: u32, state: DownloadState, path: &str) {
+        self.downloads.finish(id, state, path)
+    }
+
+    /// Update the match count displayed in the find bar.
+    pub fn find_result(&mut self, current: u32, total: u32) {
+        self.find.set_result(current, total)
+    }
+
+    pub fn set_loading(&mut self, loading: bool, progress: f32) {
+        if let Some(ref mut status) = self.status {
+            status.set_loading(loading, progress)
+        }
+    }
+
+    /// Show the target of the hovered link, or clear it with an empty URL.
+    pub fn set_hover_url(&mut self, url: &str) {
+        if let Some(ref mut status) = self.status {
+            status.set_hover_url(url)
+        }
+    }
+
+    pub fn set_security_state(&mut self, security: SecurityState) {
+        if let Some(ref mut status) = self.status {
+            status.set_security_state(security)
+        }
+    }
+
     pub fn push_nav(&mut self, url: &str, can_go_back: bool, can_go_forward: bool) {
+        // The reader shows the content of the previous page
+        if self.nav.display_url() != url {
+            self.reader = None;
+        }
+
         self.nav.push(url, can_go_back, can_go_forward)
     }
 
+    /// Save bytes read from the terminal to the recording, with `--record-input`.
+    pub fn record_input(&mut self, input: &[u8]) {
+        if let Some(recorder) = self.painter.recorder() {
+            if let Err(error) = recorder.input(input) {
+                log::error!("failed to record the input: {error}")
+            }
+        }
+    }
+
     pub fn get_size(&self) -> Size {
         self.size
     }
 
     pub fn set_size(&mut self, size: Size) {
         self.nav.set_size(size);
+        self.find.set_size(size);
+        self.downloads.set_size(size);
+
+        if let Some(ref mut reader) = self.reader {
+            reader.set_size(size);
+        }
+        if let Some(ref mut dialog) = self.dialog {
+            dialog.set_size(size);
+        }
+        self.permission_prompt.set_size(size);
+        self.auth.set_size(size);
+
+        if let Some(ref mut menu) = self.menu {
+            menu.set_size(size);
+        }
+        if let Some(ref mut chooser) = self.file_chooser {
+            chooser.set_size(size);
+        }
         self.size = size;
 
+        // The terminal also shows the navigation bar and the status bar
+        let rows = (self.theme.bar != BarPosition::Hidden) as u32 + self.status.is_some() as u32;
+
+        if let Some(recorder) = self.painter.recorder() {
+            if let Err(error) = recorder.resize(Size::new(size.width, size.height + rows)) {
+                log::error!("failed to record the terminal size: {error}")
+            }
+        }
+
         let mut x = 0;
         let mut y = 0;
         let bound = size.width - 1;
-        let cells = (size.width + size.width * size.height) as usize;
+        let rows = if self.status.is_some() { 2 } else { 1 };
+        let cells = (size.width * rows + size.width * size.height) as usize;
+        let screen_rows = (0..rows + size.height)
+            .map(|row| self.screen_row(row))
+            .collect::<Vec<_>>();
 
         self.cells.clear();
         self.cells.resize_with(cells, || {
-            let cell = (Cell::new(x, y), Cell::new(x, y));
+            let row = screen_rows[y as usize];
+            let cell = (Cell::new(x, row), Cell::new(x, row));
 
             if x < bound {
                 x += 1;
@@ -101,24 +789,60 @@ impl Renderer {
     }
 
     pub fn render(&mut self) -> io::Result<()> {
+        if self.headless {
+            return Ok(());
+        }
+
         let size = self.size;
 
-        for (origin, element) in self.nav.render(size) {
-            self.fill_rect(
-                Rect::new(origin.x, origin.y, element.text.width() as u32, 1),
-                element.background,
-            );
-            self.draw_text(
-                &element.text,
-                origin * (2, 1),
-                Size::splat(0),
-                element.foreground,
-            );
+        let bar = if self.downloads.prompting() {
+            self.downloads.render_prompt(&self.theme)
+        } else if self.find.is_open() {
+            self.find.render(size, &self.theme)
+        } else {
+            self.nav.render(size, &self.theme)
+        };
+
+        let status = match self.status {
+            Some(ref status) => status.render(size, size.height as i32 + 1, self.zoom, &self.theme),
+            None => Vec::new(),
+        };
+
+        for (origin, element) in bar.into_iter().chain(status) {
+            self.draw_element(origin, &element);
+        }
+
+        // Elements drawn over the page are removed after painting,
+        // this way the page is visible again once they're gone.
+        let overlay = self.overlay();
+        let saved = overlay
+            .iter()
+            .flat_map(|(origin, element)| self.element_cells(*origin, element))
+            .map(|index| (index, self.cells[index].1.clone()))
+            .collect::<Vec<_>>();
+
+        for (origin, element) in &overlay {
+            self.draw_element(*origin, element);
+        }
+
+        let selected = match self.selection {
+            Some(ref selection) => selection.range(size.width, self.cells.len()),
+            None => 0..0,
+        };
+
+        for (_, cell) in &mut self.cells[selected.clone()] {
+            cell.selected = true;
         }
 
+        // The navigation bar isn't displayed in kiosk mode
+        let hidden = match self.theme.bar {
+            BarPosition::Hidden => size.width as usize,
+            _ => 0,
+        };
+
         self.painter.begin()?;
 
-        for (previous, current) in self.cells.iter_mut() {
+        for (previous, current) in self.cells.iter_mut().skip(hidden) {
             if current == previous {
                 continue;
             }
@@ -126,17 +850,126 @@ impl Renderer {
             previous.quadrant = current.quadrant;
             previous.grapheme = current.grapheme.clone();
             previous.image = current.image;
+            previous.selected = current.selected;
 
             self.painter.paint(current)?;
         }
 
-        self.painter.end(self.nav.cursor())?;
+        let cursor = match self.dialog {
+            Some(ref dialog) => dialog.cursor(),
+            None => self
+                .auth
+                .cursor()
+                .or(self.downloads.cursor())
+                .or(self.nav.cursor())
+                .or(self.find.cursor()),
+        };
+        let cursor =
+            cursor.map(|cursor| Point::new(cursor.x, self.screen_row(cursor.y as u32) as i32));
+
+        self.painter.end(cursor)?;
+
+        for (_, cell) in &mut self.cells[selected] {
+            cell.selected = false;
+        }
+
+        // Saved in drawing order, restore in reverse for overlapping elements
+        for (index, cell) in saved.into_iter().rev() {
+            self.cells[index].1 = cell;
+        }
 
         Ok(())
     }
 
+    /// UI elements to draw over the page.
+    fn overlay(&self) -> Vec<(Point, NavigationElement)> {
+        let mut elements = Vec::new();
+
+        if let Some(ref reader) = self.reader {
+            elements.extend(reader.render());
+        }
+
+        if self.downloads.is_open() {
+            elements.extend(self.downloads.render(&self.theme));
+        }
+
+        if let Some(ref hints) = self.hints {
+            elements.extend(hints.render());
+        }
+
+        if let Some(ref chooser) = self.file_chooser {
+            elements.extend(chooser.render(&self.theme));
+        }
+
+        if let Some(ref menu) = self.menu {
+            elements.extend(menu.render(&self.theme));
+        }
+
+        elements.extend(self.permission_prompt.render(&self.theme));
+        elements.extend(self.auth.render(&self.theme));
+
+        if let Some(ref dialog) = self.dialog {
+            elements.extend(dialog.render(&self.theme));
+        }
+
+        elements
+    }
+
+    /// Indices of the cells covered by an element, clipped to the viewport.
+    fn element_cells(&self, origin: Point, element: &NavigationElement) -> Vec<usize> {
+        let width = self.size.width as i32;
+        let rows = self.cells.len() as i32 / width.max(1);
+
+        if origin.y < 0 || origin.y >= rows || origin.x >= width {
+            return Vec::new();
+        }
+
+        let left = origin.x.max(0);
+        let right = (origin.x + element.text.width() as i32).min(width);
+
+        (left..right)
+            .map(|x| (origin.y * width + x) as usize)
+            .collect()
+    }
+
+    /// Draw a UI element at a cell position, clipped to the viewport.
+    fn draw_element(&mut self, origin: Point, element: &NavigationElement) {
+        let cells = self.element_cells(origin, element);
+
+        if cells.is_empty() || origin.x < 0 {
+            return;
+        }
+
+        let mut text = String::new();
+        let mut width = 0;
+
+        for grapheme in element.text.graphemes(true) {
+            width += grapheme.width();
+
+            if width > cells.len() {
+                break;
+            }
+
+            text.push_str(grapheme);
+        }
+
+        self.fill_rect(
+            Rect::new(origin.x, origin.y, cells.len() as u32, 1),
+            element.background,
+        );
+        self.draw_graphemes(&text, cells[0], element.foreground, element.style, None);
+    }
+
     /// Draw the background from a pixel array encoded in RGBA8888
     pub fn draw_background(&mut self, pixels: &[u8], pixels_size: Size, rect: Rect) {
+        if self.screenshots {
+            let (frame, size) = self.frame.get_or_insert_with(Default::default);
+
+            frame.clear();
+            frame.extend_from_slice(pixels);
+            *size = pixels_size;
+        }
+
         let uses_sixel = self.painter.queue_sixel_background(pixels, pixels_size);
 
         let viewport = self.size.cast::<usize>();
@@ -200,20 +1033,249 @@ impl Renderer {
         }
     }
 
+    /// Save the last frame drawn by the browser, as a PNG or SIXEL image
+    /// or as text art depending on the extension of the file.
+    pub fn screenshot(&self, path: &str) -> io::Result<()> {
+        let Some((ref pixels, size)) = self.frame else {
+            return Err(io::Error::other("the page wasn't drawn"));
+        };
+        let bytes = match ScreenshotFormat::from_path(path) {
+            ScreenshotFormat::Png => png::encode_bgra(pixels, size.width, size.height),
+            ScreenshotFormat::Sixel => {
+                Frame::from_viewport(pixels, size, DiffusionMethod::None)
+                    .map_err(|error| io::Error::other(format!("{error:?}")))?
+                    .bytes
+            }
+            ScreenshotFormat::Text => self.text_art(pixels, size)?,
+        };
+
+        fs::write(path, bytes)
+    }
+
+    /// Compare the screen with a golden file, creating it if it doesn't exist.
+    /// If they differ, the screen is saved next to it with an `.actual` extension.
+    pub fn check_golden(&self, path: &str) -> io::Result<bool> {
+        let snapshot = self.snapshot()?;
+
+        match fs::read(path) {
+            Ok(golden) if golden == snapshot => Ok(true),
+            Ok(_) => {
+                fs::write(format!("{path}.actual"), snapshot)?;
+
+                Ok(false)
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                log::warning!("creating golden file {path}");
+
+                fs::write(path, snapshot).map(|_| true)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// What was last painted to the terminal, as text art.
+    fn snapshot(&self) -> io::Result<Vec<u8>> {
+        let width = self.size.width as usize;
+
+        // Nothing was painted before the size is known
+        if width == 0 {
+            return Ok(Vec::new());
+        }
+
+        // The navigation bar isn't displayed in kiosk mode
+        let hidden = match self.theme.bar {
+            BarPosition::Hidden => width,
+            _ => 0,
+        };
+        let mut rows = self.cells[hidden..]
+            .chunks(width)
+            .map(|row| row.iter().map(|(previous, _)| previous.clone()).collect())
+            .collect::<Vec<Vec<_>>>();
+
+        rows.sort_by_key(|row| row[0].cursor.y);
+
+        let mut painter = Painter::new();
+
+        painter.set_true_color(true);
+        painter.export(&rows)
+    }
+
+    /// Text drawn by the browser in the window, one line per row.
+    pub fn page_text(&self) -> String {
+        let width = self.size.width as usize;
+        let mut text = String::new();
+
+        // Page rows come after the navigation bar
+        for row in self.cells[width..width * (self.size.height as usize + 1)].chunks(width) {
+            let line = row
+                .iter()
+                .filter_map(|(_, cell)| match cell.grapheme {
+                    None => Some(" "),
+                    Some(ref grapheme) if grapheme.index == 0 => Some(grapheme.char.as_str()),
+                    // Covered by a wide grapheme
+                    Some(_) => None,
+                })
+                .collect::<String>();
+
+            text += line.trim_end();
+            text.push('\n');
+        }
+
+        text.trim_end_matches('\n').to_owned() + "\n"
+    }
+
+    /// Paint the page with the text drawn by the browser over quadrants
+    /// averaged from the frame, as the terminal would display it.
+    fn text_art(&self, pixels: &[u8], size: Size) -> io::Result<Vec<u8>> {
+        let (width, height) = (self.size.width as usize, self.size.height as usize);
+
+        if width == 0 || height == 0 {
+            return Err(io::Error::other("the window is empty"));
+        }
+
+        // Each quarter of a cell needs at least a pixel
+        if size.width < self.size.width * 2 || size.height < self.size.height * 2 {
+            return Err(io::Error::other("the frame is smaller than the window"));
+        }
+
+        let pixels_per_cell = Size::new(size.width as usize / width, size.height as usize / height);
+        let quarter = pixels_per_cell / 2;
+        // Average color of a quarter of a cell
+        let average = |left: usize, top: usize| {
+            let mut sum = [0; 3];
+
+            for y in top..top + quarter.height {
+                for x in left..left + quarter.width {
+                    let index = (x + y * size.width as usize) * 4;
+
+                    sum[0] += pixels[index + 2] as usize;
+                    sum[1] += pixels[index + 1] as usize;
+                    sum[2] += pixels[index] as usize;
+                }
+            }
+
+            let count = quarter.width * quarter.height;
+
+            Color::new(
+                (sum[0] / count) as u8,
+                (sum[1] / count) as u8,
+                (sum[2] / count) as u8,
+            )
+        };
+        // Page rows come after the navigation bar
+        let rows = self.cells[width..width * (height + 1)]
+            .chunks(width)
+            .enumerate()
+            .map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(x, (_, cell))| {
+                        let (left, top) = (x * pixels_per_cell.width, y * pixels_per_cell.height);
+                        let (right, bottom) = (left + quarter.width, top + quarter.height);
+
+                        Cell {
+                            quadrant: (
+                                average(left, top),
+                                average(right, top),
+                                average(right, bottom),
+                                average(left, bottom),
+                            ),
+                            selected: false,
+                            ..cell.clone()
+                        }
+                    })
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        let mut painter = Painter::new();
+
+        painter.set_true_color(true);
+        painter.export(&rows)
+    }
+
     pub fn clear_text(&mut self) {
         for (_, cell) in self.cells.iter_mut() {
             cell.grapheme = None
         }
     }
 
-    pub fn set_title(&self, title: &str) -> io::Result<()> {
-        let mut stdout = io::stdout();
+    pub fn set_title(&mut self, title: &str) -> io::Result<()> {
+        if !self.terminal {
+            return Ok(());
+        }
+
+        self.painter.write_sequence(&format!(
+            "\x1b]0;{title}\x07\x1b]1;{title}\x07\x1b]2;{title}\x07"
+        ))
+    }
+
+    /// Copy the text displayed in the selected cells.
+    fn copy_selection(&mut self) {
+        let Some(ref selection) = self.selection else {
+            return;
+        };
+        // Use what's on screen, including the UI drawn over the page
+        let text = selection.text(
+            self.size.width,
+            self.cells.iter().map(|(previous, _)| previous),
+        );
 
-        write!(stdout, "\x1b]0;{title}\x07")?;
-        write!(stdout, "\x1b]1;{title}\x07")?;
-        write!(stdout, "\x1b]2;{title}\x07")?;
+        if let Err(error) = self.copy(&text) {
+            log::warning!("failed to copy the selection: {error}")
+        }
+    }
+
+    /// Write text to the clipboard of the terminal using OSC 52.
+    pub fn copy(&mut self, text: &str) -> io::Result<()> {
+        if !self.terminal {
+            return Ok(());
+        }
 
-        stdout.flush()
+        self.painter.write_sequence(&format!(
+            "\x1b]52;c;{}\x07",
+            base64::encode(text.as_bytes())
+        ))
+    }
+
+    /// Ask the terminal for its clipboard, terminals allowing it
+    /// reply with a `TerminalEvent::Clipboard`.
+    pub fn request_paste(&mut self) -> io::Result<()> {
+        if !self.terminal {
+            return Ok(());
+        }
+
+        self.painter.write_sequence("\x1b]52;c;?\x07")
+    }
+
+    /// Insert text read from the clipboard.
+    pub fn paste(&mut self, text: &str) -> io::Result<NavigationAction> {
+        let action = if let Some(ref mut dialog) = self.dialog {
+            dialog.paste(text);
+
+            NavigationAction::Ignore
+        } else if self.auth.is_open() {
+            self.auth.paste(text);
+
+            NavigationAction::Ignore
+        } else if self.permission_prompt.is_open() {
+            NavigationAction::Ignore
+        } else if self.downloads.prompting() {
+            self.downloads.paste(text);
+
+            NavigationAction::Ignore
+        } else if self.nav.focused() {
+            self.nav.paste(text);
+
+            NavigationAction::Ignore
+        } else if self.find.is_open() {
+            self.find.paste(text)
+        } else if self.reader.is_some() {
+            NavigationAction::Ignore
+        } else {
+            NavigationAction::Paste(text.to_owned())
+        };
+
+        Ok(action)
     }
 
     pub fn fill_rect(&mut self, rect: Rect, color: Color) {
@@ -247,9 +1309,15 @@ impl Renderer {
     }
 
     /// Render some text into the terminal output
-    pub fn draw_text(&mut self, string: &str, origin: Point, size: Size, color: Color) {
-        // Get an iterator starting at the text origin
-        let len = self.cells.len();
+    pub fn draw_text(
+        &mut self,
+        string: &str,
+        origin: Point,
+        size: Size,
+        color: Color,
+        style: TextStyle,
+        link: Option<&str>,
+    ) {
         let viewport = &self.size.cast::<usize>();
 
         if size.width > 2 && size.height > 2 {
@@ -272,37 +1340,57 @@ impl Renderer {
         } else {
             // Compute the buffer index based on the position
             let index = origin.x / 2 + (origin.y + 1) / 4 * (viewport.width as i32);
-            let mut iter = self.cells[len.min(index as usize)..].iter_mut();
-
-            // Get every Unicode grapheme in the input string
-            for grapheme in UnicodeSegmentation::graphemes(string, true) {
-                let width = grapheme.width();
-
-                for index in 0..width {
-                    // Get the next terminal cell at the given position
-                    match iter.next() {
-                        // Stop if we're at the end of the buffer
-                        None => return,
-                        // Set the cell to the current grapheme
-                        Some((_, cell)) => {
-                            let next = Grapheme {
-                                // Create a new shared reference to the text
-                                color,
-                                index,
-                                width,
-                                // Export the set of unicode code points for this graphene into an UTF-8 string
-                                char: grapheme.to_string(),
-                            };
-
-                            if match cell.grapheme {
-                                None => true,
-                                Some(ref previous) => {
-                                    previous.color != next.color || previous.char != next.char
-                                }
-                            } {
-                                cell.image = false;
-                                cell.grapheme = Some(Rc::new(next))
+
+            self.draw_graphemes(string, index as usize, color, style, link.map(Rc::from))
+        }
+    }
+
+    /// Write text into the cells starting at a buffer index.
+    fn draw_graphemes(
+        &mut self,
+        string: &str,
+        index: usize,
+        color: Color,
+        style: TextStyle,
+        link: Option<Rc<str>>,
+    ) {
+        let len = self.cells.len();
+        // Get an iterator starting at the text origin
+        let mut iter = self.cells[len.min(index)..].iter_mut();
+
+        // Get every Unicode grapheme in the input string
+        for grapheme in UnicodeSegmentation::graphemes(string, true) {
+            let width = grapheme.width();
+
+            for index in 0..width {
+                // Get the next terminal cell at the given position
+                match iter.next() {
+                    // Stop if we're at the end of the buffer
+                    None => return,
+                    // Set the cell to the current grapheme
+                    Some((_, cell)) => {
+                        let next = Grapheme {
+                            // Create a new shared reference to the text
+                            color,
+                            index,
+                            width,
+                            style,
+                            link: link.clone(),
+                            // Export the set of unicode code points for this graphene into an UTF-8 string
+                            char: grapheme.to_string(),
+                        };
+
+                        if match cell.grapheme {
+                            None => true,
+                            Some(ref previous) => {
+                                previous.color != next.color
+                                    || previous.char != next.char
+                                    || previous.style != next.style
+                                    || previous.link != next.link
                             }
+                        } {
+                            cell.image = false;
+                            cell.grapheme = Some(Rc::new(next))
                         }
                     }
                 }
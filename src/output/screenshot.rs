@@ -0,0 +1,30 @@
+use std::path::Path;
+
+/// Format of the file written by `--screenshot`, picked from its extension.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScreenshotFormat {
+    Png,
+    /// Raw SIXEL data, displayed by printing the file in a compatible terminal
+    Sixel,
+    /// Colored quadrants and text using ANSI escape sequences
+    Text,
+}
+
+impl ScreenshotFormat {
+    pub fn from_path(path: &str) -> Self {
+        let extension = Path::new(path)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("png") => ScreenshotFormat::Png,
+            Some("six" | "sixel") => ScreenshotFormat::Sixel,
+            _ => ScreenshotFormat::Text,
+        }
+    }
+
+    /// Whether the text has to be drawn by the browser in the bitmap.
+    pub fn is_bitmap(&self) -> bool {
+        *self != ScreenshotFormat::Text
+    }
+}
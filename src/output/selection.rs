@@ -0,0 +1,164 @@
+use std::ops::Range;
+
+use crate::gfx::Point;
+
+use super::Cell;
+
+/// Text selected with the mouse, from the cell where the drag started
+/// to the cell under the pointer, in reading order like terminals do.
+#[derive(Clone, Debug)]
+pub struct Selection {
+    anchor: Point,
+    cursor: Point,
+    /// Whether the mouse button is still pressed
+    dragging: bool,
+}
+
+impl Selection {
+    pub fn new(origin: Point) -> Self {
+        Self {
+            anchor: origin,
+            cursor: origin,
+            dragging: true,
+        }
+    }
+
+    pub fn dragging(&self) -> bool {
+        self.dragging
+    }
+
+    pub fn extend(&mut self, origin: Point) {
+        self.cursor = origin;
+    }
+
+    pub fn release(&mut self, origin: Point) {
+        self.cursor = origin;
+        self.dragging = false;
+    }
+
+    /// Indices of the selected cells in a grid of the given width.
+    pub fn range(&self, width: u32, cells: usize) -> Range<usize> {
+        if width == 0 {
+            return 0..0;
+        }
+
+        let index = |point: Point| {
+            (point.y.max(0) as usize * width as usize + point.x.clamp(0, width as i32 - 1) as usize)
+                .min(cells)
+        };
+        let (start, end) = (index(self.anchor), index(self.cursor));
+
+        start.min(end)..(start.max(end) + 1).min(cells)
+    }
+
+    /// Text displayed in the selected cells, one line per row.
+    pub fn text<'a>(&self, width: u32, cells: impl Iterator<Item = &'a Cell>) -> String {
+        let range = self.range(width, usize::MAX);
+        let mut lines = Vec::new();
+        let mut line = String::new();
+
+        for (index, cell) in cells.enumerate().take(range.end).skip(range.start) {
+            if index > range.start && index % width as usize == 0 {
+                lines.push(std::mem::take(&mut line));
+            }
+
+            match cell.grapheme {
+                // Wide characters span multiple cells
+                Some(ref grapheme) if grapheme.index > 0 => (),
+                Some(ref grapheme) => line.push_str(&grapheme.char),
+                None => line.push(' '),
+            }
+        }
+
+        lines.push(line);
+        lines
+            .iter()
+            .map(|line| line.trim_end())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::{
+        gfx::Color,
+        output::{Grapheme, TextStyle},
+    };
+
+    /// Cells of a grid with one grapheme per character, spaces are empty cells.
+    fn grid(rows: &[&str]) -> Vec<Cell> {
+        let mut cells = Vec::new();
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, char) in row.chars().enumerate() {
+                let mut cell = Cell::new(x as u32, y as u32);
+
+                if char != ' ' {
+                    cell.grapheme = Some(Rc::new(Grapheme {
+                        char: char.to_string(),
+                        index: 0,
+                        width: 1,
+                        color: Color::black(),
+                        style: TextStyle::default(),
+                        link: None,
+                    }))
+                }
+
+                cells.push(cell)
+            }
+        }
+
+        cells
+    }
+
+    #[test]
+    fn selects_in_reading_order() {
+        let cells = grid(&["ab  ", "cdef", "gh  "]);
+        let mut selection = Selection::new(Point::new(1, 2));
+
+        selection.release(Point::new(1, 0));
+
+        assert!(!selection.dragging());
+        assert_eq!(selection.range(4, cells.len()), 1..10);
+        assert_eq!(selection.text(4, cells.iter()), "b\ncdef\ngh");
+    }
+
+    #[test]
+    fn clamps_to_the_grid() {
+        let cells = grid(&["ab", "cd"]);
+        let mut selection = Selection::new(Point::new(-5, -1));
+
+        selection.extend(Point::new(10, 7));
+
+        assert!(selection.dragging());
+        assert_eq!(selection.range(2, cells.len()), 0..4);
+        assert_eq!(selection.range(0, cells.len()), 0..0);
+        assert_eq!(selection.text(2, cells.iter()), "ab\ncd");
+    }
+
+    #[test]
+    fn skips_wide_character_tails() {
+        let mut cells = grid(&["x  "]);
+
+        for (index, cell) in cells[1..].iter_mut().enumerate() {
+            cell.grapheme = Some(Rc::new(Grapheme {
+                char: "字".to_owned(),
+                index,
+                width: 2,
+                color: Color::black(),
+                style: TextStyle::default(),
+                link: None,
+            }))
+        }
+
+        let mut selection = Selection::new(Point::new(0, 0));
+
+        selection.release(Point::new(2, 0));
+
+        assert_eq!(selection.text(3, cells.iter()), "x字");
+    }
+}
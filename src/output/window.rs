@@ -1,13 +1,8 @@
-use core::mem::MaybeUninit;
-use std::{
-    fs::OpenOptions,
-    io::{Read, Write},
-    os::fd::AsRawFd,
-    str::FromStr,
-    time::{Duration, Instant},
-};
+use std::str::FromStr;
 
-use crate::{cli::CommandLine, gfx::Size, utils::log};
+use crate::{
+    cli::CommandLine, config::Config, gfx::Size, terminal, ui::theme::BarPosition, utils::log,
+};
 
 /// A terminal window.
 #[derive(Clone, Debug)]
@@ -26,6 +21,8 @@ pub struct Window {
     pub dsf: f32,
     /// Command line arguments
     pub cmd: CommandLine,
+    /// Placement of the navigation bar
+    pub bar: BarPosition,
 }
 
 impl Window {
@@ -39,6 +36,7 @@ impl Window {
             graphics_px: (0, 0).into(),
             dsf: 1.0,
             cmd: CommandLine::parse(),
+            bar: Config::read().theme.bar,
         };
 
         window.update();
@@ -47,20 +45,10 @@ impl Window {
     }
 
     pub fn update(&mut self) -> &Self {
-        let (mut term, cell) = unsafe {
-            let mut ptr = MaybeUninit::<libc::winsize>::uninit();
-
-            if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, ptr.as_mut_ptr()) == 0 {
-                let size = ptr.assume_init();
-
-                (
-                    Size::new(size.ws_col, size.ws_row),
-                    Size::new(size.ws_xpixel, size.ws_ypixel),
-                )
-            } else {
-                (Size::splat(0), Size::splat(0))
-            }
-        };
+        // Captures don't display the navigation bar, the page takes the whole window
+        let headless = self.cmd.headless();
+        let terminal = terminal::open(&self.cmd);
+        let (mut term, cell) = terminal.size();
 
         if term.width == 0 || term.height == 0 {
             let cols = match parse_var("COLUMNS").unwrap_or(0) {
@@ -95,21 +83,17 @@ impl Window {
             };
 
         if cell_pixels.width <= 0.0 || cell_pixels.height <= 0.0 {
-            if let Some(win_px) = query_window_pixels() {
-                cell_pixels = Size::new(
-                    win_px.width / term.width.max(1) as f32,
-                    win_px.height / term.height.max(1) as f32,
-                );
-            }
-
-            if cell_pixels.width <= 0.0 || cell_pixels.height <= 0.0 {
-                cell_pixels = query_cell_geometry().unwrap_or(Size::new(8.0, 16.0));
-            }
+            cell_pixels = terminal.cell_size(term).unwrap_or(Size::new(8.0, 16.0));
         }
         // Normalize the cells dimensions for an aspect ratio of 1:2
         self.scale = cell_pixels;
-        // Keep some space for the UI
-        self.cells = Size::new(term.width.max(1), term.height.max(2) - 1).cast();
+        // Keep some space for the UI, and the status bar if enabled
+        let ui_rows = match headless {
+            true => 0,
+            false => (self.bar != BarPosition::Hidden) as u16 + self.cmd.status_bar as u16,
+        };
+
+        self.cells = Size::new(term.width.max(1), term.height.max(ui_rows + 1) - ui_rows).cast();
         self.graphics_px = Size::new(
             (self.cells.width as f32 * cell_pixels.width).round() as u32,
             (self.cells.height as f32 * cell_pixels.height).round() as u32,
@@ -140,131 +124,3 @@ impl Window {
 fn parse_var<T: FromStr>(var: &str) -> Option<T> {
     std::env::var(var).ok()?.parse().ok()
 }
-
-fn query_cell_geometry() -> Option<Size<f32>> {
-    let mut tty = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .open("/dev/tty")
-        .ok()?;
-    let fd = tty.as_raw_fd();
-    let mut term = MaybeUninit::<libc::termios>::uninit();
-
-    unsafe {
-        if libc::tcgetattr(fd, term.as_mut_ptr()) != 0 {
-            return None;
-        }
-    }
-
-    let original = unsafe { term.assume_init() };
-    let mut raw = original;
-    let c_oflag = raw.c_oflag;
-
-    unsafe {
-        libc::cfmakeraw(&mut raw);
-    }
-
-    raw.c_oflag = c_oflag;
-
-    if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
-        return None;
-    }
-
-    struct Restore(libc::c_int, libc::termios);
-
-    impl Drop for Restore {
-        fn drop(&mut self) {
-            unsafe {
-                libc::tcsetattr(self.0, libc::TCSANOW, &self.1);
-            }
-        }
-    }
-
-    let _restore = Restore(fd, original);
-
-    if tty.write_all(b"\x1b[16t").is_err() || tty.flush().is_err() {
-        return None;
-    }
-
-    let mut buffer = [0u8; 128];
-    let mut length = 0usize;
-    let deadline = Instant::now() + Duration::from_millis(100);
-
-    while length < buffer.len() && Instant::now() < deadline {
-        let remaining = deadline.saturating_duration_since(Instant::now());
-        let timeout = remaining.as_millis().min(i32::MAX as u128) as libc::c_int;
-        let mut fds = libc::pollfd {
-            fd,
-            events: libc::POLLIN,
-            revents: 0,
-        };
-
-        let result = unsafe { libc::poll(&mut fds, 1, timeout) };
-
-        if result <= 0 {
-            break;
-        }
-
-        match tty.read(&mut buffer[length..]) {
-            Ok(0) => break,
-            Ok(read) => {
-                length += read;
-
-                if buffer[..length].contains(&b't') {
-                    break;
-                }
-            }
-            Err(_) => break,
-        }
-    }
-
-    if length == 0 {
-        return None;
-    }
-
-    let response = std::str::from_utf8(&buffer[..length]).ok()?;
-    let start = response.rfind("\u{1b}[6;")?;
-    let rest = &response[start + 3..];
-    let end = rest.find('t')?;
-    let mut parts = rest[..end].split(';');
-    let height = parts.next()?.parse::<f32>().ok()?;
-    let width = parts.next()?.parse::<f32>().ok()?;
-
-    if width <= 0.0 || height <= 0.0 {
-        return None;
-    }
-
-    Some(Size::new(width, height))
-}
-
-fn query_window_pixels() -> Option<Size<f32>> {
-    let mut tty = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .open("/dev/tty")
-        .ok()?;
-
-    tty.write_all(b"\x1b[14t").ok()?;
-    tty.flush().ok()?;
-
-    let mut buf = [0u8; 128];
-    let n = tty.read(&mut buf).ok()?;
-
-    if n == 0 {
-        return None;
-    }
-
-    let response = std::str::from_utf8(&buf[..n]).ok()?;
-    let start = response.rfind("\u{1b}[4;")?;
-    let rest = &response[start + 3..];
-    let end = rest.find('t')?;
-    let mut parts = rest[..end].split(';');
-    let height = parts.next()?.parse::<f32>().ok()?;
-    let width = parts.next()?.parse::<f32>().ok()?;
-
-    if width <= 0.0 || height <= 0.0 {
-        return None;
-    }
-
-    Some(Size::new(width, height))
-}
@@ -0,0 +1,46 @@
+mod tty;
+mod virtual_terminal;
+
+pub use tty::*;
+pub use virtual_terminal::*;
+
+use std::fs::File;
+use std::io::{Read, Write};
+
+use crate::{cli::CommandLine, gfx::Size};
+
+/// Input and output of the terminal Carbonyl runs in.
+pub trait TerminalIo {
+    /// Size of the window in cells, and in pixels if known, zero otherwise.
+    fn size(&self) -> (Size<u16>, Size<u16>);
+    /// Size of a cell in pixels in a window of the given size in cells,
+    /// if it can't be computed from `size`.
+    fn cell_size(&self, cells: Size<u16>) -> Option<Size<f32>>;
+    /// Switch to raw input and the alternate screen until `teardown`.
+    fn setup(&mut self);
+    fn teardown(&mut self);
+    /// Stream the input is read from, blocking until there's some.
+    fn input(&self) -> Box<dyn Read + Send>;
+    /// Stream the output is written to.
+    fn output(&self) -> Box<dyn Write + Send>;
+}
+
+/// Terminal used by this session: a virtual one with `--virtual-terminal`
+/// or when there's no terminal attached, the TTY otherwise.
+pub fn open(cmd: &CommandLine) -> Box<dyn TerminalIo> {
+    match cmd.virtual_terminal {
+        true => Box::new(VirtualTerminal::new(
+            cmd.size.unwrap_or(Size::new(80, 24)),
+            cmd.cell_size.unwrap_or(Size::new(8, 16)),
+        )),
+        false => Box::new(Tty::new(cmd)),
+    }
+}
+
+/// Whether the process has a terminal, ie. it's not run by systemd
+/// or in a container without one.
+pub fn attached() -> bool {
+    let isatty = |fd| unsafe { libc::isatty(fd) == 1 };
+
+    isatty(libc::STDIN_FILENO) || isatty(libc::STDOUT_FILENO) || File::open("/dev/tty").is_ok()
+}
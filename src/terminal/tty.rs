@@ -0,0 +1,393 @@
+use core::mem::MaybeUninit;
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    os::fd::{AsRawFd, RawFd},
+    time::{Duration, Instant},
+};
+
+use super::TerminalIo;
+use crate::{cli::CommandLine, gfx::Size, utils::log};
+
+/// Terminal on the standard input and output of the process.
+pub struct Tty {
+    /// Size set with `--size`, used instead of the size of the terminal
+    size: Option<Size>,
+    /// Whether the terminal can be queried, it's not when capturing
+    /// the page or replaying a recording
+    query: bool,
+    /// Settings to restore once done
+    terminal: Option<Terminal>,
+}
+
+impl Tty {
+    pub fn new(cmd: &CommandLine) -> Tty {
+        Tty {
+            size: cmd.size,
+            query: cmd.interactive(),
+            terminal: None,
+        }
+    }
+}
+
+impl TerminalIo for Tty {
+    fn size(&self) -> (Size<u16>, Size<u16>) {
+        if let Some(size) = self.size {
+            return (size.cast(), Size::splat(0));
+        }
+
+        unsafe {
+            let mut ptr = MaybeUninit::<libc::winsize>::uninit();
+
+            if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, ptr.as_mut_ptr()) == 0 {
+                let size = ptr.assume_init();
+
+                (
+                    Size::new(size.ws_col, size.ws_row),
+                    Size::new(size.ws_xpixel, size.ws_ypixel),
+                )
+            } else {
+                (Size::splat(0), Size::splat(0))
+            }
+        }
+    }
+
+    fn cell_size(&self, cells: Size<u16>) -> Option<Size<f32>> {
+        if !self.query {
+            return None;
+        }
+
+        let cell = query_window_pixels().map(|window| {
+            Size::new(
+                window.width / cells.width.max(1) as f32,
+                window.height / cells.height.max(1) as f32,
+            )
+        });
+
+        match cell {
+            Some(cell) if cell.width > 0.0 && cell.height > 0.0 => Some(cell),
+            _ => query_cell_geometry(),
+        }
+    }
+
+    fn setup(&mut self) {
+        self.terminal = Some(Terminal::setup());
+    }
+
+    fn teardown(&mut self) {
+        if let Some(mut terminal) = self.terminal.take() {
+            terminal.teardown()
+        }
+    }
+
+    fn input(&self) -> Box<dyn Read + Send> {
+        Box::new(io::stdin())
+    }
+
+    fn output(&self) -> Box<dyn Write + Send> {
+        Box::new(io::stdout())
+    }
+}
+
+fn query_cell_geometry() -> Option<Size<f32>> {
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    let fd = tty.as_raw_fd();
+    let mut term = MaybeUninit::<libc::termios>::uninit();
+
+    unsafe {
+        if libc::tcgetattr(fd, term.as_mut_ptr()) != 0 {
+            return None;
+        }
+    }
+
+    let original = unsafe { term.assume_init() };
+    let mut raw = original;
+    let c_oflag = raw.c_oflag;
+
+    unsafe {
+        libc::cfmakeraw(&mut raw);
+    }
+
+    raw.c_oflag = c_oflag;
+
+    if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+        return None;
+    }
+
+    struct Restore(libc::c_int, libc::termios);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            unsafe {
+                libc::tcsetattr(self.0, libc::TCSANOW, &self.1);
+            }
+        }
+    }
+
+    let _restore = Restore(fd, original);
+
+    if tty.write_all(b"\x1b[16t").is_err() || tty.flush().is_err() {
+        return None;
+    }
+
+    let mut buffer = [0u8; 128];
+    let mut length = 0usize;
+    let deadline = Instant::now() + Duration::from_millis(100);
+
+    while length < buffer.len() && Instant::now() < deadline {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let timeout = remaining.as_millis().min(i32::MAX as u128) as libc::c_int;
+        let mut fds = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        let result = unsafe { libc::poll(&mut fds, 1, timeout) };
+
+        if result <= 0 {
+            break;
+        }
+
+        match tty.read(&mut buffer[length..]) {
+            Ok(0) => break,
+            Ok(read) => {
+                length += read;
+
+                if buffer[..length].contains(&b't') {
+                    break;
+                }
+            }
+            Err(_) => break,
+        }
+    }
+
+    if length == 0 {
+        return None;
+    }
+
+    let response = std::str::from_utf8(&buffer[..length]).ok()?;
+    let start = response.rfind("\u{1b}[6;")?;
+    let rest = &response[start + 3..];
+    let end = rest.find('t')?;
+    let mut parts = rest[..end].split(';');
+    let height = parts.next()?.parse::<f32>().ok()?;
+    let width = parts.next()?.parse::<f32>().ok()?;
+
+    if width <= 0.0 || height <= 0.0 {
+        return None;
+    }
+
+    Some(Size::new(width, height))
+}
+
+fn query_window_pixels() -> Option<Size<f32>> {
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+
+    tty.write_all(b"\x1b[14t").ok()?;
+    tty.flush().ok()?;
+
+    let mut buf = [0u8; 128];
+    let n = tty.read(&mut buf).ok()?;
+
+    if n == 0 {
+        return None;
+    }
+
+    let response = std::str::from_utf8(&buf[..n]).ok()?;
+    let start = response.rfind("\u{1b}[4;")?;
+    let rest = &response[start + 3..];
+    let end = rest.find('t')?;
+    let mut parts = rest[..end].split(';');
+    let height = parts.next()?.parse::<f32>().ok()?;
+    let width = parts.next()?.parse::<f32>().ok()?;
+
+    if width <= 0.0 || height <= 0.0 {
+        return None;
+    }
+
+    Some(Size::new(width, height))
+}
+
+/// Raw input and alternate screen of the TTY, restored when dropped.
+struct Terminal {
+    settings: Option<TerminalSettings>,
+    alt_screen: bool,
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        self.teardown()
+    }
+}
+
+impl Terminal {
+    /// Setup the input stream to operate in raw mode.
+    /// Returns an object that'll revert terminal settings.
+    fn setup() -> Self {
+        Self {
+            settings: match TerminalSettings::open_raw() {
+                Ok(settings) => Some(settings),
+                Err(error) => {
+                    log::error!("Failed to setup terminal: {error}");
+
+                    None
+                }
+            },
+            alt_screen: if let Err(error) = TTY::enter_alt_screen() {
+                log::error!("Failed to enter alternative screen: {error}");
+
+                false
+            } else {
+                true
+            },
+        }
+    }
+
+    fn teardown(&mut self) {
+        if let Some(ref settings) = self.settings {
+            if let Err(error) = settings.apply() {
+                log::error!("Failed to revert terminal settings: {error}");
+            }
+
+            self.settings = None;
+        }
+
+        if self.alt_screen {
+            if let Err(error) = TTY::quit_alt_screen() {
+                log::error!("Failed to quit alternative screen: {error}");
+            }
+
+            self.alt_screen = false;
+        }
+    }
+}
+
+enum TTY {
+    Raw(RawFd),
+    File(File),
+}
+
+const SEQUENCES: [(u32, bool); 4] = [(1049, true), (1003, true), (1006, true), (25, false)];
+
+impl TTY {
+    fn stdin() -> TTY {
+        let isatty = unsafe { libc::isatty(libc::STDIN_FILENO) };
+
+        if isatty != 1 {
+            if let Ok(file) = File::open("/dev/tty") {
+                return TTY::File(file);
+            }
+        }
+
+        TTY::Raw(libc::STDIN_FILENO)
+    }
+
+    fn enter_alt_screen() -> io::Result<()> {
+        let mut out = io::stdout();
+
+        for (sequence, enable) in SEQUENCES {
+            write!(out, "\x1b[?{}{}", sequence, if enable { "h" } else { "l" })?;
+        }
+
+        // Set the current background color to black
+        write!(out, "\x1b[48;2;0;0;0m")?;
+        // Query current foreground color to for true-color support detection
+        write!(out, "\x1bP$qm\x1b\\")?;
+        // Query current terminal name
+        write!(out, "\x1bP+q544e\x1b\\")?;
+        // Query graphics capability (XTSMGRAPHICS). Some terminals expect DCS form;
+        // use it first and fall back to CSI if ignored.
+        write!(out, "\x1bP?2;1;0S\x1b\\")?;
+        write!(out, "\x1b[?2;1;0S")?;
+
+        out.flush()
+    }
+
+    fn quit_alt_screen() -> io::Result<()> {
+        let mut out = io::stdout();
+
+        for (sequence, enable) in SEQUENCES {
+            write!(out, "\x1b[?{}{}", sequence, if enable { "l" } else { "h" })?;
+        }
+
+        out.flush()
+    }
+
+    fn as_raw_fd(self) -> RawFd {
+        match self {
+            TTY::Raw(fd) => fd,
+            TTY::File(file) => file.as_raw_fd(),
+        }
+    }
+}
+
+trait ToErr {
+    fn to_err(self) -> io::Result<()>;
+}
+impl ToErr for libc::c_int {
+    fn to_err(self) -> io::Result<()> {
+        if self == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+/// Safe wrapper around libc::termios
+#[derive(Clone)]
+struct TerminalSettings {
+    data: libc::termios,
+}
+
+impl TerminalSettings {
+    /// Fetch settings from the current TTY
+    fn open() -> io::Result<Self> {
+        let tty = TTY::stdin();
+        let mut term = MaybeUninit::uninit();
+        let data = unsafe {
+            libc::tcgetattr(tty.as_raw_fd(), term.as_mut_ptr()).to_err()?;
+
+            term.assume_init()
+        };
+
+        Ok(Self { data })
+    }
+
+    fn open_raw() -> io::Result<TerminalSettings> {
+        let mut raw = Self::open()?;
+        let settings = raw.clone();
+
+        raw.make_raw();
+        raw.apply()?;
+
+        Ok(settings)
+    }
+
+    /// Enable raw input
+    fn make_raw(&mut self) {
+        let c_oflag = self.data.c_oflag;
+
+        // Set the terminal to raw mode
+        unsafe { libc::cfmakeraw(&mut self.data) }
+
+        // Restore output flags, ensures carriage returns are consistent
+        self.data.c_oflag = c_oflag;
+    }
+
+    /// Apply the settings to the current TTY
+    fn apply(&self) -> io::Result<()> {
+        let tty = TTY::stdin();
+
+        unsafe { libc::tcsetattr(tty.as_raw_fd(), libc::TCSANOW, &self.data).to_err() }
+    }
+}
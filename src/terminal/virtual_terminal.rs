@@ -0,0 +1,65 @@
+use std::io::{self, Read, Write};
+use std::thread;
+
+use super::TerminalIo;
+use crate::gfx::Size;
+
+/// Terminal kept in memory, to run without a TTY with `--virtual-terminal`.
+///
+/// The output is discarded since the screen is kept by the renderer,
+/// and the browser is driven with the control socket or DevTools,
+/// so reading the input waits forever.
+pub struct VirtualTerminal {
+    /// Size of the window in cells
+    size: Size,
+    /// Size of a cell in pixels
+    cell: Size,
+}
+
+/// Input without any data, blocking instead of reaching the end
+/// which would close the browser.
+struct Idle;
+
+impl Read for Idle {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        loop {
+            thread::park()
+        }
+    }
+}
+
+impl VirtualTerminal {
+    pub fn new(size: Size, cell: Size) -> VirtualTerminal {
+        VirtualTerminal { size, cell }
+    }
+}
+
+impl TerminalIo for VirtualTerminal {
+    fn size(&self) -> (Size<u16>, Size<u16>) {
+        let cast = |value: u32| value.min(u16::MAX as u32) as u16;
+
+        (
+            Size::new(cast(self.size.width), cast(self.size.height)),
+            Size::new(
+                cast(self.size.width.saturating_mul(self.cell.width)),
+                cast(self.size.height.saturating_mul(self.cell.height)),
+            ),
+        )
+    }
+
+    fn cell_size(&self, _: Size<u16>) -> Option<Size<f32>> {
+        Some(self.cell.cast())
+    }
+
+    fn setup(&mut self) {}
+
+    fn teardown(&mut self) {}
+
+    fn input(&self) -> Box<dyn Read + Send> {
+        Box::new(Idle)
+    }
+
+    fn output(&self) -> Box<dyn Write + Send> {
+        Box::new(io::sink())
+    }
+}
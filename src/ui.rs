@@ -1 +1,14 @@
+pub mod auth;
+pub mod dialog;
+pub mod downloads;
+pub mod file_chooser;
+pub mod find;
+pub mod hints;
+pub mod keymap;
+pub mod menu;
 pub mod navigation;
+pub mod permissions;
+pub mod reader;
+pub mod status;
+pub mod text_field;
+pub mod theme;
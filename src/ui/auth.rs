@@ -0,0 +1,481 @@
+use std::collections::VecDeque;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::{
+    dialog::{frame, wrap},
+    navigation::{single_line, NavigationAction, NavigationElement},
+    text_field::{TextField, TextFieldView},
+    theme::Theme,
+};
+use crate::{
+    gfx::{Point, Size},
+    input::Key,
+    output::TextStyle,
+};
+
+/// Maximum number of columns of the prompt.
+const MAX_WIDTH: usize = 60;
+/// Room for the fields, the buttons and the borders.
+const MIN_WIDTH: usize = 32;
+/// Columns of the field labels.
+const LABEL: usize = 10;
+const OK: &str = "[ Log in ]";
+const CANCEL: &str = "[ Cancel ]";
+
+#[derive(Clone, Debug)]
+pub struct AuthRequest {
+    /// Identifier given by the browser
+    pub id: u32,
+    pub origin: String,
+    /// Name of the protected area sent by the server, can be empty
+    pub realm: String,
+    /// Whether a proxy is asking, rather than the site
+    pub proxy: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Focus {
+    #[default]
+    Username,
+    Password,
+    Ok,
+    Cancel,
+}
+
+const ORDER: [Focus; 4] = [Focus::Username, Focus::Password, Focus::Ok, Focus::Cancel];
+
+/// Position of the parts of the prompt on screen.
+struct Layout {
+    origin: Point,
+    width: usize,
+    height: usize,
+    lines: Vec<String>,
+    username: Point,
+    password: Point,
+    ok: Point,
+    cancel: Point,
+}
+
+/// Modal box asking for a username and password when a site
+/// or a proxy requires HTTP authentication.
+#[derive(Default)]
+pub struct AuthPrompt {
+    /// Pending challenges, the first one is displayed
+    requests: VecDeque<AuthRequest>,
+    username: TextField,
+    password: TextField,
+    focus: Focus,
+    size: Size,
+}
+
+impl AuthPrompt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.requests.is_empty()
+    }
+
+    /// Ask for a login, answered with `NavigationAction::AnswerAuth`.
+    pub fn request(&mut self, request: AuthRequest) {
+        self.requests.push_back(request);
+
+        if self.requests.len() == 1 {
+            self.reset();
+        }
+    }
+
+    pub fn set_size(&mut self, size: Size) {
+        self.size = size;
+
+        let width = self.layout().width.saturating_sub(LABEL + 6);
+
+        self.username.set_width(width);
+        self.password.set_width(width);
+    }
+
+    pub fn cursor(&self) -> Option<Point> {
+        if !self.is_open() {
+            return None;
+        }
+
+        let layout = self.layout();
+        let (origin, view) = match self.focus {
+            Focus::Username => (layout.username, self.username.view()),
+            Focus::Password => (layout.password, mask(self.password.view())),
+            _ => return None,
+        };
+
+        Some(origin + (1 + view.cursor as i32, 0))
+    }
+
+    pub fn keypress(&mut self, key: &Key) -> NavigationAction {
+        match (key.char, self.focus) {
+            // Escape
+            (0x1b, _) => self.answer(false),
+            // Return moves from the username to the password
+            (0x0d, Focus::Username) => {
+                self.focus = Focus::Password;
+
+                NavigationAction::Ignore
+            }
+            (0x0d, focus) => self.answer(focus != Focus::Cancel),
+            // Tab moves to the next control, Shift+Tab to the previous one
+            (0x09, focus) => {
+                let index = ORDER.iter().position(|&item| item == focus).unwrap_or(0);
+                let index = match key.modifiers.shift {
+                    true => (index + ORDER.len() - 1) % ORDER.len(),
+                    false => (index + 1) % ORDER.len(),
+                };
+
+                self.focus = ORDER[index];
+
+                NavigationAction::Ignore
+            }
+            (_, Focus::Username) => {
+                self.username.keypress(key);
+
+                NavigationAction::Ignore
+            }
+            (_, Focus::Password) => {
+                self.password.keypress(key);
+
+                NavigationAction::Ignore
+            }
+            // Left and Right switch between the buttons
+            (0x13 | 0x14, focus) => {
+                self.focus = match focus {
+                    Focus::Ok => Focus::Cancel,
+                    _ => Focus::Ok,
+                };
+
+                NavigationAction::Ignore
+            }
+            _ => NavigationAction::Ignore,
+        }
+    }
+
+    /// Insert pasted text in the focused field.
+    pub fn paste(&mut self, text: &str) {
+        match self.focus {
+            Focus::Username => self.username.insert(&single_line(text)),
+            Focus::Password => self.password.insert(&single_line(text)),
+            _ => (),
+        }
+    }
+
+    pub fn mouse_down(&mut self, origin: Point) -> NavigationAction {
+        let layout = self.layout();
+        let hit = |point: Point, width: usize| {
+            origin.y == point.y && origin.x >= point.x && origin.x < point.x + width as i32
+        };
+        let field = layout.width - LABEL - 4;
+
+        if hit(layout.ok, OK.len()) {
+            return self.answer(true);
+        }
+        if hit(layout.cancel, CANCEL.len()) {
+            return self.answer(false);
+        }
+
+        for (focus, point) in [
+            (Focus::Username, layout.username),
+            (Focus::Password, layout.password),
+        ] {
+            if hit(point, field) {
+                let column = (origin.x - point.x - 1).max(0) as usize;
+
+                self.focus = focus;
+
+                match focus {
+                    Focus::Username => self.username.click(column),
+                    _ => self.password.click(column),
+                }
+            }
+        }
+
+        NavigationAction::Ignore
+    }
+
+    pub fn render(&self, theme: &Theme) -> Vec<(Point, NavigationElement)> {
+        if !self.is_open() {
+            return Vec::new();
+        }
+
+        let layout = self.layout();
+        let (background, foreground) = (theme.background, theme.foreground);
+        let element = |text: String, background, foreground| NavigationElement {
+            text,
+            background,
+            foreground,
+            style: TextStyle::default(),
+        };
+        let mut elements = frame(layout.origin, layout.width, layout.height, theme);
+
+        for (index, line) in layout.lines.iter().enumerate() {
+            elements.push((
+                layout.origin + (2, 2 + index as i32),
+                element(line.clone(), background, foreground),
+            ));
+        }
+
+        let field = layout.width - LABEL - 6;
+
+        for (label, origin, view) in [
+            ("Username", layout.username, self.username.view()),
+            ("Password", layout.password, mask(self.password.view())),
+        ] {
+            let width = view.before.width() + view.selected.width() + view.after.width();
+            let padding = " ".repeat(field.saturating_sub(width));
+            let mut point = origin - (LABEL as i32, 0);
+
+            for element in [
+                element(format!("{label:<LABEL$}"), background, theme.muted),
+                element("[".to_owned(), background, foreground),
+                element(view.before, background, foreground),
+                // Selection is displayed with inverted colors
+                element(view.selected, foreground, background),
+                element(view.after + &padding, background, foreground),
+                element("]".to_owned(), background, foreground),
+            ] {
+                let width = element.text.width() as i32;
+
+                elements.push((point, element));
+
+                point = point + (width, 0);
+            }
+        }
+
+        // The focused button is displayed with inverted colors
+        let button = |text: &str, focus: Focus| match self.focus == focus {
+            true => element(text.to_owned(), foreground, background),
+            false => element(text.to_owned(), background, foreground),
+        };
+
+        elements.push((layout.cancel, button(CANCEL, Focus::Cancel)));
+        elements.push((layout.ok, button(OK, Focus::Ok)));
+        elements
+    }
+
+    /// Send the login, or cancel the challenge, and move on to the next one.
+    fn answer(&mut self, accepted: bool) -> NavigationAction {
+        let Some(request) = self.requests.pop_front() else {
+            return NavigationAction::Ignore;
+        };
+        let credentials = accepted.then(|| {
+            (
+                self.username.text().to_owned(),
+                self.password.text().to_owned(),
+            )
+        });
+
+        self.reset();
+
+        NavigationAction::AnswerAuth(request.id, credentials)
+    }
+
+    /// Clear the fields for the next challenge.
+    fn reset(&mut self) {
+        self.username.set_text("");
+        self.password.set_text("");
+        self.focus = Focus::Username;
+    }
+
+    /// Center the prompt over the page, wrapping the message.
+    fn layout(&self) -> Layout {
+        let width = (self.size.width as usize)
+            .saturating_sub(4)
+            .clamp(MIN_WIDTH, MAX_WIDTH);
+        let mut message = match self.requests.front() {
+            None => String::new(),
+            Some(request) if request.proxy => {
+                format!("The proxy {} requires a login.", request.origin)
+            }
+            Some(request) => format!("{} requires a login.", request.origin),
+        };
+
+        if let Some(request) = self.requests.front() {
+            if !request.realm.is_empty() {
+                message += &format!("\nThe server says: {}", single_line(&request.realm));
+            }
+        }
+
+        // Keep the fields and buttons visible if the message is too long
+        let max_lines = (self.size.height as usize).saturating_sub(9).max(1);
+        let mut lines = wrap(&message, width - 4);
+
+        if lines.len() > max_lines {
+            lines.truncate(max_lines);
+
+            if let Some(line) = lines.last_mut() {
+                line.push('…');
+            }
+        }
+
+        let height = lines.len() + 9;
+        let origin = Point::new(
+            (self.size.width as i32 - width as i32).max(0) / 2,
+            1 + (self.size.height as i32 - height as i32).max(0) / 2,
+        );
+        let username = origin + (2 + LABEL as i32, lines.len() as i32 + 3);
+        let buttons = origin.y + height as i32 - 3;
+        let ok = Point::new(origin.x + (width - 2 - OK.len()) as i32, buttons);
+        let cancel = Point::new(ok.x - CANCEL.len() as i32 - 2, buttons);
+
+        Layout {
+            origin,
+            width,
+            height,
+            lines,
+            username,
+            password: username + (0, 1),
+            ok,
+            cancel,
+        }
+    }
+}
+
+/// Hide the characters of a password, keeping one per grapheme.
+fn mask(view: TextFieldView) -> TextFieldView {
+    let hide = |text: &str| "*".repeat(text.graphemes(true).count());
+    // The cursor is at either end of the selection
+    let cursor = match view.cursor == view.before.width() {
+        true => hide(&view.before).len(),
+        false => hide(&view.before).len() + hide(&view.selected).len(),
+    };
+
+    TextFieldView {
+        cursor,
+        before: hide(&view.before),
+        selected: hide(&view.selected),
+        after: hide(&view.after),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(id: u32, proxy: bool) -> AuthRequest {
+        AuthRequest {
+            id,
+            origin: "https://example.com".to_owned(),
+            realm: "Staff\nonly".to_owned(),
+            proxy,
+        }
+    }
+
+    fn prompt() -> AuthPrompt {
+        let mut prompt = AuthPrompt::new();
+
+        prompt.set_size(Size::new(80, 24));
+        prompt
+    }
+
+    fn answer(action: NavigationAction) -> Option<(u32, Option<(String, String)>)> {
+        match action {
+            NavigationAction::AnswerAuth(id, credentials) => Some((id, credentials)),
+            _ => None,
+        }
+    }
+
+    fn type_text(prompt: &mut AuthPrompt, text: &str) {
+        for byte in text.bytes() {
+            prompt.keypress(&byte.into());
+        }
+    }
+
+    #[test]
+    fn sends_credentials() {
+        let mut prompt = prompt();
+
+        prompt.request(request(1, false));
+        prompt.request(request(2, true));
+
+        type_text(&mut prompt, "user");
+        assert!(answer(prompt.keypress(&0x0d.into())).is_none());
+        type_text(&mut prompt, "pass");
+        prompt.paste("word\n");
+        assert_eq!(
+            answer(prompt.keypress(&0x0d.into())),
+            Some((1, Some(("user".into(), "password".into()))))
+        );
+
+        // The fields are cleared for the next challenge
+        assert_eq!(prompt.username.text(), "");
+        assert_eq!(prompt.password.text(), "");
+        assert_eq!(prompt.focus, Focus::Username);
+        assert_eq!(answer(prompt.keypress(&0x1b.into())), Some((2, None)));
+        assert!(!prompt.is_open());
+        assert_eq!(prompt.cursor(), None);
+    }
+
+    #[test]
+    fn cycles_focus() {
+        let mut prompt = prompt();
+
+        prompt.request(request(1, false));
+
+        for focus in [Focus::Password, Focus::Ok, Focus::Cancel, Focus::Username] {
+            prompt.keypress(&0x09.into());
+            assert_eq!(prompt.focus, focus);
+        }
+
+        prompt.focus = Focus::Cancel;
+        assert_eq!(answer(prompt.keypress(&0x0d.into())), Some((1, None)));
+    }
+
+    #[test]
+    fn clicks_buttons() {
+        let mut prompt = prompt();
+
+        prompt.request(request(1, false));
+        prompt.request(request(2, false));
+
+        let layout = prompt.layout();
+
+        assert_eq!(answer(prompt.mouse_down(layout.cancel)), Some((1, None)));
+        assert_eq!(
+            answer(prompt.mouse_down(layout.ok)),
+            Some((2, Some(("".into(), "".into()))))
+        );
+    }
+
+    #[test]
+    fn describes_challenges() {
+        let mut prompt = prompt();
+
+        prompt.request(request(1, true));
+        assert_eq!(
+            prompt.layout().lines,
+            [
+                "The proxy https://example.com requires a login.",
+                "The server says: Staff only"
+            ]
+        );
+    }
+
+    #[test]
+    fn masks_passwords() {
+        let view = TextFieldView {
+            before: "né".to_owned(),
+            selected: "字".to_owned(),
+            after: "x".to_owned(),
+            cursor: 4,
+        };
+        let masked = mask(view);
+
+        assert_eq!(
+            (
+                masked.before.as_str(),
+                masked.selected.as_str(),
+                masked.after.as_str()
+            ),
+            ("**", "*", "*")
+        );
+        assert_eq!(masked.cursor, 3);
+    }
+}
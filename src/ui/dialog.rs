@@ -0,0 +1,474 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::{
+    navigation::{single_line, NavigationAction, NavigationElement},
+    text_field::TextField,
+    theme::Theme,
+};
+use crate::{
+    gfx::{Point, Size},
+    input::Key,
+    output::TextStyle,
+};
+
+/// Maximum number of columns of the dialog box.
+const MAX_WIDTH: usize = 60;
+/// Room for both buttons and the borders.
+const MIN_WIDTH: usize = 24;
+const OK: &str = "[ OK ]";
+const CANCEL: &str = "[ Cancel ]";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DialogKind {
+    Alert,
+    Confirm,
+    Prompt,
+}
+
+impl DialogKind {
+    pub fn from_code(code: u32) -> Self {
+        match code {
+            1 => DialogKind::Confirm,
+            2 => DialogKind::Prompt,
+            _ => DialogKind::Alert,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Focus {
+    Field,
+    Ok,
+    Cancel,
+}
+
+/// Position of the parts of the dialog on screen.
+struct Layout {
+    origin: Point,
+    width: usize,
+    height: usize,
+    lines: Vec<String>,
+    field: Option<Point>,
+    ok: Point,
+    cancel: Option<Point>,
+}
+
+/// Modal box for `alert()`, `confirm()` and `prompt()`,
+/// drawn over the page until the user answers it.
+pub struct Dialog {
+    kind: DialogKind,
+    message: String,
+    field: TextField,
+    focus: Focus,
+    size: Size,
+}
+
+impl Dialog {
+    pub fn new(kind: DialogKind, message: &str, default: &str) -> Self {
+        let mut field = TextField::new();
+
+        field.set_text(&single_line(default));
+        field.select_all();
+
+        Self {
+            kind,
+            message: message.to_owned(),
+            field,
+            focus: match kind {
+                DialogKind::Prompt => Focus::Field,
+                _ => Focus::Ok,
+            },
+            size: Size::splat(0),
+        }
+    }
+
+    pub fn set_size(&mut self, size: Size) {
+        self.size = size;
+        self.field.set_width(self.layout().width.saturating_sub(6));
+    }
+
+    pub fn cursor(&self) -> Option<Point> {
+        match (self.focus, self.layout().field) {
+            (Focus::Field, Some(origin)) => Some(origin + (1 + self.field.view().cursor as i32, 0)),
+            _ => None,
+        }
+    }
+
+    pub fn keypress(&mut self, key: &Key) -> NavigationAction {
+        let cancel = self.kind != DialogKind::Alert;
+
+        match key.char {
+            // Escape
+            0x1b => self.close(!cancel),
+            // Return
+            0x0d => self.close(self.focus != Focus::Cancel),
+            // Tab moves to the next control, Shift+Tab to the previous one
+            0x09 => {
+                let mut order = vec![Focus::Ok];
+
+                if cancel {
+                    order.insert(0, Focus::Cancel);
+                }
+                if self.kind == DialogKind::Prompt {
+                    order.insert(0, Focus::Field);
+                }
+
+                let index = order.iter().position(|&focus| focus == self.focus);
+                let index = match (index, key.modifiers.shift) {
+                    (Some(0), true) | (None, true) => order.len() - 1,
+                    (Some(index), true) => index - 1,
+                    (Some(index), false) => (index + 1) % order.len(),
+                    (None, false) => 0,
+                };
+
+                self.focus = order[index];
+
+                NavigationAction::Ignore
+            }
+            _ if self.focus == Focus::Field => {
+                self.field.keypress(key);
+
+                NavigationAction::Ignore
+            }
+            // Left and Right switch between the buttons
+            0x13 | 0x14 if cancel => {
+                self.focus = match self.focus {
+                    Focus::Ok => Focus::Cancel,
+                    _ => Focus::Ok,
+                };
+
+                NavigationAction::Ignore
+            }
+            _ => NavigationAction::Ignore,
+        }
+    }
+
+    /// Insert pasted text in the prompt field.
+    pub fn paste(&mut self, text: &str) {
+        if self.focus == Focus::Field {
+            self.field.insert(&single_line(text))
+        }
+    }
+
+    pub fn mouse_down(&mut self, origin: Point) -> NavigationAction {
+        let layout = self.layout();
+        let hit = |point: Point, width: usize| {
+            origin.y == point.y && origin.x >= point.x && origin.x < point.x + width as i32
+        };
+
+        if hit(layout.ok, OK.len()) {
+            return self.close(true);
+        }
+
+        if let Some(cancel) = layout.cancel {
+            if hit(cancel, CANCEL.len()) {
+                return self.close(false);
+            }
+        }
+
+        if let Some(field) = layout.field {
+            if hit(field, layout.width - 4) {
+                self.focus = Focus::Field;
+                self.field.click((origin.x - field.x - 1).max(0) as usize);
+            }
+        }
+
+        NavigationAction::Ignore
+    }
+
+    pub fn render(&self, theme: &Theme) -> Vec<(Point, NavigationElement)> {
+        let layout = self.layout();
+        let (background, foreground) = (theme.background, theme.foreground);
+        let element = |text: String, background, foreground| NavigationElement {
+            text,
+            background,
+            foreground,
+            style: TextStyle::default(),
+        };
+        let mut elements = frame(layout.origin, layout.width, layout.height, theme);
+
+        for (index, line) in layout.lines.iter().enumerate() {
+            elements.push((
+                layout.origin + (2, 2 + index as i32),
+                element(line.clone(), background, foreground),
+            ));
+        }
+
+        if let Some(origin) = layout.field {
+            let view = self.field.view();
+            let width = view.before.width() + view.selected.width() + view.after.width();
+            let padding = " ".repeat((layout.width - 6).saturating_sub(width));
+            let mut point = origin;
+
+            for element in [
+                element("[".to_owned(), background, foreground),
+                element(view.before, background, foreground),
+                // Selection is displayed with inverted colors
+                element(view.selected, foreground, background),
+                element(view.after + &padding, background, foreground),
+                element("]".to_owned(), background, foreground),
+            ] {
+                let width = element.text.width() as i32;
+
+                elements.push((point, element));
+
+                point = point + (width, 0);
+            }
+        }
+
+        // The focused button is displayed with inverted colors
+        let button = |text: &str, focus: Focus| match self.focus == focus {
+            true => element(text.to_owned(), foreground, background),
+            false => element(text.to_owned(), background, foreground),
+        };
+
+        if let Some(origin) = layout.cancel {
+            elements.push((origin, button(CANCEL, Focus::Cancel)));
+        }
+
+        elements.push((layout.ok, button(OK, Focus::Ok)));
+        elements
+    }
+
+    fn close(&self, accepted: bool) -> NavigationAction {
+        let value = match self.kind {
+            DialogKind::Prompt if accepted => self.field.text().to_owned(),
+            _ => String::new(),
+        };
+
+        NavigationAction::CloseDialog(accepted, value)
+    }
+
+    /// Center the dialog over the page, wrapping the message.
+    fn layout(&self) -> Layout {
+        let width = (self.size.width as usize)
+            .saturating_sub(4)
+            .clamp(MIN_WIDTH, MAX_WIDTH);
+        let controls = match self.kind {
+            DialogKind::Prompt => 5,
+            _ => 3,
+        };
+        // Keep the buttons visible if the message is too long
+        let max_lines = (self.size.height as usize)
+            .saturating_sub(controls + 3)
+            .max(1);
+        let mut lines = wrap(&self.message, width - 4);
+
+        if lines.len() > max_lines {
+            lines.truncate(max_lines);
+
+            if let Some(line) = lines.last_mut() {
+                line.push('…');
+            }
+        }
+
+        let height = lines.len() + controls + 3;
+        let origin = Point::new(
+            (self.size.width as i32 - width as i32).max(0) / 2,
+            1 + (self.size.height as i32 - height as i32).max(0) / 2,
+        );
+        let field = (self.kind == DialogKind::Prompt).then(|| origin + (2, lines.len() as i32 + 3));
+        let buttons = origin.y + height as i32 - 3;
+        let ok = Point::new(origin.x + (width - 2 - OK.len()) as i32, buttons);
+        let cancel = (self.kind != DialogKind::Alert)
+            .then(|| Point::new(ok.x - CANCEL.len() as i32 - 2, buttons));
+
+        Layout {
+            origin,
+            width,
+            height,
+            lines,
+            field,
+            ok,
+            cancel,
+        }
+    }
+}
+
+/// Empty box with a border, the background of modal components.
+pub fn frame(
+    origin: Point,
+    width: usize,
+    height: usize,
+    theme: &Theme,
+) -> Vec<(Point, NavigationElement)> {
+    let (line, side, corners) = match theme.ascii {
+        true => ('-', '|', ['+'; 4]),
+        false => ('─', '│', ['┌', '┐', '└', '┘']),
+    };
+    let inner = width - 2;
+    let fill = line.to_string().repeat(inner);
+
+    (0..height)
+        .map(|row| {
+            let text = match row {
+                0 => format!("{}{fill}{}", corners[0], corners[1]),
+                _ if row == height - 1 => format!("{}{fill}{}", corners[2], corners[3]),
+                _ => format!("{side}{}{side}", " ".repeat(inner)),
+            };
+
+            (
+                origin + (0, row as i32),
+                NavigationElement {
+                    text,
+                    background: theme.background,
+                    foreground: theme.muted,
+                    style: TextStyle::default(),
+                },
+            )
+        })
+        .collect()
+}
+
+/// Word-wrap text, keeping the line breaks of the message.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.width() + 1 + word.width() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+
+            // Break words longer than a line
+            for grapheme in word.graphemes(true) {
+                if line.width() + grapheme.width() > width {
+                    lines.push(std::mem::take(&mut line));
+                }
+
+                line.push_str(grapheme);
+            }
+        }
+
+        lines.push(line);
+    }
+
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::KeyModifiers;
+
+    fn dialog(kind: DialogKind, message: &str, default: &str) -> Dialog {
+        let mut dialog = Dialog::new(kind, message, default);
+
+        dialog.set_size(Size::new(40, 20));
+        dialog
+    }
+
+    fn closed(action: NavigationAction) -> Option<(bool, String)> {
+        match action {
+            NavigationAction::CloseDialog(accepted, value) => Some((accepted, value)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn wraps_messages() {
+        assert_eq!(wrap("one two three\nfour", 9), ["one two", "three", "four"]);
+        assert_eq!(wrap("abcdefgh", 3), ["abc", "def", "gh"]);
+        assert_eq!(wrap("", 10), [""]);
+    }
+
+    #[test]
+    fn truncates_long_messages() {
+        let mut dialog = Dialog::new(DialogKind::Alert, &"word ".repeat(100), "");
+
+        dialog.set_size(Size::new(40, 10));
+
+        let layout = dialog.layout();
+
+        assert_eq!(layout.lines.len(), 4);
+        assert!(layout.lines[3].ends_with('…'));
+        assert_eq!(layout.ok.y, layout.origin.y + layout.height as i32 - 3);
+    }
+
+    #[test]
+    fn answers_prompts() {
+        let mut prompt = dialog(DialogKind::Prompt, "Name?", "default");
+
+        // The default value is selected and replaced by typing
+        prompt.keypress(&b'x'.into());
+        prompt.paste("y\nz");
+        assert_eq!(
+            closed(prompt.keypress(&0x0d.into())),
+            Some((true, "xy z".into()))
+        );
+        assert_eq!(
+            closed(prompt.keypress(&0x1b.into())),
+            Some((false, "".into()))
+        );
+    }
+
+    #[test]
+    fn cycles_focus() {
+        let mut prompt = dialog(DialogKind::Prompt, "Name?", "");
+        let back = Key {
+            char: 0x09,
+            modifiers: KeyModifiers {
+                shift: true,
+                ..Default::default()
+            },
+        };
+
+        assert!(prompt.cursor().is_some());
+        prompt.keypress(&0x09.into());
+        assert_eq!(prompt.focus, Focus::Cancel);
+        assert_eq!(prompt.cursor(), None);
+        prompt.keypress(&0x09.into());
+        assert_eq!(prompt.focus, Focus::Ok);
+        prompt.keypress(&0x09.into());
+        assert_eq!(prompt.focus, Focus::Field);
+        prompt.keypress(&back);
+        assert_eq!(prompt.focus, Focus::Ok);
+
+        let mut confirm = dialog(DialogKind::Confirm, "Sure?", "");
+
+        confirm.keypress(&0x14.into());
+        assert_eq!(
+            closed(confirm.keypress(&0x0d.into())),
+            Some((false, "".into()))
+        );
+    }
+
+    #[test]
+    fn alerts_cannot_be_cancelled() {
+        let mut alert = dialog(DialogKind::Alert, "Hello", "");
+
+        assert_eq!(alert.layout().cancel, None);
+        alert.keypress(&0x14.into());
+        assert_eq!(
+            closed(alert.keypress(&0x1b.into())),
+            Some((true, "".into()))
+        );
+    }
+
+    #[test]
+    fn clicks_buttons() {
+        let mut confirm = dialog(DialogKind::Confirm, "Sure?", "");
+        let layout = confirm.layout();
+        let cancel = layout.cancel.unwrap();
+
+        assert_eq!(
+            closed(confirm.mouse_down(layout.ok)),
+            Some((true, "".into()))
+        );
+        assert_eq!(
+            closed(confirm.mouse_down(cancel + (CANCEL.len() as i32 - 1, 0))),
+            Some((false, "".into()))
+        );
+        assert_eq!(closed(confirm.mouse_down(cancel + (-1, 0))), None);
+    }
+}
@@ -0,0 +1,532 @@
+use std::collections::VecDeque;
+
+use unicode_width::UnicodeWidthStr;
+
+use super::{
+    navigation::{fit, single_line, NavigationAction, NavigationElement},
+    text_field::TextField,
+    theme::Theme,
+};
+use crate::{
+    gfx::{Point, Size},
+    input::Key,
+    output::TextStyle,
+};
+
+const LABEL: &str = " Save to: ";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DownloadState {
+    /// Waiting for the user to choose where to save the file
+    Pending,
+    InProgress,
+    Paused,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+impl DownloadState {
+    /// State of a finished download: 0 for completed, 1 for cancelled, 2 for failed.
+    pub fn from_code(code: u32) -> Self {
+        match code {
+            0 => DownloadState::Completed,
+            1 => DownloadState::Cancelled,
+            _ => DownloadState::Failed,
+        }
+    }
+}
+
+/// Actions on a download sent to the browser.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DownloadControl {
+    Pause = 0,
+    Resume = 1,
+    Cancel = 2,
+}
+
+#[derive(Clone, Debug)]
+pub struct Download {
+    /// Identifier given by the browser
+    pub id: u32,
+    pub url: String,
+    /// Where the file is saved, the suggested path until it's confirmed
+    pub path: String,
+    pub received: u64,
+    pub total: Option<u64>,
+    pub state: DownloadState,
+}
+
+/// List of the downloads of the session, and the prompt asking
+/// where to save new ones, displayed in place of the navigation bar.
+#[derive(Default)]
+pub struct Downloads {
+    items: Vec<Download>,
+    /// Downloads waiting for a destination, the first one is being asked
+    prompts: VecDeque<u32>,
+    field: TextField,
+    /// Whether the panel is displayed
+    open: bool,
+    /// Index of the highlighted download
+    selected: usize,
+    size: Size,
+}
+
+impl Downloads {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.selected = self.selected.min(self.items.len().saturating_sub(1));
+    }
+
+    /// Whether the user is being asked where to save a download.
+    pub fn prompting(&self) -> bool {
+        !self.prompts.is_empty()
+    }
+
+    /// Register a new download and ask where to save it.
+    pub fn start(&mut self, id: u32, url: &str, path: &str) {
+        self.items.retain(|item| item.id != id);
+        self.items.push(Download {
+            id,
+            url: url.to_owned(),
+            path: path.to_owned(),
+            received: 0,
+            total: None,
+            state: DownloadState::Pending,
+        });
+        self.prompts.push_back(id);
+
+        if self.prompts.len() == 1 {
+            self.field.set_text(path);
+        }
+    }
+
+    pub fn progress(&mut self, id: u32, received: u64, total: Option<u64>, paused: bool) {
+        if let Some(item) = self.get(id) {
+            item.received = received;
+            item.total = total;
+            item.state = match paused {
+                true => DownloadState::Paused,
+                false => DownloadState::InProgress,
+            };
+        }
+    }
+
+    pub fn finish(&mut self, id: u32, state: DownloadState, path: &str) {
+        if let Some(item) = self.get(id) {
+            item.state = state;
+
+            if !path.is_empty() {
+                item.path = path.to_owned();
+            }
+            if state == DownloadState::Completed {
+                item.total = Some(item.received.max(item.total.unwrap_or(0)));
+                item.received = item.total.unwrap_or(0);
+            }
+        }
+
+        self.answered(id);
+    }
+
+    pub fn set_size(&mut self, size: Size) {
+        self.size = size;
+        self.field
+            .set_width((size.width as usize).saturating_sub(LABEL.len() + 1));
+    }
+
+    pub fn cursor(&self) -> Option<Point> {
+        if !self.prompting() {
+            return None;
+        }
+
+        Some((LABEL.len() as i32 + self.field.view().cursor as i32, 0).into())
+    }
+
+    /// Handle a key while the save location is being asked.
+    pub fn prompt_keypress(&mut self, key: &Key) -> NavigationAction {
+        let Some(&id) = self.prompts.front() else {
+            return NavigationAction::Ignore;
+        };
+
+        match key.char {
+            // Escape cancels the download
+            0x1b => self.answer(id, String::new()),
+            // Return
+            0x0d if self.field.text().trim().is_empty() => NavigationAction::Ignore,
+            0x0d => self.answer(id, self.field.text().trim().to_owned()),
+            _ => {
+                self.field.keypress(key);
+
+                NavigationAction::Ignore
+            }
+        }
+    }
+
+    /// Insert pasted text in the save location.
+    pub fn paste(&mut self, text: &str) {
+        self.field.insert(&single_line(text))
+    }
+
+    /// Handle a key in the panel.
+    pub fn keypress(&mut self, key: &Key) -> NavigationAction {
+        let last = self.items.len().saturating_sub(1);
+        let control =
+            |item: &Download, control| NavigationAction::ControlDownload(item.id, control);
+
+        match key.char {
+            // Escape
+            0x1b | b'q' => self.open = false,
+            // Up and Down
+            0x11 | b'k' => self.selected = self.selected.saturating_sub(1),
+            0x12 | b'j' => self.selected = (self.selected + 1).min(last),
+            // Pause or resume
+            b'p' | b' ' => {
+                return match self.items.get(self.selected) {
+                    Some(item) if item.state == DownloadState::InProgress => {
+                        control(item, DownloadControl::Pause)
+                    }
+                    Some(item) if item.state == DownloadState::Paused => {
+                        control(item, DownloadControl::Resume)
+                    }
+                    _ => NavigationAction::Ignore,
+                }
+            }
+            b'c' | b'x' => {
+                return match self.items.get(self.selected) {
+                    Some(item)
+                        if matches!(
+                            item.state,
+                            DownloadState::InProgress | DownloadState::Paused
+                        ) =>
+                    {
+                        control(item, DownloadControl::Cancel)
+                    }
+                    _ => NavigationAction::Ignore,
+                }
+            }
+            // Remove finished downloads from the list
+            b'd' => {
+                self.items.retain(|item| {
+                    !matches!(
+                        item.state,
+                        DownloadState::Completed | DownloadState::Cancelled | DownloadState::Failed
+                    )
+                });
+                self.selected = self.selected.min(self.items.len().saturating_sub(1));
+            }
+            _ => (),
+        }
+
+        NavigationAction::Ignore
+    }
+
+    pub fn mouse_down(&mut self, origin: Point) -> NavigationAction {
+        let row = origin.y - 2;
+        let index = self.scroll() + row.max(0) as usize;
+
+        if row >= 0 && (row as usize) < self.visible() && index < self.items.len() {
+            self.selected = index;
+        }
+
+        NavigationAction::Ignore
+    }
+
+    /// Save location prompt, drawn over the navigation bar.
+    pub fn render_prompt(&self, theme: &Theme) -> Vec<(Point, NavigationElement)> {
+        let (background, foreground) = (theme.background, theme.foreground);
+        let view = self.field.view();
+        let width = view.before.width() + view.selected.width() + view.after.width();
+        let space = (self.size.width as usize).saturating_sub(LABEL.len());
+        let mut elements = Vec::new();
+        let mut point = Point::splat(0);
+
+        for element in [
+            NavigationElement {
+                text: LABEL.to_owned(),
+                background,
+                foreground: theme.muted,
+                style: TextStyle::default(),
+            },
+            NavigationElement {
+                text: view.before,
+                background,
+                foreground,
+                style: TextStyle::default(),
+            },
+            // Selection is displayed with inverted colors
+            NavigationElement {
+                text: view.selected,
+                background: foreground,
+                foreground: background,
+                style: TextStyle::default(),
+            },
+            NavigationElement {
+                text: format!("{}{}", view.after, " ".repeat(space.saturating_sub(width))),
+                background,
+                foreground,
+                style: TextStyle::default(),
+            },
+        ] {
+            let width = element.text.width() as i32;
+
+            elements.push((point, element));
+
+            point = point + (width, 0);
+        }
+
+        elements
+    }
+
+    /// Panel listing the downloads, drawn over the page.
+    pub fn render(&self, theme: &Theme) -> Vec<(Point, NavigationElement)> {
+        let width = self.size.width as usize;
+        let (background, foreground) = (theme.background, theme.foreground);
+        let element = |text: String, background, foreground| NavigationElement {
+            text: fit(&text, width),
+            background,
+            foreground,
+            style: TextStyle::default(),
+        };
+        let mut elements = vec![(
+            Point::new(0, 1),
+            element(" Downloads".to_owned(), foreground, background),
+        )];
+
+        for row in 0..self.visible() {
+            let index = self.scroll() + row;
+            let text = match self.items.get(index) {
+                None if index == 0 => " No downloads".to_owned(),
+                None => String::new(),
+                Some(item) if item.path.is_empty() => format!(" {:<11} {}", status(item), item.url),
+                Some(item) => format!(" {:<11} {}", status(item), item.path),
+            };
+            let (background, foreground) = match index == self.selected && index < self.items.len()
+            {
+                // The highlighted download is displayed with inverted colors
+                true => (foreground, background),
+                false if self.items.is_empty() => (background, theme.muted),
+                false => (background, foreground),
+            };
+
+            elements.push((
+                Point::new(0, row as i32 + 2),
+                element(text, background, foreground),
+            ));
+        }
+
+        elements.push((
+            Point::new(0, self.size.height as i32),
+            element(
+                " p pause/resume  c cancel  d clear finished  Esc close".to_owned(),
+                background,
+                theme.muted,
+            ),
+        ));
+
+        elements
+    }
+
+    /// Number of downloads displayed at once, between the title and the help.
+    fn visible(&self) -> usize {
+        (self.size.height as usize).saturating_sub(2)
+    }
+
+    /// Index of the first visible download, keeping the highlighted one visible.
+    fn scroll(&self) -> usize {
+        self.selected
+            .saturating_sub(self.visible().saturating_sub(1))
+    }
+
+    fn get(&mut self, id: u32) -> Option<&mut Download> {
+        self.items.iter_mut().find(|item| item.id == id)
+    }
+
+    /// Send the save location, an empty path cancels the download.
+    fn answer(&mut self, id: u32, path: String) -> NavigationAction {
+        if let Some(item) = self.get(id) {
+            item.state = match path.is_empty() {
+                true => DownloadState::Cancelled,
+                false => DownloadState::InProgress,
+            };
+
+            if !path.is_empty() {
+                item.path = path.clone();
+            }
+        }
+
+        self.answered(id);
+
+        NavigationAction::SetDownloadPath(id, path)
+    }
+
+    /// Move on to the next download waiting for a location.
+    fn answered(&mut self, id: u32) {
+        let first = self.prompts.front() == Some(&id);
+
+        self.prompts.retain(|&prompt| prompt != id);
+
+        if first {
+            let next = self.prompts.front().copied();
+
+            if let Some(path) = next
+                .and_then(|id| self.get(id))
+                .map(|item| item.path.clone())
+            {
+                self.field.set_text(&path);
+            }
+        }
+    }
+}
+
+/// Progress of a download, ie. `47% 1.2 MB`.
+fn status(item: &Download) -> String {
+    match item.state {
+        DownloadState::Pending => "waiting".to_owned(),
+        DownloadState::Completed => format!("done {}", format_size(item.received)),
+        DownloadState::Cancelled => "cancelled".to_owned(),
+        DownloadState::Failed => "failed".to_owned(),
+        DownloadState::Paused => "paused".to_owned(),
+        DownloadState::InProgress => match item.total {
+            Some(total) if total > 0 => format!("{:>3}%", item.received * 100 / total),
+            _ => format_size(item.received),
+        },
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "kB", "MB", "GB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", UNITS[unit]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(action: NavigationAction) -> Option<(u32, String)> {
+        match action {
+            NavigationAction::SetDownloadPath(id, path) => Some((id, path)),
+            _ => None,
+        }
+    }
+
+    fn control(action: NavigationAction) -> Option<(u32, DownloadControl)> {
+        match action {
+            NavigationAction::ControlDownload(id, control) => Some((id, control)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn asks_for_locations_in_order() {
+        let mut downloads = Downloads::new();
+
+        downloads.start(1, "https://a.test/a", "/tmp/a");
+        downloads.start(2, "https://b.test/b", "/tmp/b");
+
+        assert!(downloads.prompting());
+        assert_eq!(downloads.field.text(), "/tmp/a");
+        downloads.paste("2");
+        assert_eq!(
+            answer(downloads.prompt_keypress(&0x0d.into())),
+            Some((1, "/tmp/a2".into()))
+        );
+        assert_eq!(downloads.items[0].state, DownloadState::InProgress);
+        assert_eq!(downloads.items[0].path, "/tmp/a2");
+
+        // Escape cancels the next one
+        assert_eq!(downloads.field.text(), "/tmp/b");
+        assert_eq!(
+            answer(downloads.prompt_keypress(&0x1b.into())),
+            Some((2, "".into()))
+        );
+        assert_eq!(downloads.items[1].state, DownloadState::Cancelled);
+        assert!(!downloads.prompting());
+    }
+
+    #[test]
+    fn finishing_skips_the_prompt() {
+        let mut downloads = Downloads::new();
+
+        downloads.start(1, "https://a.test/a", "/tmp/a");
+        downloads.start(2, "https://b.test/b", "/tmp/b");
+        downloads.finish(1, DownloadState::from_code(2), "");
+
+        assert_eq!(downloads.items[0].state, DownloadState::Failed);
+        assert_eq!(downloads.field.text(), "/tmp/b");
+        assert_eq!(downloads.prompts, [2]);
+    }
+
+    #[test]
+    fn controls_downloads() {
+        let mut downloads = Downloads::new();
+
+        downloads.start(1, "https://a.test/a", "/tmp/a");
+        downloads.start(2, "https://b.test/b", "/tmp/b");
+        downloads.progress(1, 10, Some(100), false);
+        downloads.progress(2, 10, None, true);
+
+        assert_eq!(
+            control(downloads.keypress(&b'p'.into())),
+            Some((1, DownloadControl::Pause))
+        );
+        downloads.keypress(&b'j'.into());
+        assert_eq!(
+            control(downloads.keypress(&b' '.into())),
+            Some((2, DownloadControl::Resume))
+        );
+        assert_eq!(
+            control(downloads.keypress(&b'c'.into())),
+            Some((2, DownloadControl::Cancel))
+        );
+
+        downloads.finish(2, DownloadState::Completed, "/tmp/b.zip");
+        assert!(control(downloads.keypress(&b'c'.into())).is_none());
+        assert_eq!(downloads.items[1].path, "/tmp/b.zip");
+        assert_eq!(downloads.items[1].total, Some(10));
+
+        downloads.keypress(&b'd'.into());
+        assert_eq!(downloads.items.len(), 1);
+        assert_eq!(downloads.selected, 0);
+    }
+
+    #[test]
+    fn formats_progress() {
+        let mut item = Download {
+            id: 1,
+            url: String::new(),
+            path: String::new(),
+            received: 512,
+            total: Some(2048),
+            state: DownloadState::InProgress,
+        };
+
+        assert_eq!(status(&item), " 25%");
+        item.total = None;
+        assert_eq!(status(&item), "512 B");
+        item.received = 1_250_000;
+        assert_eq!(status(&item), "1.2 MB");
+        item.state = DownloadState::Completed;
+        assert_eq!(status(&item), "done 1.2 MB");
+        assert_eq!(format_size(5_000_000_000_000), "5000.0 GB");
+    }
+}
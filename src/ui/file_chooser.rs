@@ -0,0 +1,543 @@
+use std::{
+    collections::BTreeSet,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    navigation::{fit, NavigationAction, NavigationElement},
+    theme::Theme,
+};
+use crate::{
+    gfx::{Point, Size},
+    input::Key,
+    output::TextStyle,
+};
+
+/// Extensions of common file types, used to match MIME types in `accept`.
+const MIME_TYPES: &[(&str, &str)] = &[
+    ("txt", "text/plain"),
+    ("log", "text/plain"),
+    ("md", "text/markdown"),
+    ("csv", "text/csv"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("css", "text/css"),
+    ("js", "text/javascript"),
+    ("json", "application/json"),
+    ("xml", "application/xml"),
+    ("pdf", "application/pdf"),
+    ("zip", "application/zip"),
+    ("gz", "application/gzip"),
+    ("tar", "application/x-tar"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("svg", "image/svg+xml"),
+    ("bmp", "image/bmp"),
+    ("ico", "image/x-icon"),
+    ("avif", "image/avif"),
+    ("mp3", "audio/mpeg"),
+    ("ogg", "audio/ogg"),
+    ("wav", "audio/wav"),
+    ("flac", "audio/flac"),
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+    ("mov", "video/quicktime"),
+];
+
+#[derive(Clone, Debug)]
+struct Entry {
+    name: String,
+    path: PathBuf,
+    is_dir: bool,
+}
+
+/// Terminal file browser opened by `<input type=file>`.
+pub struct FileChooser {
+    dir: PathBuf,
+    entries: Vec<Entry>,
+    /// File types from the `accept` attribute, lowercased
+    accept: Vec<String>,
+    multiple: bool,
+    /// Files picked so far, when multiple files can be chosen
+    chosen: BTreeSet<PathBuf>,
+    /// Index of the highlighted entry
+    selected: usize,
+    /// Index of the first visible entry
+    scroll: usize,
+    show_hidden: bool,
+    /// Ignore `accept` and list every file
+    show_all: bool,
+    /// Why the directory couldn't be listed
+    error: Option<String>,
+    size: Size,
+}
+
+impl FileChooser {
+    /// Open a file chooser in `dir`, or the current directory if empty.
+    pub fn new(dir: &str, accept: &str, multiple: bool) -> Self {
+        let dir = match dir {
+            "" => env::current_dir()
+                .ok()
+                .or_else(|| env::var_os("HOME").map(PathBuf::from))
+                .unwrap_or_else(|| PathBuf::from("/")),
+            dir => PathBuf::from(dir),
+        };
+        let mut chooser = Self {
+            dir: PathBuf::new(),
+            entries: Vec::new(),
+            accept: accept
+                .split(',')
+                .map(|token| token.trim().to_lowercase())
+                .filter(|token| !token.is_empty())
+                .collect(),
+            multiple,
+            chosen: BTreeSet::new(),
+            selected: 0,
+            scroll: 0,
+            show_hidden: false,
+            show_all: false,
+            error: None,
+            size: Size::splat(0),
+        };
+
+        chooser.open(dir);
+        chooser
+    }
+
+    pub fn set_size(&mut self, size: Size) {
+        self.size = size;
+        self.reveal();
+    }
+
+    pub fn keypress(&mut self, key: &Key) -> NavigationAction {
+        let page = self.visible().saturating_sub(1).max(1) as isize;
+
+        match key.char {
+            // Escape
+            0x1b | b'q' => return NavigationAction::CloseFileChooser(Vec::new()),
+            // Return
+            0x0d => return self.activate(),
+            // Up, Down, Home and End
+            0x11 | b'k' => self.step(-1),
+            0x12 | b'j' => self.step(1),
+            0xf5 | b'g' => self.step(isize::MIN / 2),
+            0xf6 | b'G' => self.step(isize::MAX / 2),
+            b'b' => self.step(-page),
+            b' ' if self.multiple => {
+                self.toggle();
+                self.step(1);
+            }
+            b' ' => self.step(page),
+            // Right enters the highlighted directory
+            0x13 | b'l' => match self.entries.get(self.selected) {
+                Some(entry) if entry.is_dir => self.open(entry.path.clone()),
+                _ => (),
+            },
+            // Backspace and Left go to the parent directory
+            0x7f | 0x08 | 0x14 | b'h' => self.parent(),
+            b'.' => {
+                self.show_hidden = !self.show_hidden;
+                self.open(self.dir.clone());
+            }
+            b'a' if !self.accept.is_empty() => {
+                self.show_all = !self.show_all;
+                self.open(self.dir.clone());
+            }
+            _ => (),
+        }
+
+        NavigationAction::Ignore
+    }
+
+    /// Scroll by a number of rows, positive values scroll up.
+    pub fn scroll_by(&mut self, rows: isize) {
+        let max = self.entries.len().saturating_sub(self.visible());
+
+        self.scroll = self.scroll.saturating_add_signed(-rows).min(max);
+    }
+
+    /// Highlight the entry under the pointer, clicking it again opens it.
+    pub fn mouse_down(&mut self, origin: Point) -> NavigationAction {
+        let row = origin.y - 2;
+
+        if row < 0 || row as usize >= self.visible() {
+            return NavigationAction::Ignore;
+        }
+
+        let index = self.scroll + row as usize;
+
+        if index >= self.entries.len() {
+            NavigationAction::Ignore
+        } else if index == self.selected {
+            self.activate()
+        } else {
+            self.selected = index;
+
+            NavigationAction::Ignore
+        }
+    }
+
+    pub fn render(&self, theme: &Theme) -> Vec<(Point, NavigationElement)> {
+        let width = self.size.width as usize;
+        let element = |text: String, background, foreground| NavigationElement {
+            text: fit(&text, width),
+            background,
+            foreground,
+            style: TextStyle::default(),
+        };
+        let (background, foreground) = (theme.background, theme.foreground);
+        let title = match self.multiple {
+            true => "Open files",
+            false => "Open file",
+        };
+        let mut elements = vec![(
+            Point::new(0, 1),
+            element(
+                format!(" {title}: {}", self.dir.display()),
+                foreground,
+                background,
+            ),
+        )];
+
+        for row in 0..self.visible() {
+            let index = self.scroll + row;
+            let y = row as i32 + 2;
+            let (text, background, foreground) = match self.entries.get(index) {
+                None => (String::new(), background, foreground),
+                Some(entry) => {
+                    let mark = match (self.multiple, self.chosen.contains(&entry.path)) {
+                        (false, _) => "",
+                        (true, true) => "[x] ",
+                        (true, false) if entry.is_dir => "    ",
+                        (true, false) => "[ ] ",
+                    };
+                    let suffix = if entry.is_dir { "/" } else { "" };
+                    let text = format!(" {mark}{}{suffix}", entry.name);
+
+                    match index == self.selected {
+                        // The highlighted entry is displayed with inverted colors
+                        true => (text, foreground, background),
+                        false if entry.is_dir => (text, background, theme.muted),
+                        false => (text, background, foreground),
+                    }
+                }
+            };
+
+            elements.push((Point::new(0, y), element(text, background, foreground)));
+        }
+
+        let mut help = vec!["Enter open", "Backspace parent"];
+
+        if self.multiple {
+            help.push("Space select");
+        }
+        if !self.accept.is_empty() {
+            help.push(if self.show_all {
+                "a matching files"
+            } else {
+                "a all files"
+            });
+        }
+        help.extend([". hidden files", "Esc cancel"]);
+
+        let mut help = help.join("  ");
+
+        if !self.chosen.is_empty() {
+            help = format!("{} selected  {help}", self.chosen.len());
+        }
+        if let Some(ref error) = self.error {
+            help = error.clone();
+        }
+
+        elements.push((
+            Point::new(0, self.size.height as i32),
+            element(format!(" {help}"), background, theme.muted),
+        ));
+
+        elements
+    }
+
+    /// Open the highlighted directory, or choose the highlighted file.
+    fn activate(&mut self) -> NavigationAction {
+        let Some(entry) = self.entries.get(self.selected) else {
+            return NavigationAction::Ignore;
+        };
+
+        if entry.is_dir {
+            self.open(entry.path.clone());
+
+            return NavigationAction::Ignore;
+        }
+
+        let mut paths = std::mem::take(&mut self.chosen);
+
+        if paths.is_empty() {
+            paths.insert(entry.path.clone());
+        }
+
+        NavigationAction::CloseFileChooser(paths.into_iter().collect())
+    }
+
+    /// Pick or unpick the highlighted file.
+    fn toggle(&mut self) {
+        if let Some(entry) = self.entries.get(self.selected) {
+            if !entry.is_dir && !self.chosen.remove(&entry.path) {
+                self.chosen.insert(entry.path.clone());
+            }
+        }
+    }
+
+    fn parent(&mut self) {
+        let current = self.dir.clone();
+
+        if let Some(parent) = current.parent() {
+            self.open(parent.to_owned());
+
+            // Highlight the directory we came from
+            if let Some(index) = self.entries.iter().position(|entry| entry.path == current) {
+                self.selected = index;
+                self.reveal();
+            }
+        }
+    }
+
+    /// List the content of a directory.
+    fn open(&mut self, dir: PathBuf) {
+        let dir = fs::canonicalize(&dir).unwrap_or(dir);
+        let mut entries = Vec::new();
+
+        self.error = None;
+
+        match fs::read_dir(&dir) {
+            Ok(iter) => {
+                for entry in iter.flatten() {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    let path = entry.path();
+                    // Follow symbolic links to directories
+                    let is_dir = path.is_dir();
+
+                    if !self.show_hidden && name.starts_with('.') {
+                        continue;
+                    }
+                    if !is_dir && !self.show_all && !self.accepts(&path) {
+                        continue;
+                    }
+
+                    entries.push(Entry { name, path, is_dir });
+                }
+            }
+            Err(error) => self.error = Some(format!("Cannot open this directory: {error}")),
+        }
+
+        entries.sort_by_cached_key(|entry| (!entry.is_dir, entry.name.to_lowercase()));
+
+        if let Some(parent) = dir.parent() {
+            entries.insert(
+                0,
+                Entry {
+                    name: "..".to_owned(),
+                    path: parent.to_owned(),
+                    is_dir: true,
+                },
+            );
+        }
+
+        // Keep the highlighted entry when the listing is refreshed
+        let selected = self.entries.get(self.selected).map(|entry| &entry.path);
+
+        self.selected = match selected {
+            Some(path) if dir == self.dir => entries
+                .iter()
+                .position(|entry| &entry.path == path)
+                .unwrap_or(0),
+            _ => 0,
+        };
+        self.scroll = 0;
+        self.entries = entries;
+        self.dir = dir;
+        self.reveal();
+    }
+
+    /// Whether a file matches the `accept` attribute.
+    fn accepts(&self, path: &Path) -> bool {
+        if self.accept.is_empty() {
+            return true;
+        }
+
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let mime = MIME_TYPES
+            .iter()
+            .find(|(ext, _)| *ext == extension)
+            .map(|(_, mime)| *mime);
+
+        self.accept.iter().any(|token| {
+            if token.starts_with('.') {
+                name.ends_with(token.as_str())
+            } else if let Some(group) = token.strip_suffix("/*") {
+                mime.is_some_and(|mime| mime.split('/').next() == Some(group))
+            } else {
+                mime == Some(token.as_str())
+            }
+        })
+    }
+
+    /// Number of entries displayed at once, between the title and the help.
+    fn visible(&self) -> usize {
+        (self.size.height as usize).saturating_sub(2)
+    }
+
+    fn step(&mut self, delta: isize) {
+        let last = self.entries.len().saturating_sub(1);
+
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+        self.reveal();
+    }
+
+    /// Scroll to keep the highlighted entry visible.
+    fn reveal(&mut self) {
+        let visible = self.visible().max(1);
+
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + visible {
+            self.scroll = self.selected + 1 - visible;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process;
+
+    use super::*;
+
+    /// Temporary directory with a few files, removed when dropped.
+    struct Fixture(PathBuf);
+
+    impl Fixture {
+        fn new(name: &str) -> Self {
+            let root = env::temp_dir().join(format!("carbonyl-{name}-{}", process::id()));
+
+            fs::create_dir_all(root.join("sub")).unwrap();
+
+            for file in ["b.txt", "A.png", "c.PDF", ".hidden.txt", "sub/x.txt"] {
+                fs::write(root.join(file), "").unwrap();
+            }
+
+            Self(fs::canonicalize(root).unwrap())
+        }
+
+        fn chooser(&self, accept: &str, multiple: bool) -> FileChooser {
+            let mut chooser = FileChooser::new(self.0.to_str().unwrap(), accept, multiple);
+
+            chooser.set_size(Size::new(40, 10));
+            chooser
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn names(chooser: &FileChooser) -> Vec<&str> {
+        chooser
+            .entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn lists_directories_first() {
+        let fixture = Fixture::new("chooser-list");
+        let mut chooser = fixture.chooser("", false);
+
+        assert_eq!(names(&chooser), ["..", "sub", "A.png", "b.txt", "c.PDF"]);
+
+        chooser.keypress(&b'.'.into());
+        assert_eq!(
+            names(&chooser),
+            ["..", "sub", ".hidden.txt", "A.png", "b.txt", "c.PDF"]
+        );
+    }
+
+    #[test]
+    fn filters_accepted_types() {
+        let fixture = Fixture::new("chooser-accept");
+        let mut chooser = fixture.chooser("image/*, .TXT", false);
+
+        assert_eq!(names(&chooser), ["..", "sub", "A.png", "b.txt"]);
+
+        chooser.keypress(&b'a'.into());
+        assert_eq!(names(&chooser), ["..", "sub", "A.png", "b.txt", "c.PDF"]);
+
+        let pdf = fixture.chooser("application/pdf", false);
+
+        assert_eq!(names(&pdf), ["..", "sub", "c.PDF"]);
+    }
+
+    #[test]
+    fn enters_and_leaves_directories() {
+        let fixture = Fixture::new("chooser-navigate");
+        let mut chooser = fixture.chooser("", false);
+
+        chooser.keypress(&b'j'.into());
+        chooser.keypress(&0x0d.into());
+        assert_eq!(chooser.dir, fixture.0.join("sub"));
+        assert_eq!(names(&chooser), ["..", "x.txt"]);
+
+        // Going back highlights the directory we came from
+        chooser.keypress(&0x7f.into());
+        assert_eq!(chooser.dir, fixture.0);
+        assert_eq!(chooser.selected, 1);
+
+        chooser.keypress(&b'G'.into());
+        assert!(matches!(
+            chooser.keypress(&0x0d.into()),
+            NavigationAction::CloseFileChooser(paths) if paths == [fixture.0.join("c.PDF")]
+        ));
+    }
+
+    #[test]
+    fn picks_multiple_files() {
+        let fixture = Fixture::new("chooser-multiple");
+        let mut chooser = fixture.chooser("", true);
+
+        chooser.keypress(&b'g'.into());
+        // Directories can't be picked
+        chooser.keypress(&b' '.into());
+        chooser.keypress(&b' '.into());
+        chooser.keypress(&b' '.into());
+        chooser.keypress(&b' '.into());
+        // Picking a file again unpicks it
+        chooser.keypress(&b'k'.into());
+        chooser.keypress(&b' '.into());
+        assert!(matches!(
+            chooser.keypress(&0x0d.into()),
+            NavigationAction::CloseFileChooser(paths)
+                if paths == [fixture.0.join("A.png")]
+        ));
+    }
+
+    #[test]
+    fn reports_missing_directories() {
+        let fixture = Fixture::new("chooser-missing");
+        let chooser = FileChooser::new(fixture.0.join("missing").to_str().unwrap(), "", false);
+
+        assert!(chooser.error.is_some());
+        assert_eq!(names(&chooser), [".."]);
+    }
+}
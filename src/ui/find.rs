@@ -0,0 +1,164 @@
+use unicode_width::UnicodeWidthStr;
+
+use super::{
+    navigation::{single_line, NavigationAction, NavigationElement},
+    text_field::TextField,
+    theme::Theme,
+};
+use crate::{
+    gfx::{Point, Size},
+    input::Key,
+    output::TextStyle,
+};
+
+const LABEL: &str = " Find: ";
+/// Columns reserved on the right for the match count
+const RESULT_WIDTH: usize = 14;
+
+/// In-page search, displayed in place of the navigation bar.
+#[derive(Default)]
+pub struct FindBar {
+    field: TextField,
+    open: bool,
+    /// Active match and number of matches, as reported by the browser
+    result: Option<(u32, u32)>,
+}
+
+impl FindBar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Show the find bar, keeping the previous query selected.
+    pub fn open(&mut self) {
+        self.open = true;
+        self.field.select_all();
+    }
+
+    /// Hide the find bar and clear the highlighted matches.
+    pub fn close(&mut self) -> NavigationAction {
+        self.open = false;
+        self.result = None;
+
+        NavigationAction::Find(String::new(), true)
+    }
+
+    pub fn set_result(&mut self, current: u32, total: u32) {
+        self.result = Some((current, total));
+    }
+
+    pub fn set_size(&mut self, size: Size) {
+        self.field
+            .set_width((size.width as usize).saturating_sub(LABEL.len() + RESULT_WIDTH));
+    }
+
+    pub fn cursor(&self) -> Option<Point> {
+        if !self.open {
+            return None;
+        }
+
+        Some((LABEL.len() as i32 + self.field.view().cursor as i32, 0).into())
+    }
+
+    pub fn keypress(&mut self, key: &Key) -> NavigationAction {
+        match key.char {
+            // Escape
+            0x1b => self.close(),
+            // Return, Shift+Return searches backwards
+            0x0d if self.field.text().is_empty() => NavigationAction::Ignore,
+            0x0d => NavigationAction::Find(self.field.text().to_owned(), !key.modifiers.shift),
+            _ => {
+                let query = self.field.text().to_owned();
+
+                self.field.keypress(key);
+
+                // Search as the user types
+                if self.field.text() == query {
+                    NavigationAction::Ignore
+                } else {
+                    self.result = None;
+
+                    NavigationAction::Find(self.field.text().to_owned(), true)
+                }
+            }
+        }
+    }
+
+    /// Insert pasted text and search for it.
+    pub fn paste(&mut self, text: &str) -> NavigationAction {
+        self.field.insert(&single_line(text));
+        self.result = None;
+
+        NavigationAction::Find(self.field.text().to_owned(), true)
+    }
+
+    pub fn mouse_down(&mut self, origin: Point) -> NavigationAction {
+        if origin.y == 0 && origin.x >= LABEL.len() as i32 {
+            self.field.click(origin.x as usize - LABEL.len());
+        }
+
+        NavigationAction::Ignore
+    }
+
+    pub fn render(&self, size: Size, theme: &Theme) -> Vec<(Point, NavigationElement)> {
+        let background = theme.background;
+        let foreground = theme.foreground;
+        let view = self.field.view();
+        let width = view.before.width() + view.selected.width() + view.after.width();
+        let space = (size.width as usize).saturating_sub(LABEL.len() + RESULT_WIDTH);
+        let result = match self.result {
+            _ if self.field.text().is_empty() => String::new(),
+            None => String::new(),
+            Some((_, 0)) => "No matches".to_owned(),
+            Some((current, total)) => format!("{current}/{total}"),
+        };
+        let mut elements = Vec::new();
+        let mut point = Point::splat(0);
+
+        for element in [
+            NavigationElement {
+                text: LABEL.to_owned(),
+                background,
+                foreground: theme.muted,
+                style: TextStyle::default(),
+            },
+            NavigationElement {
+                text: view.before,
+                background,
+                foreground,
+                style: TextStyle::default(),
+            },
+            // Selection is displayed with inverted colors
+            NavigationElement {
+                text: view.selected,
+                background: foreground,
+                foreground: background,
+                style: TextStyle::default(),
+            },
+            NavigationElement {
+                text: format!("{}{}", view.after, " ".repeat(space.saturating_sub(width))),
+                background,
+                foreground,
+                style: TextStyle::default(),
+            },
+            NavigationElement {
+                text: format!("{result:>width$} ", width = RESULT_WIDTH - 1),
+                background,
+                foreground: theme.muted,
+                style: TextStyle::default(),
+            },
+        ] {
+            let width = element.text.width() as i32;
+
+            elements.push((point, element));
+
+            point = point + (width, 0);
+        }
+
+        elements
+    }
+}
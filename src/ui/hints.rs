@@ -0,0 +1,153 @@
+use crate::{
+    gfx::{Color, Point},
+    input::Key,
+    output::TextStyle,
+};
+
+use super::navigation::{NavigationAction, NavigationElement};
+
+/// Characters used for labels, home row first.
+const ALPHABET: &[u8] = b"sadfjklewcmpgh";
+
+/// A clickable element of the page.
+#[derive(Clone, Debug)]
+pub struct Hint {
+    pub label: String,
+    /// Terminal cell where the label is drawn
+    pub origin: Point,
+    /// Point to click in browser pixels
+    pub target: Point<u32>,
+}
+
+/// Link hinting mode, label every clickable element
+/// and click the one whose label gets typed.
+pub struct Hints {
+    hints: Vec<Hint>,
+    typed: String,
+    done: bool,
+}
+
+impl Hints {
+    /// Create labels for a list of `(cell, target)` pairs.
+    pub fn new(mut targets: Vec<(Point, Point<u32>)>) -> Hints {
+        // Label in reading order
+        targets.sort_by_key(|(origin, _)| (origin.y, origin.x));
+
+        let labels = labels(targets.len());
+        let hints = targets
+            .into_iter()
+            .zip(labels)
+            .map(|((origin, target), label)| Hint {
+                label,
+                origin,
+                target,
+            })
+            .collect();
+
+        Hints {
+            hints,
+            typed: String::new(),
+            done: false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hints.is_empty()
+    }
+
+    /// Whether hint mode should be exited.
+    pub fn done(&self) -> bool {
+        self.done
+    }
+
+    pub fn cancel(&mut self) {
+        self.done = true
+    }
+
+    pub fn keypress(&mut self, key: &Key) -> NavigationAction {
+        match key.char {
+            // Backspace
+            0x7f | 0x08 => {
+                self.typed.pop();
+            }
+            char if ALPHABET.contains(&char.to_ascii_lowercase()) => {
+                self.typed.push(char.to_ascii_lowercase() as char);
+
+                let mut matches = self
+                    .hints
+                    .iter()
+                    .filter(|hint| hint.label.starts_with(&self.typed));
+
+                match (matches.next(), matches.next()) {
+                    // Unknown label, ignore the key
+                    (None, _) => {
+                        self.typed.pop();
+                    }
+                    (Some(hint), None) if hint.label == self.typed => {
+                        self.done = true;
+
+                        return NavigationAction::Click(hint.target);
+                    }
+                    _ => (),
+                }
+            }
+            // Escape or anything else exits
+            _ => self.done = true,
+        }
+
+        NavigationAction::Ignore
+    }
+
+    pub fn render(&self) -> Vec<(Point, NavigationElement)> {
+        let background = Color::new(255, 215, 0);
+        let mut elements = Vec::new();
+
+        for hint in &self.hints {
+            if let Some(rest) = hint.label.strip_prefix(&self.typed) {
+                elements.push((
+                    hint.origin,
+                    NavigationElement {
+                        text: self.typed.clone(),
+                        background,
+                        foreground: Color::splat(140),
+                        style: TextStyle::default(),
+                    },
+                ));
+                elements.push((
+                    hint.origin + (self.typed.len() as i32, 0),
+                    NavigationElement {
+                        text: rest.to_owned(),
+                        background,
+                        foreground: Color::black(),
+                        style: TextStyle::default(),
+                    },
+                ));
+            }
+        }
+
+        elements
+    }
+}
+
+/// Generate `count` labels of the same length, so no label is the prefix of another.
+fn labels(count: usize) -> Vec<String> {
+    let base = ALPHABET.len();
+    let mut length = 1;
+
+    while base.pow(length as u32) < count {
+        length += 1;
+    }
+
+    (0..count)
+        .map(|mut index| {
+            let mut label = vec![0u8; length];
+
+            for char in label.iter_mut().rev() {
+                *char = ALPHABET[index % base];
+                index /= base;
+            }
+
+            String::from_utf8(label).unwrap()
+        })
+        .collect()
+}
@@ -0,0 +1,445 @@
+use std::env;
+
+use crate::{
+    config::Ini,
+    input::{Key, KeyModifiers},
+    utils::log,
+};
+
+/// Commands that can be bound to a key sequence.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    Back,
+    Forward,
+    FocusUrlBar,
+    Reload,
+    HardReload,
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    ScrollUp,
+    ScrollDown,
+    PageUp,
+    PageDown,
+    Quit,
+    /// Search for text in the page
+    Find,
+    /// Toggle the reflowed text view of the article
+    ReaderMode,
+    /// Copy the current URL to the clipboard
+    CopyUrl,
+    /// Copy the text selected in the page to the clipboard
+    CopySelection,
+    /// Paste the clipboard, if the terminal allows reading it
+    Paste,
+    /// Select text with the mouse instead of sending clicks to the page
+    SelectionMode,
+    /// Label clickable elements to click them with the keyboard
+    Hints,
+    /// Show or hide the list of downloads
+    Downloads,
+    /// List the permissions allowed or denied for the current site
+    Permissions,
+    /// Only used by modal presets, let keys go through to the page
+    InsertMode,
+    /// Only used by modal presets, interpret keys as commands again
+    NormalMode,
+}
+
+pub enum KeymapResult {
+    /// A binding matched
+    Command(Command),
+    /// The keys so far are the beginning of a chord
+    Pending,
+    /// The chord was abandoned with Escape
+    Cancelled,
+    /// No binding matched, the keys should be forwarded to the page
+    Unbound(Vec<Key>),
+}
+
+/// Translate key presses into commands.
+///
+/// Bindings come from a preset (`default`, `emacs` or `vi`) and can be
+/// overridden in the `[keys]` section of the configuration file:
+///
+/// ```ini
+/// [keys]
+/// preset = vi
+/// C-l = focus-url-bar
+/// g r = hard-reload
+/// A-Equal = zoom-in
+/// A-q = none
+/// ```
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: Vec<(Vec<Key>, Command)>,
+    pending: Vec<Key>,
+    /// Whether the preset has a normal and an insert mode (vi)
+    modal: bool,
+    insert: bool,
+}
+
+const DEFAULT: &[(&str, Command)] = &[
+    // Option+Arrow is usually reported as Alt on macOS
+    ("A-Left", Command::Back),
+    ("A-Right", Command::Forward),
+    ("M-Left", Command::Back),
+    ("M-Right", Command::Forward),
+    ("C-l", Command::FocusUrlBar),
+    ("A-r", Command::Reload),
+    ("A-R", Command::HardReload),
+    ("A-=", Command::ZoomIn),
+    ("A-+", Command::ZoomIn),
+    ("A--", Command::ZoomOut),
+    ("A-0", Command::ZoomReset),
+    ("A-q", Command::Quit),
+    ("A-h", Command::Hints),
+    ("C-f", Command::Find),
+    ("A-m", Command::ReaderMode),
+    ("A-u", Command::CopyUrl),
+    ("A-c", Command::CopySelection),
+    ("A-v", Command::Paste),
+    ("A-s", Command::SelectionMode),
+    ("A-d", Command::Downloads),
+    ("A-p", Command::Permissions),
+];
+
+const EMACS: &[(&str, Command)] = &[
+    ("A-b", Command::Back),
+    ("A-f", Command::Forward),
+    ("C-x C-f", Command::FocusUrlBar),
+    ("C-x r", Command::Reload),
+    ("C-x R", Command::HardReload),
+    ("C-x +", Command::ZoomIn),
+    ("C-x -", Command::ZoomOut),
+    ("C-x 0", Command::ZoomReset),
+    ("C-p", Command::ScrollUp),
+    ("C-n", Command::ScrollDown),
+    ("A-v", Command::PageUp),
+    ("C-v", Command::PageDown),
+    ("C-x k", Command::Quit),
+    ("C-x h", Command::Hints),
+    ("C-s", Command::Find),
+    ("C-x m", Command::ReaderMode),
+    ("C-x y", Command::CopyUrl),
+    ("A-w", Command::CopySelection),
+    ("C-y", Command::Paste),
+    ("C-x Space", Command::SelectionMode),
+    ("C-x d", Command::Downloads),
+    ("C-x p", Command::Permissions),
+];
+
+const VI: &[(&str, Command)] = &[
+    ("H", Command::Back),
+    ("L", Command::Forward),
+    ("o", Command::FocusUrlBar),
+    ("r", Command::Reload),
+    ("R", Command::HardReload),
+    ("+", Command::ZoomIn),
+    ("-", Command::ZoomOut),
+    ("=", Command::ZoomReset),
+    ("k", Command::ScrollUp),
+    ("j", Command::ScrollDown),
+    ("u", Command::PageUp),
+    ("d", Command::PageDown),
+    ("Z Z", Command::Quit),
+    ("f", Command::Hints),
+    ("/", Command::Find),
+    ("g r", Command::ReaderMode),
+    ("y y", Command::CopyUrl),
+    ("y v", Command::CopySelection),
+    ("p", Command::Paste),
+    ("v", Command::SelectionMode),
+    ("g d", Command::Downloads),
+    ("g p", Command::Permissions),
+    ("i", Command::InsertMode),
+    ("Esc", Command::NormalMode),
+];
+
+impl Command {
+    pub fn parse(name: &str) -> Option<Command> {
+        Some(match name {
+            "back" => Command::Back,
+            "forward" => Command::Forward,
+            "focus-url-bar" => Command::FocusUrlBar,
+            "reload" => Command::Reload,
+            "hard-reload" => Command::HardReload,
+            "zoom-in" => Command::ZoomIn,
+            "zoom-out" => Command::ZoomOut,
+            "zoom-reset" => Command::ZoomReset,
+            "scroll-up" => Command::ScrollUp,
+            "scroll-down" => Command::ScrollDown,
+            "page-up" => Command::PageUp,
+            "page-down" => Command::PageDown,
+            "quit" => Command::Quit,
+            "hints" => Command::Hints,
+            "find" => Command::Find,
+            "reader-mode" => Command::ReaderMode,
+            "copy-url" => Command::CopyUrl,
+            "copy-selection" => Command::CopySelection,
+            "paste" => Command::Paste,
+            "selection-mode" => Command::SelectionMode,
+            "downloads" => Command::Downloads,
+            "permissions" => Command::Permissions,
+            "insert-mode" => Command::InsertMode,
+            "normal-mode" => Command::NormalMode,
+            _ => return None,
+        })
+    }
+}
+
+impl Keymap {
+    pub fn from_ini(ini: &Ini) -> Keymap {
+        let preset = ini.get("keys", "preset").unwrap_or("default");
+        let (bindings, modal) = match preset {
+            "emacs" => (EMACS, false),
+            "vi" => (VI, true),
+            "default" => (DEFAULT, false),
+            name => {
+                log::warning!("unknown key preset {name:?}, using default");
+
+                (DEFAULT, false)
+            }
+        };
+        let mut keymap = Keymap {
+            bindings: Vec::new(),
+            pending: Vec::new(),
+            modal,
+            insert: false,
+        };
+
+        for &(keys, command) in bindings {
+            keymap.bind(keys, Some(command));
+        }
+
+        for (keys, command) in ini.section("keys") {
+            match (keys, command) {
+                ("preset", _) => (),
+                (keys, "none") => keymap.bind(keys, None),
+                (keys, name) => match Command::parse(name) {
+                    Some(command) => keymap.bind(keys, Some(command)),
+                    None => log::warning!("unknown command {name:?} for keys {keys:?}"),
+                },
+            }
+        }
+
+        keymap
+    }
+
+    /// Bind a key sequence to a command, or remove its binding.
+    pub fn bind(&mut self, keys: &str, command: Option<Command>) {
+        let Some(keys) = keys
+            .split_whitespace()
+            .map(parse_key)
+            .collect::<Option<Vec<Key>>>()
+        else {
+            return log::warning!("invalid key sequence {keys:?}");
+        };
+
+        self.bindings.retain(|(bound, _)| bound != &keys);
+
+        if let Some(command) = command {
+            self.bindings.push((keys, command));
+        }
+    }
+
+    /// Feed a key press, returns the command to run once a binding is complete.
+    pub fn feed(&mut self, key: &Key) -> KeymapResult {
+        if !self.pending.is_empty() && key == &Key::from(0x1b) {
+            self.pending.clear();
+
+            return KeymapResult::Cancelled;
+        }
+
+        self.pending.push(key.clone());
+
+        let insert = self.modal && self.insert;
+        let mut partial = false;
+
+        for (keys, command) in &self.bindings {
+            // Only the key leaving insert mode is active in insert mode
+            if insert && *command != Command::NormalMode {
+                continue;
+            }
+
+            if keys.starts_with(&self.pending) {
+                if keys.len() == self.pending.len() {
+                    let command = *command;
+
+                    self.pending.clear();
+
+                    match command {
+                        Command::InsertMode if self.modal => self.insert = true,
+                        Command::NormalMode if self.modal => self.insert = false,
+                        _ => (),
+                    }
+
+                    return KeymapResult::Command(command);
+                }
+
+                partial = true;
+            }
+        }
+
+        if partial {
+            KeymapResult::Pending
+        } else {
+            KeymapResult::Unbound(std::mem::take(&mut self.pending))
+        }
+    }
+}
+
+/// Parse a key in Emacs notation: `C-` for Control, `A-` for Alt, `S-` for Shift
+/// and `M-` for the platform modifier (Command on macOS, Alt elsewhere).
+fn parse_key(notation: &str) -> Option<Key> {
+    let mut modifiers = KeyModifiers::default();
+    let mut control = false;
+    let mut name = notation;
+
+    while name.len() > 2 && name.as_bytes()[1] == b'-' {
+        match name.as_bytes()[0] {
+            b'C' => control = true,
+            b'A' => modifiers.alt = true,
+            b'S' => modifiers.shift = true,
+            b'M' if env::consts::OS == "macos" => modifiers.meta = true,
+            b'M' => modifiers.alt = true,
+            _ => return None,
+        }
+
+        name = &name[2..];
+    }
+
+    let char = match name {
+        "Up" => 0x11,
+        "Down" => 0x12,
+        "Right" => 0x13,
+        "Left" => 0x14,
+        "Home" => 0xf5,
+        "End" => 0xf6,
+        "Delete" => 0xf7,
+        "Backspace" => 0x7f,
+        "Tab" => 0x09,
+        "Enter" | "Return" => 0x0d,
+        "Esc" | "Escape" => 0x1b,
+        "Space" => 0x20,
+        // Can't be used as is in INI keys
+        "Equal" => b'=',
+        "Plus" => b'+',
+        "Minus" => b'-',
+        name if name.len() == 1 && name.is_ascii() => name.as_bytes()[0],
+        _ => return None,
+    };
+
+    Some(match (control, char) {
+        // Terminals send Control + letter as an ASCII control code
+        (true, b'a'..=b'z') => Key {
+            char: char - b'a' + 1,
+            modifiers,
+        },
+        (true, b'@'..=b'_') => Key {
+            char: char - b'@',
+            modifiers,
+        },
+        // Control is reported as a modifier for special keys
+        (true, _) => Key {
+            char,
+            modifiers: KeyModifiers {
+                control: true,
+                ..modifiers
+            },
+        },
+        (false, _) => Key { char, modifiers },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(char: u8, modifiers: KeyModifiers) -> Option<Key> {
+        Some(Key { char, modifiers })
+    }
+
+    #[test]
+    fn parse_plain_keys() {
+        assert_eq!(parse_key("a"), Some(b'a'.into()));
+        assert_eq!(parse_key("Up"), Some(0x11.into()));
+        assert_eq!(parse_key("Home"), Some(0xf5.into()));
+        assert_eq!(parse_key("End"), Some(0xf6.into()));
+        assert_eq!(parse_key("Delete"), Some(0xf7.into()));
+        assert_eq!(parse_key("Esc"), Some(0x1b.into()));
+        assert_eq!(parse_key("Equal"), Some(b'='.into()));
+        assert_eq!(parse_key("-"), Some(b'-'.into()));
+    }
+
+    #[test]
+    fn parse_control() {
+        assert_eq!(parse_key("C-a"), Some(0x01.into()));
+        assert_eq!(parse_key("C-["), Some(0x1b.into()));
+        assert_eq!(
+            parse_key("C-Left"),
+            key(
+                0x14,
+                KeyModifiers {
+                    control: true,
+                    ..Default::default()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn parse_modifiers() {
+        let alt = KeyModifiers {
+            alt: true,
+            ..Default::default()
+        };
+
+        assert_eq!(parse_key("A-f"), key(b'f', alt.clone()));
+        assert_eq!(
+            parse_key("A-S-Right"),
+            key(
+                0x13,
+                KeyModifiers {
+                    shift: true,
+                    ..alt.clone()
+                }
+            )
+        );
+
+        if env::consts::OS != "macos" {
+            assert_eq!(parse_key("M-x"), key(b'x', alt));
+        }
+    }
+
+    #[test]
+    fn escape_cancels_chords() {
+        let mut keymap = Keymap {
+            bindings: Vec::new(),
+            pending: Vec::new(),
+            modal: false,
+            insert: false,
+        };
+
+        keymap.bind("C-x r", Some(Command::Reload));
+
+        assert!(matches!(keymap.feed(&0x18.into()), KeymapResult::Pending));
+        assert!(matches!(keymap.feed(&0x1b.into()), KeymapResult::Cancelled));
+        assert!(matches!(
+            keymap.feed(&b'r'.into()),
+            KeymapResult::Unbound(keys) if keys == [b'r'.into()]
+        ));
+        assert!(matches!(keymap.feed(&0x18.into()), KeymapResult::Pending));
+        assert!(matches!(
+            keymap.feed(&b'r'.into()),
+            KeymapResult::Command(Command::Reload)
+        ));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for notation in ["", "X-a", "Foo", "C-", "é"] {
+            assert_eq!(parse_key(notation), None, "{notation}");
+        }
+    }
+}
@@ -0,0 +1,406 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::{
+    navigation::{NavigationAction, NavigationElement},
+    theme::Theme,
+};
+use crate::{
+    gfx::{Point, Rect, Size},
+    input::Key,
+    output::TextStyle,
+};
+
+/// Columns used around the labels, for the check mark and padding.
+const PADDING: usize = 4;
+
+#[derive(Clone, Debug, Default)]
+pub struct MenuItem {
+    pub label: String,
+    /// Disabled items and group headers can't be picked
+    pub enabled: bool,
+    /// Draw a line instead of a label
+    pub separator: bool,
+    /// Current value of a `<select>`, or a checked context menu entry
+    pub checked: bool,
+}
+
+impl MenuItem {
+    fn selectable(&self) -> bool {
+        self.enabled && !self.separator
+    }
+}
+
+/// List of options displayed next to an element, used for `<select>`
+/// popups, context menus and any other choice offered by the browser.
+pub struct Menu {
+    items: Vec<MenuItem>,
+    /// Cells covered by the element that opened the menu
+    anchor: Rect,
+    /// Index of the highlighted item
+    selected: Option<usize>,
+    /// Index of the first visible item
+    scroll: usize,
+    size: Size,
+}
+
+impl Menu {
+    pub fn new(anchor: Rect, items: Vec<MenuItem>, selected: Option<usize>) -> Self {
+        let mut menu = Self {
+            items,
+            anchor,
+            selected: None,
+            scroll: 0,
+            size: Size::splat(0),
+        };
+
+        menu.select(selected.or_else(|| menu.items.iter().position(|item| item.checked)));
+        menu
+    }
+
+    pub fn set_size(&mut self, size: Size) {
+        self.size = size;
+        self.select(self.selected);
+    }
+
+    pub fn keypress(&mut self, key: &Key) -> NavigationAction {
+        match key.char {
+            // Escape
+            0x1b => return NavigationAction::CloseMenu(None),
+            // Return or Space
+            0x0d | b' ' => return NavigationAction::CloseMenu(self.selected),
+            // Up, Down, Home and End
+            0x11 => self.step(-1),
+            0x12 | 0x09 => self.step(1),
+            0xf5 => {
+                self.selected = None;
+                self.step(1);
+            }
+            0xf6 => {
+                self.selected = None;
+                self.step(-1);
+            }
+            // Jump to the next item starting with a letter
+            char if char.is_ascii_graphic() => {
+                let start = self.selected.map_or(0, |index| index + 1);
+                let prefix = (char as char).to_ascii_lowercase();
+                let count = self.items.len();
+                let found = (0..count)
+                    .map(|offset| (start + offset) % count)
+                    .find(|&index| {
+                        let item = &self.items[index];
+
+                        item.selectable()
+                            && item.label.trim_start().to_lowercase().starts_with(prefix)
+                    });
+
+                if found.is_some() {
+                    self.select(found);
+                }
+            }
+            _ => (),
+        }
+
+        NavigationAction::Ignore
+    }
+
+    /// Scroll by a number of rows, positive values scroll up.
+    pub fn scroll_by(&mut self, rows: isize) {
+        let visible = self.visible();
+        let max = self.items.len().saturating_sub(visible);
+
+        self.scroll = self.scroll.saturating_add_signed(-rows).min(max);
+    }
+
+    /// Pick the item under the pointer, or close the menu when clicking outside.
+    pub fn mouse_down(&mut self, origin: Point) -> NavigationAction {
+        match self.item_at(origin) {
+            Some(index) if self.items[index].selectable() => {
+                NavigationAction::CloseMenu(Some(index))
+            }
+            Some(_) => NavigationAction::Ignore,
+            None => NavigationAction::CloseMenu(None),
+        }
+    }
+
+    /// Highlight the item under the pointer.
+    pub fn mouse_move(&mut self, origin: Point) {
+        if let Some(index) = self.item_at(origin) {
+            if self.items[index].selectable() {
+                self.selected = Some(index);
+            }
+        }
+    }
+
+    pub fn render(&self, theme: &Theme) -> Vec<(Point, NavigationElement)> {
+        let frame = self.frame();
+        let width = frame.size.width as usize;
+        let (separator, check) = match theme.ascii {
+            true => ("-", "*"),
+            false => ("─", "✓"),
+        };
+        let mut elements = Vec::new();
+
+        for (row, (index, item)) in self
+            .items
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(self.visible())
+            .enumerate()
+        {
+            let (background, foreground) = match self.selected == Some(index) {
+                // The highlighted item is displayed with inverted colors
+                true => (theme.foreground, theme.background),
+                false if item.selectable() => (theme.background, theme.foreground),
+                false => (theme.background, theme.disabled),
+            };
+            let text = if item.separator {
+                separator.repeat(width)
+            } else {
+                let mark = if item.checked { check } else { " " };
+                let label = truncate(&item.label, width.saturating_sub(PADDING));
+                let padding = width.saturating_sub(PADDING + label.width());
+
+                format!(" {mark} {label}{} ", " ".repeat(padding))
+            };
+
+            elements.push((
+                frame.origin + (0, row as i32),
+                NavigationElement {
+                    text,
+                    background,
+                    foreground,
+                    style: TextStyle::default(),
+                },
+            ));
+        }
+
+        elements
+    }
+
+    /// Number of items displayed at once.
+    fn visible(&self) -> usize {
+        self.frame().size.height as usize
+    }
+
+    /// Cells covered by the menu, below the anchor if there's enough room,
+    /// or on the side with the most space.
+    fn frame(&self) -> Rect {
+        let viewport = self.size.cast::<i32>();
+        let labels = self.items.iter().map(|item| item.label.width()).max();
+        let width = (labels.unwrap_or(0) + PADDING)
+            .max(self.anchor.size.width as usize)
+            .min(viewport.width as usize) as i32;
+        let height = self.items.len() as i32;
+        // Page rows start at 1, below the navigation bar
+        let below = viewport.height - (self.anchor.origin.y + self.anchor.size.height as i32) + 1;
+        let above = self.anchor.origin.y - 1;
+        let (y, height) = if height <= below || below >= above {
+            (
+                self.anchor.origin.y + self.anchor.size.height as i32,
+                height.min(below),
+            )
+        } else {
+            let height = height.min(above);
+
+            (self.anchor.origin.y - height, height)
+        };
+        let x = self.anchor.origin.x.min(viewport.width - width).max(0);
+
+        Rect::new(x, y, width as u32, height.max(0) as u32)
+    }
+
+    fn item_at(&self, origin: Point) -> Option<usize> {
+        let frame = self.frame();
+        let (x, y) = (origin.x - frame.origin.x, origin.y - frame.origin.y);
+
+        if x < 0 || y < 0 || x >= frame.size.width as i32 || y >= frame.size.height as i32 {
+            return None;
+        }
+
+        Some(self.scroll + y as usize).filter(|&index| index < self.items.len())
+    }
+
+    /// Highlight the closest selectable item in a direction.
+    fn step(&mut self, delta: isize) {
+        let mut index = match self.selected {
+            Some(index) => index as isize,
+            None if delta > 0 => -1,
+            None => self.items.len() as isize,
+        };
+        let mut found = None;
+        let mut remaining = delta.unsigned_abs();
+
+        loop {
+            index += delta.signum();
+
+            let Some(item) = usize::try_from(index).ok().and_then(|i| self.items.get(i)) else {
+                break;
+            };
+
+            if item.selectable() {
+                found = Some(index as usize);
+                remaining -= 1;
+
+                if remaining == 0 {
+                    break;
+                }
+            }
+        }
+
+        if found.is_some() {
+            self.select(found);
+        }
+    }
+
+    /// Highlight an item and scroll to reveal it.
+    fn select(&mut self, index: Option<usize>) {
+        let Some(index) = index.filter(|&index| index < self.items.len()) else {
+            return;
+        };
+        let visible = self.visible().max(1);
+
+        self.selected = Some(index);
+
+        if index < self.scroll {
+            self.scroll = index;
+        } else if index >= self.scroll + visible {
+            self.scroll = index + 1 - visible;
+        }
+    }
+}
+
+/// Shorten a label to fit a number of columns, ending with an ellipsis.
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_owned();
+    }
+
+    let mut output = String::new();
+
+    for grapheme in text.graphemes(true) {
+        if output.width() + grapheme.width() + 1 > width {
+            break;
+        }
+
+        output.push_str(grapheme);
+    }
+
+    output.push('…');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(label: &str) -> MenuItem {
+        MenuItem {
+            label: label.to_owned(),
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    fn menu(items: Vec<MenuItem>, anchor: Rect, height: u32) -> Menu {
+        let mut menu = Menu::new(anchor, items, None);
+
+        menu.set_size(Size::new(40, height));
+        menu
+    }
+
+    fn fruits() -> Vec<MenuItem> {
+        vec![
+            item("Apple"),
+            MenuItem {
+                enabled: false,
+                ..item("Banana")
+            },
+            MenuItem {
+                separator: true,
+                ..item("")
+            },
+            item("Blueberry"),
+            MenuItem {
+                checked: true,
+                ..item("Cherry")
+            },
+            item("  blackberry"),
+        ]
+    }
+
+    #[test]
+    fn skips_disabled_items() {
+        let mut menu = menu(fruits(), Rect::new(0, 1, 10, 1), 20);
+
+        // The checked item is highlighted first
+        assert_eq!(menu.selected, Some(4));
+        menu.keypress(&0x11.into());
+        assert_eq!(menu.selected, Some(3));
+        menu.keypress(&0x11.into());
+        assert_eq!(menu.selected, Some(0));
+        menu.keypress(&0x11.into());
+        assert_eq!(menu.selected, Some(0));
+        menu.keypress(&0xf6.into());
+        assert_eq!(menu.selected, Some(5));
+        menu.keypress(&0x12.into());
+        assert_eq!(menu.selected, Some(5));
+    }
+
+    #[test]
+    fn jumps_to_letters() {
+        let mut menu = menu(fruits(), Rect::new(0, 1, 10, 1), 20);
+
+        menu.keypress(&b'b'.into());
+        assert_eq!(menu.selected, Some(5));
+        // Wraps around and skips the disabled "Banana"
+        menu.keypress(&b'B'.into());
+        assert_eq!(menu.selected, Some(3));
+        menu.keypress(&b'z'.into());
+        assert_eq!(menu.selected, Some(3));
+        assert!(matches!(
+            menu.keypress(&0x0d.into()),
+            NavigationAction::CloseMenu(Some(3))
+        ));
+    }
+
+    #[test]
+    fn scrolls_to_the_selection() {
+        let items = (0..20).map(|index| item(&index.to_string())).collect();
+        let mut menu = menu(items, Rect::new(2, 3, 10, 1), 10);
+
+        // Rows 4 to 10 are below the anchor
+        assert_eq!(menu.frame(), Rect::new(2, 4, 10, 7));
+
+        menu.keypress(&0xf6.into());
+        assert_eq!((menu.selected, menu.scroll), (Some(19), 13));
+        menu.keypress(&0xf5.into());
+        assert_eq!((menu.selected, menu.scroll), (Some(0), 0));
+
+        menu.scroll_by(-100);
+        assert_eq!(menu.scroll, 13);
+        assert!(matches!(
+            menu.mouse_down(Point::new(3, 4)),
+            NavigationAction::CloseMenu(Some(13))
+        ));
+        assert!(matches!(
+            menu.mouse_down(Point::new(0, 4)),
+            NavigationAction::CloseMenu(None)
+        ));
+    }
+
+    #[test]
+    fn opens_above_near_the_bottom() {
+        let items = (0..5).map(|index| item(&index.to_string())).collect();
+        let menu = menu(items, Rect::new(38, 9, 4, 1), 10);
+
+        assert_eq!(menu.frame(), Rect::new(35, 4, 5, 5));
+    }
+
+    #[test]
+    fn truncates_labels() {
+        assert_eq!(truncate("short", 5), "short");
+        assert_eq!(truncate("longer", 5), "long…");
+    }
+}
@@ -1,20 +1,69 @@
-use std::env;
+mod classify;
 
+pub use classify::*;
+
+use std::path::PathBuf;
+
+use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
+use super::{
+    downloads::DownloadControl,
+    permissions::{PermissionAnswer, PermissionRequest},
+    text_field::TextField,
+    theme::Theme,
+};
 use crate::{
+    config::SearchConfig,
     gfx::{Color, Point, Size},
     input::Key,
+    output::TextStyle,
     utils::log,
 };
 
 pub enum NavigationAction {
     Ignore,
     Forward,
+    /// Forward a sequence of keys, ie. an unbound chord
+    Keys(Vec<Key>),
     GoTo(String),
     GoBack(),
     GoForward(),
     Refresh(),
+    HardRefresh(),
+    /// Set the zoom factor
+    Zoom(f32),
+    /// Scroll by a number of rows, positive values scroll up
+    Scroll(isize),
+    Quit(),
+    /// Ask the browser for the clickable elements of the page
+    RequestHints(),
+    /// Click at a point in browser pixels
+    Click(Point<u32>),
+    /// Search for text in the page, forward or backward,
+    /// an empty query stops the search
+    Find(String, bool),
+    /// Ask the browser for the main content of the page
+    RequestReader(),
+    /// Ask the browser to copy the text selected in the page
+    RequestCopy(),
+    /// Insert text in the focused element of the page
+    Paste(String),
+    /// Answer a JavaScript dialog, with the text typed in a prompt
+    CloseDialog(bool, String),
+    /// Pick an item of a popup menu, `None` if it was dismissed
+    CloseMenu(Option<usize>),
+    /// Answer a file chooser with the picked files, none if it was cancelled
+    CloseFileChooser(Vec<PathBuf>),
+    /// Save a download to a path, an empty path cancels it
+    SetDownloadPath(u32, String),
+    /// Pause, resume or cancel a download
+    ControlDownload(u32, DownloadControl),
+    /// Answer a permission request
+    AnswerPermission(PermissionRequest, PermissionAnswer),
+    /// Answer an authentication challenge with a username and password,
+    /// `None` if it was cancelled
+    AnswerAuth(u32, Option<(String, String)>),
 }
 
 #[derive(Debug)]
@@ -22,80 +71,86 @@ pub struct NavigationElement {
     pub text: String,
     pub background: Color,
     pub foreground: Color,
+    pub style: TextStyle,
 }
 
 pub struct Navigation {
     url: Option<String>,
     size: Size,
-    cursor: Option<usize>,
+    field: TextField,
+    focused: bool,
     can_go_back: bool,
     can_go_forward: bool,
+    search: SearchConfig,
 }
 
 impl Navigation {
-    pub fn new() -> Self {
+    pub fn new(search: SearchConfig) -> Self {
+        let mut field = TextField::new();
+
+        field.set_text("about:blank");
+
         Self {
             url: None,
             size: (0, 0).into(),
-            cursor: None,
+            field,
+            focused: false,
             can_go_back: false,
             can_go_forward: false,
+            search,
         }
     }
 
     pub fn cursor(&self) -> Option<Point> {
-        Some((11 + self.cursor? as i32, 0).into())
+        if !self.focused {
+            return None;
+        }
+
+        Some((11 + self.field.view().cursor as i32, 0).into())
+    }
+
+    pub fn focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Focus the URL bar and select its content.
+    pub fn focus(&mut self) {
+        self.focused = true;
+        self.field.select_all();
     }
 
     pub fn keypress(&mut self, key: &Key) -> NavigationAction {
-        let modifier_key = match env::consts::OS {
-            "macos" => key.modifiers.meta,
-            _ => key.modifiers.alt,
-        };
+        if !self.focused {
+            return NavigationAction::Forward;
+        }
 
-        match self.cursor {
-            None => match (modifier_key, key.char) {
-                (true, 0x14) => NavigationAction::GoBack(),
-                (true, 0x13) => NavigationAction::GoForward(),
-                _ => NavigationAction::Forward,
-            },
-            Some(cursor) => {
-                if let Some(url) = &mut self.url {
-                    // TODO: Unicode
-                    match key.char {
-                        // Return
-                        0x0d => return NavigationAction::GoTo(url.clone()),
-                        // Up
-                        0x11 => self.cursor = Some(0),
-                        // Down
-                        0x12 => self.cursor = Some(url.width()),
-                        // Right
-                        0x13 => self.cursor = Some((cursor + 1).min(url.width())),
-                        // Left
-                        0x14 => self.cursor = Some(if cursor > 0 { cursor - 1 } else { 0 }),
-                        // Backspace
-                        0x7f => {
-                            if cursor > 0 {
-                                url.remove(cursor - 1);
-
-                                self.cursor = Some(cursor - 1);
-                            }
-                        }
-                        key => {
-                            url.insert(cursor, key as char);
-
-                            self.cursor = Some((cursor + 1).min(url.width()))
-                        }
-                    }
-
-                    NavigationAction::Ignore
-                } else {
-                    NavigationAction::Forward
+        match key.char {
+            // Escape
+            0x1b => {
+                self.blur();
+
+                NavigationAction::Ignore
+            }
+            // Return
+            0x0d => {
+                match UrlInput::classify(self.field.text(), &self.search).into_url(&self.search) {
+                    Some(url) => NavigationAction::GoTo(url),
+                    None => NavigationAction::Ignore,
                 }
             }
+            _ => {
+                self.field.keypress(key);
+
+                NavigationAction::Ignore
+            }
         }
     }
 
+    /// Insert pasted text at the cursor, on a single line.
+    pub fn paste(&mut self, text: &str) {
+        self.field.insert(&single_line(text))
+    }
+
     pub fn display_url(&self) -> &str {
         match &self.url {
             None => "about:blank",
@@ -103,13 +158,9 @@ impl Navigation {
         }
     }
 
-    pub fn url_size(&self) -> usize {
-        self.display_url().width()
-    }
-
     pub fn mouse_up(&mut self, origin: Point) -> NavigationAction {
         if origin.y != 0 {
-            self.cursor = None;
+            self.blur();
 
             NavigationAction::Forward
         } else {
@@ -118,25 +169,28 @@ impl Navigation {
     }
     pub fn mouse_down(&mut self, origin: Point) -> NavigationAction {
         if origin.y != 0 {
-            self.cursor = None;
+            self.blur();
 
             return NavigationAction::Forward;
         }
 
-        self.cursor = None;
-
         return match origin.x {
             0..=2 => NavigationAction::GoBack(),
             3..=5 => NavigationAction::GoForward(),
             6..=8 => NavigationAction::Refresh(),
             11.. => {
-                self.cursor = Some(self.url_size().min(origin.x as usize - 11));
+                self.focused = true;
+                self.field.click(origin.x as usize - 11);
+
+                log::debug!("setting cursor to {:?}", self.cursor());
 
-                log::debug!("setting cursor to {:?}", self.cursor);
+                NavigationAction::Ignore
+            }
+            _ => {
+                self.blur();
 
                 NavigationAction::Ignore
             }
-            _ => NavigationAction::Ignore,
         };
     }
     pub fn mouse_move(&mut self, _origin: Point) -> NavigationAction {
@@ -144,12 +198,9 @@ impl Navigation {
     }
 
     pub fn push(&mut self, url: &str, can_go_back: bool, can_go_forward: bool) {
-        if match (self.cursor, &self.url) {
-            (None, _) => false,
-            (_, None) => true,
-            (_, Some(current)) => current != url,
-        } {
-            self.cursor = Some(url.len())
+        // Keep what the user is typing unless we actually navigated somewhere else
+        if !self.focused || self.url.as_deref() != Some(url) {
+            self.field.set_text(url)
         }
 
         self.url = Some(url.to_owned());
@@ -158,59 +209,102 @@ impl Navigation {
     }
 
     pub fn set_size(&mut self, size: Size) {
-        self.size = size
+        self.size = size;
+        self.field
+            .set_width((size.width as usize).saturating_sub(13));
+    }
+
+    /// Drop the focus and any pending edit of the URL bar.
+    fn blur(&mut self) {
+        if self.focused {
+            let url = self.display_url().to_owned();
+
+            self.focused = false;
+            self.field.set_text(&url);
+        }
     }
 
-    pub fn render_btn(&self, icon: &str, enabled: bool) -> [NavigationElement; 3] {
-        let background = Color::splat(255);
-        let foreground = Color::splat(0);
+    pub fn render_btn(&self, icon: &str, enabled: bool, theme: &Theme) -> [NavigationElement; 3] {
+        let background = theme.background;
+        let foreground = theme.foreground;
 
         [
             NavigationElement {
                 text: "[".to_owned(),
                 background,
                 foreground,
+                style: TextStyle::default(),
             },
             NavigationElement {
                 text: icon.to_owned(),
                 background,
-                foreground: if enabled {
-                    foreground
-                } else {
-                    Color::splat(200)
-                },
+                foreground: if enabled { foreground } else { theme.disabled },
+                style: TextStyle::default(),
             },
             NavigationElement {
                 text: "]".to_owned(),
                 background,
                 foreground,
+                style: TextStyle::default(),
             },
         ]
     }
 
-    pub fn render(&self, size: Size) -> Vec<(Point, NavigationElement)> {
+    pub fn render(&self, size: Size, theme: &Theme) -> Vec<(Point, NavigationElement)> {
         let ui_elements = 13;
         let space = if size.width >= ui_elements {
             (size.width - ui_elements) as usize
         } else {
             0
         };
-        let url: String = self.display_url().chars().take(space).collect();
-        let width = url.width();
-        let padded = format!(" {}{} ", url, " ".repeat(space - width));
+        let view = self.field.view();
+        let width = view.before.width() + view.selected.width() + view.after.width();
+        let [open, _, close] = self.render_btn("", true, theme);
+        let background = open.background;
+        let foreground = open.foreground;
         let mut elements = Vec::new();
         let mut point = Point::splat(0);
 
         for list in [
-            self.render_btn("\u{276e}", self.can_go_back),
-            self.render_btn("\u{276f}", self.can_go_forward),
-            self.render_btn("↻", true),
-            self.render_btn(&padded, true),
+            self.render_btn(theme.back_glyph(), self.can_go_back, theme),
+            self.render_btn(theme.forward_glyph(), self.can_go_forward, theme),
+            self.render_btn(theme.reload_glyph(), true, theme),
+            [
+                open,
+                NavigationElement {
+                    text: format!(" {}", view.before),
+                    background,
+                    foreground,
+                    style: TextStyle::default(),
+                },
+                // Selection is displayed with inverted colors
+                NavigationElement {
+                    text: view.selected,
+                    background: foreground,
+                    foreground: background,
+                    style: TextStyle::default(),
+                },
+            ],
+            [
+                NavigationElement {
+                    text: format!("{}{} ", view.after, " ".repeat(space.saturating_sub(width))),
+                    background,
+                    foreground,
+                    style: TextStyle::default(),
+                },
+                close,
+                NavigationElement {
+                    text: String::new(),
+                    background,
+                    foreground,
+                    style: TextStyle::default(),
+                },
+            ],
         ] {
             for element in list {
                 let width = element.text.width() as i32;
 
-                elements.push((point.clone(), element));
+                elements.push((point, element));
 
                 point = point + (width, 0);
             }
@@ -219,3 +313,29 @@ impl Navigation {
         elements
     }
 }
+
+/// Replace line breaks and other control characters with spaces,
+/// text fields can only contain a single line.
+pub fn single_line(text: &str) -> String {
+    text.trim()
+        .chars()
+        .map(|char| if char.is_control() { ' ' } else { char })
+        .collect()
+}
+
+/// Clip or pad text to fill a row.
+pub fn fit(text: &str, width: usize) -> String {
+    let mut output = String::new();
+
+    for grapheme in text.graphemes(true) {
+        if output.width() + grapheme.width() > width {
+            return output;
+        }
+
+        output.push_str(grapheme);
+    }
+
+    let padding = width.saturating_sub(output.width());
+
+    output + &" ".repeat(padding)
+}
@@ -0,0 +1,356 @@
+use std::{env, path::PathBuf};
+
+use crate::config::SearchConfig;
+
+/// What the user typed in the URL bar.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UrlInput {
+    /// Nothing to navigate to
+    Empty,
+    /// An URL with an explicit scheme, used verbatim
+    Url(String),
+    /// Something that looks like a host name, ie. `github.com/fathyb`
+    Host { secure: bool, url: String },
+    /// A local file path, ie. `~/index.html`
+    File(PathBuf),
+    /// A keyword shortcut, ie. `gh carbonyl`
+    Keyword { template: String, query: String },
+    /// Anything else goes to the search engine
+    Search(String),
+}
+
+/// Schemes that are not followed by `//` but should still be used verbatim.
+const OPAQUE_SCHEMES: [&str; 7] = [
+    "about",
+    "blob",
+    "data",
+    "file",
+    "javascript",
+    "mailto",
+    "view-source",
+];
+
+/// File extensions that are not top-level domains, `index.html` is a file name
+/// rather than a host.
+const FILE_EXTENSIONS: [&str; 21] = [
+    "bak", "c", "cpp", "css", "csv", "gif", "gz", "h", "htm", "html", "jpeg", "jpg", "js", "json",
+    "log", "pdf", "png", "svg", "tar", "txt", "yaml",
+];
+
+impl UrlInput {
+    pub fn classify(input: &str, search: &SearchConfig) -> UrlInput {
+        let input = input.trim();
+
+        if input.is_empty() {
+            return UrlInput::Empty;
+        }
+
+        if let Some((keyword, query)) = input.split_once(char::is_whitespace) {
+            let query = query.trim();
+
+            for (name, template) in &search.keywords {
+                if name == keyword && !query.is_empty() {
+                    return UrlInput::Keyword {
+                        template: template.clone(),
+                        query: query.to_owned(),
+                    };
+                }
+            }
+        }
+
+        if let Some(path) = file_path(input) {
+            return UrlInput::File(path);
+        }
+
+        if has_scheme(input) {
+            return UrlInput::Url(input.to_owned());
+        }
+
+        if let Some(local) = host_kind(input) {
+            return UrlInput::Host {
+                secure: !local,
+                url: input.to_owned(),
+            };
+        }
+
+        UrlInput::Search(input.to_owned())
+    }
+
+    /// Turn the input into an URL Chromium can navigate to.
+    pub fn into_url(self, search: &SearchConfig) -> Option<String> {
+        Some(match self {
+            UrlInput::Empty => return None,
+            UrlInput::Url(url) => url,
+            UrlInput::Host { secure: true, url } => format!("https://{url}"),
+            UrlInput::Host { secure: false, url } => format!("http://{url}"),
+            UrlInput::File(path) => format!("file://{}", encode(&path.to_string_lossy(), true)),
+            UrlInput::Keyword { template, query } => expand(&template, &query),
+            UrlInput::Search(query) => expand(&search.engine, &query),
+        })
+    }
+}
+
+/// Resolve local paths: absolute, relative to the working directory or the home directory.
+fn file_path(input: &str) -> Option<PathBuf> {
+    if input.starts_with('/') {
+        Some(PathBuf::from(input))
+    } else if let Some(rest) = input.strip_prefix("~/") {
+        Some(PathBuf::from(env::var_os("HOME")?).join(rest))
+    } else if input.starts_with("./") || input.starts_with("../") {
+        Some(env::current_dir().ok()?.join(input))
+    } else {
+        None
+    }
+}
+
+fn has_scheme(input: &str) -> bool {
+    let Some((scheme, rest)) = input.split_once(':') else {
+        return false;
+    };
+    let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+
+    valid
+        && (rest.starts_with("//")
+            || OPAQUE_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()))
+}
+
+/// Check if the input starts with a host name, optionally followed by a port and a path.
+/// Returns `Some(true)` for local hosts (`localhost`, `.local` mDNS names, IP addresses)
+/// which are usually served over plain HTTP, `Some(false)` for domain names.
+fn host_kind(input: &str) -> Option<bool> {
+    if input.contains(char::is_whitespace) {
+        return None;
+    }
+
+    let end = input.find(['/', '?', '#']).unwrap_or(input.len());
+    let authority = &input[..end];
+    let authority = match authority.rsplit_once('@') {
+        // `user@example.org` is more likely an email address than a login
+        Some(_) if end == input.len() => return None,
+        Some((_, host)) => host,
+        None => authority,
+    };
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        // IPv6 literal
+        let (host, rest) = rest.split_once(']')?;
+
+        if host.is_empty() || !host.chars().all(|c| c.is_ascii_hexdigit() || c == ':') {
+            return None;
+        }
+
+        return match rest.strip_prefix(':') {
+            None if rest.is_empty() => Some(true),
+            Some(port) if is_port(port) => Some(true),
+            _ => None,
+        };
+    } else {
+        match authority.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+
+    if let Some(port) = port {
+        if !is_port(port) {
+            return None;
+        }
+    }
+
+    let host = host.strip_suffix('.').unwrap_or(host).to_ascii_lowercase();
+
+    if host == "localhost"
+        || host.ends_with(".localhost")
+        || host.ends_with(".local")
+        || is_ipv4(&host)
+    {
+        return Some(true);
+    }
+
+    let labels: Vec<&str> = host.split('.').collect();
+    let valid_label = |label: &&str| {
+        !label.is_empty()
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+    };
+
+    match labels.last() {
+        // A file name, unless something else says it's an URL
+        Some(tld) if port.is_none() && end == input.len() && FILE_EXTENSIONS.contains(tld) => None,
+        Some(tld)
+            if labels.len() > 1
+                && labels.iter().all(valid_label)
+                && tld.chars().count() >= 2
+                && (tld.chars().all(char::is_alphabetic) || tld.starts_with("xn--")) =>
+        {
+            Some(false)
+        }
+        // A bare word with a port, ie. `devbox:8080`
+        _ if port.is_some() && labels.len() == 1 && labels.iter().all(valid_label) => Some(true),
+        _ => None,
+    }
+}
+
+fn is_port(port: &str) -> bool {
+    !port.is_empty() && port.len() <= 5 && port.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_ipv4(host: &str) -> bool {
+    let parts: Vec<&str> = host.split('.').collect();
+
+    parts.len() == 4 && parts.iter().all(|part| part.parse::<u8>().is_ok())
+}
+
+/// Replace `%s` in a template with the percent-encoded query.
+fn expand(template: &str, query: &str) -> String {
+    let query = encode(query, false);
+
+    if template.contains("%s") {
+        template.replace("%s", &query)
+    } else {
+        format!("{template}{query}")
+    }
+}
+
+/// Percent-encode a string, keeping slashes for paths or encoding spaces as `+` for queries.
+fn encode(input: &str, path: bool) -> String {
+    let mut output = String::with_capacity(input.len());
+
+    for &byte in input.as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                output.push(byte as char)
+            }
+            b'/' if path => output.push('/'),
+            b' ' if !path => output.push('+'),
+            byte => output.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(input: &str) -> UrlInput {
+        UrlInput::classify(input, &SearchConfig::default())
+    }
+
+    fn url(input: &str) -> Option<String> {
+        classify(input).into_url(&SearchConfig::default())
+    }
+
+    #[test]
+    fn hosts() {
+        assert_eq!(
+            url("github.com/fathyb"),
+            Some("https://github.com/fathyb".into())
+        );
+        assert_eq!(url("localhost:3000"), Some("http://localhost:3000".into()));
+        assert_eq!(
+            url("app.localhost/x"),
+            Some("http://app.localhost/x".into())
+        );
+        assert_eq!(url("foo.local"), Some("http://foo.local".into()));
+        assert_eq!(
+            url("192.168.1.1:80/a"),
+            Some("http://192.168.1.1:80/a".into())
+        );
+        assert_eq!(url("devbox:8080"), Some("http://devbox:8080".into()));
+        assert_eq!(
+            url("user@example.org/x"),
+            Some("https://user@example.org/x".into())
+        );
+        assert_eq!(url("docs.rs"), Some("https://docs.rs".into()));
+        assert_eq!(
+            url("example.com/index.html"),
+            Some("https://example.com/index.html".into())
+        );
+    }
+
+    #[test]
+    fn ipv6() {
+        assert_eq!(url("[::1]"), Some("http://[::1]".into()));
+        assert_eq!(url("[::1]:8080/x"), Some("http://[::1]:8080/x".into()));
+        assert_eq!(
+            url("[fe80::1%eth0]"),
+            Some("https://duckduckgo.com/?q=%5Bfe80%3A%3A1%25eth0%5D".into())
+        );
+        assert_eq!(
+            classify("[::1]:port"),
+            UrlInput::Search("[::1]:port".into())
+        );
+    }
+
+    #[test]
+    fn schemes() {
+        assert_eq!(classify("https://a b"), UrlInput::Url("https://a b".into()));
+        assert_eq!(classify("about:blank"), UrlInput::Url("about:blank".into()));
+        assert_eq!(
+            classify("data:text/plain,a"),
+            UrlInput::Url("data:text/plain,a".into())
+        );
+        assert_eq!(classify("foo:bar"), UrlInput::Search("foo:bar".into()));
+    }
+
+    #[test]
+    fn searches() {
+        assert_eq!(classify(""), UrlInput::Empty);
+        assert_eq!(url("  "), None);
+        assert_eq!(url("a b"), Some("https://duckduckgo.com/?q=a+b".into()));
+        assert_eq!(url("rust"), Some("https://duckduckgo.com/?q=rust".into()));
+        assert_eq!(url("1.5"), Some("https://duckduckgo.com/?q=1.5".into()));
+        assert_eq!(
+            classify("index.html"),
+            UrlInput::Search("index.html".into())
+        );
+        assert_eq!(
+            classify("user@example.org"),
+            UrlInput::Search("user@example.org".into())
+        );
+        assert_eq!(
+            url("user@example.org"),
+            Some("https://duckduckgo.com/?q=user%40example.org".into())
+        );
+        assert_eq!(
+            url("c++ & co"),
+            Some("https://duckduckgo.com/?q=c%2B%2B+%26+co".into())
+        );
+    }
+
+    #[test]
+    fn keywords() {
+        let search = SearchConfig {
+            keywords: vec![("gh".into(), "https://github.com/search?q=%s".into())],
+            ..Default::default()
+        };
+        let url = |input| UrlInput::classify(input, &search).into_url(&search);
+
+        assert_eq!(
+            url("gh car bonyl"),
+            Some("https://github.com/search?q=car+bonyl".into())
+        );
+        assert_eq!(url("gh"), Some("https://duckduckgo.com/?q=gh".into()));
+    }
+
+    #[test]
+    fn files() {
+        assert_eq!(url("/tmp/a b.html"), Some("file:///tmp/a%20b.html".into()));
+        assert_eq!(
+            classify("../x"),
+            UrlInput::File(env::current_dir().unwrap().join("../x"))
+        );
+
+        if let Some(home) = env::var_os("HOME") {
+            assert_eq!(
+                classify("~/x"),
+                UrlInput::File(PathBuf::from(home).join("x"))
+            );
+        }
+    }
+}
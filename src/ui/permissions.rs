@@ -0,0 +1,356 @@
+use std::collections::VecDeque;
+
+use super::{
+    dialog::{frame, wrap},
+    navigation::{NavigationAction, NavigationElement},
+    theme::Theme,
+};
+use crate::{
+    config::Permission,
+    gfx::{Point, Size},
+    input::Key,
+    output::TextStyle,
+};
+
+/// Maximum number of columns of the prompt.
+const MAX_WIDTH: usize = 60;
+/// Room for the three buttons and the borders.
+const MIN_WIDTH: usize = 40;
+const BUTTONS: [(&str, PermissionAnswer); 3] = [
+    ("[ Allow once ]", PermissionAnswer::AllowOnce),
+    ("[ Always ]", PermissionAnswer::AllowAlways),
+    ("[ Deny ]", PermissionAnswer::Deny),
+];
+const CLOSE: &str = "[ Close ]";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PermissionAnswer {
+    AllowOnce,
+    /// Allow and save the decision for the origin
+    AllowAlways,
+    /// Deny for the rest of the session
+    Deny,
+}
+
+#[derive(Clone, Debug)]
+pub struct PermissionRequest {
+    /// Identifier given by the browser
+    pub id: u32,
+    pub origin: String,
+    pub permission: Permission,
+}
+
+/// Position of the parts of the prompt on screen.
+struct Layout {
+    origin: Point,
+    width: usize,
+    height: usize,
+    lines: Vec<String>,
+    buttons: Vec<(Point, &'static str)>,
+}
+
+/// Modal box asking whether a site can use a permission, also used
+/// to list the decisions taken for the current site on demand.
+#[derive(Default)]
+pub struct PermissionPrompt {
+    /// Pending requests, the first one is displayed
+    requests: VecDeque<PermissionRequest>,
+    /// Origin and decisions listed when no request is pending
+    summary: Option<(String, Vec<(Permission, bool)>)>,
+    /// Index of the focused button
+    focus: usize,
+    size: Size,
+}
+
+impl PermissionPrompt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.requests.is_empty() || self.summary.is_some()
+    }
+
+    /// Ask for a permission, answered with `NavigationAction::AnswerPermission`.
+    pub fn request(&mut self, request: PermissionRequest) {
+        if self.requests.is_empty() {
+            self.reset_focus();
+        }
+
+        self.summary = None;
+        self.requests.push_back(request);
+    }
+
+    /// List the decisions taken for an origin.
+    pub fn show(&mut self, origin: &str, decisions: Vec<(Permission, bool)>) {
+        if self.requests.is_empty() {
+            self.summary = Some((origin.to_owned(), decisions));
+            self.focus = 0;
+        }
+    }
+
+    pub fn set_size(&mut self, size: Size) {
+        self.size = size;
+    }
+
+    pub fn keypress(&mut self, key: &Key) -> NavigationAction {
+        let count = self.layout().buttons.len();
+
+        match key.char {
+            // Escape denies the request, or closes the list
+            0x1b | b'n' => self.answer(BUTTONS.len() - 1),
+            // Return
+            0x0d => self.answer(self.focus),
+            b'y' if !self.requests.is_empty() => self.answer(0),
+            b'a' if !self.requests.is_empty() => self.answer(1),
+            b'q' if self.requests.is_empty() => self.answer(0),
+            // Tab and Right move to the next button, Shift+Tab and Left to the previous one
+            0x09 if key.modifiers.shift => self.step(count - 1),
+            0x09 | 0x13 => self.step(1),
+            0x14 => self.step(count - 1),
+            _ => NavigationAction::Ignore,
+        }
+    }
+
+    pub fn mouse_down(&mut self, origin: Point) -> NavigationAction {
+        let layout = self.layout();
+        let hit = layout.buttons.iter().position(|(point, text)| {
+            origin.y == point.y && origin.x >= point.x && origin.x < point.x + text.len() as i32
+        });
+
+        match hit {
+            Some(index) => self.answer(index),
+            None => NavigationAction::Ignore,
+        }
+    }
+
+    pub fn render(&self, theme: &Theme) -> Vec<(Point, NavigationElement)> {
+        if !self.is_open() {
+            return Vec::new();
+        }
+
+        let layout = self.layout();
+        let (background, foreground) = (theme.background, theme.foreground);
+        let element = |text: String, background, foreground| NavigationElement {
+            text,
+            background,
+            foreground,
+            style: TextStyle::default(),
+        };
+        let mut elements = frame(layout.origin, layout.width, layout.height, theme);
+
+        for (index, line) in layout.lines.iter().enumerate() {
+            elements.push((
+                layout.origin + (2, 2 + index as i32),
+                element(line.clone(), background, foreground),
+            ));
+        }
+
+        for (index, &(point, text)) in layout.buttons.iter().enumerate() {
+            // The focused button is displayed with inverted colors
+            let button = match index == self.focus {
+                true => element(text.to_owned(), foreground, background),
+                false => element(text.to_owned(), background, foreground),
+            };
+
+            elements.push((point, button));
+        }
+
+        elements
+    }
+
+    /// Press a button, closing the list or answering the current request.
+    fn answer(&mut self, index: usize) -> NavigationAction {
+        let Some(request) = self.requests.pop_front() else {
+            self.summary = None;
+
+            return NavigationAction::Ignore;
+        };
+
+        self.reset_focus();
+
+        NavigationAction::AnswerPermission(request, BUTTONS[index.min(BUTTONS.len() - 1)].1)
+    }
+
+    fn step(&mut self, delta: usize) -> NavigationAction {
+        self.focus = (self.focus + delta) % self.layout().buttons.len();
+
+        NavigationAction::Ignore
+    }
+
+    /// Focus the deny button, so a stray Return doesn't allow anything.
+    fn reset_focus(&mut self) {
+        self.focus = BUTTONS.len() - 1;
+    }
+
+    /// Center the prompt over the page, wrapping the message.
+    fn layout(&self) -> Layout {
+        let width = (self.size.width as usize)
+            .saturating_sub(4)
+            .clamp(MIN_WIDTH, MAX_WIDTH);
+        let (mut lines, labels) = match (self.requests.front(), &self.summary) {
+            (Some(request), _) => (
+                wrap(
+                    &format!(
+                        "{} wants to {}.",
+                        request.origin,
+                        request.permission.description()
+                    ),
+                    width - 4,
+                ),
+                BUTTONS.iter().map(|(text, _)| *text).collect(),
+            ),
+            (None, Some((origin, decisions))) => {
+                let mut lines = wrap(&format!("Permissions of {origin}"), width - 4);
+
+                lines.push(String::new());
+
+                if decisions.is_empty() {
+                    lines.push("Nothing was allowed or denied.".to_owned());
+                }
+
+                for (permission, allowed) in decisions {
+                    let decision = if *allowed { "allowed" } else { "denied" };
+
+                    lines.push(format!("{:<16}{decision}", permission.name()));
+                }
+
+                (lines, vec![CLOSE])
+            }
+            (None, None) => (Vec::new(), vec![CLOSE]),
+        };
+        // Keep the buttons visible if the message is too long
+        let max_lines = (self.size.height as usize).saturating_sub(6).max(1);
+
+        if lines.len() > max_lines {
+            lines.truncate(max_lines);
+
+            if let Some(line) = lines.last_mut() {
+                line.push('…');
+            }
+        }
+
+        let height = lines.len() + 6;
+        let origin = Point::new(
+            (self.size.width as i32 - width as i32).max(0) / 2,
+            1 + (self.size.height as i32 - height as i32).max(0) / 2,
+        );
+        let y = origin.y + height as i32 - 3;
+        // Buttons are aligned to the right, two columns apart
+        let mut x = origin.x + width as i32;
+        let mut buttons = labels
+            .into_iter()
+            .rev()
+            .map(|text: &'static str| {
+                x -= text.len() as i32 + 2;
+
+                (Point::new(x, y), text)
+            })
+            .collect::<Vec<_>>();
+
+        buttons.reverse();
+
+        Layout {
+            origin,
+            width,
+            height,
+            lines,
+            buttons,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(id: u32, permission: Permission) -> PermissionRequest {
+        PermissionRequest {
+            id,
+            origin: "https://example.com".to_owned(),
+            permission,
+        }
+    }
+
+    fn answer(action: NavigationAction) -> Option<(u32, PermissionAnswer)> {
+        match action {
+            NavigationAction::AnswerPermission(request, answer) => Some((request.id, answer)),
+            _ => None,
+        }
+    }
+
+    fn prompt() -> PermissionPrompt {
+        let mut prompt = PermissionPrompt::new();
+
+        prompt.set_size(Size::new(80, 24));
+        prompt
+    }
+
+    #[test]
+    fn answers_requests_in_order() {
+        let mut prompt = prompt();
+
+        prompt.request(request(1, Permission::Camera));
+        prompt.request(request(2, Permission::Microphone));
+
+        // Return denies unless another button is focused
+        assert_eq!(
+            answer(prompt.keypress(&0x0d.into())),
+            Some((1, PermissionAnswer::Deny))
+        );
+        prompt.keypress(&0x09.into());
+        assert_eq!(
+            answer(prompt.keypress(&0x0d.into())),
+            Some((2, PermissionAnswer::AllowOnce))
+        );
+        assert!(!prompt.is_open());
+    }
+
+    #[test]
+    fn answers_with_shortcuts_and_clicks() {
+        let mut prompt = prompt();
+
+        prompt.request(request(1, Permission::Camera));
+        prompt.request(request(2, Permission::Camera));
+        prompt.request(request(3, Permission::Camera));
+
+        assert_eq!(
+            answer(prompt.keypress(&b'a'.into())),
+            Some((1, PermissionAnswer::AllowAlways))
+        );
+        assert_eq!(
+            answer(prompt.keypress(&b'y'.into())),
+            Some((2, PermissionAnswer::AllowOnce))
+        );
+
+        let (point, _) = prompt.layout().buttons[1];
+
+        assert_eq!(
+            answer(prompt.mouse_down(point)),
+            Some((3, PermissionAnswer::AllowAlways))
+        );
+    }
+
+    #[test]
+    fn lists_decisions() {
+        let mut prompt = prompt();
+
+        prompt.show("https://example.com", vec![(Permission::Camera, true)]);
+
+        let layout = prompt.layout();
+
+        assert_eq!(layout.buttons.len(), 1);
+        assert_eq!(layout.lines[2], "camera          allowed");
+
+        // Requests replace the list
+        prompt.request(request(1, Permission::Camera));
+        prompt.show("https://example.com", Vec::new());
+        assert_eq!(prompt.layout().buttons.len(), 3);
+        prompt.keypress(&0x1b.into());
+
+        prompt.show("https://example.com", Vec::new());
+        assert_eq!(prompt.layout().lines[2], "Nothing was allowed or denied.");
+        assert!(answer(prompt.keypress(&b'q'.into())).is_none());
+        assert!(!prompt.is_open());
+    }
+}
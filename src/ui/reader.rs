@@ -0,0 +1,581 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::{
+    navigation::{NavigationAction, NavigationElement},
+    theme::Theme,
+};
+use crate::{
+    gfx::{Color, Point, Size},
+    input::Key,
+    output::TextStyle,
+};
+
+/// Maximum number of columns used for text, long lines are hard to read.
+const MAX_WIDTH: usize = 80;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlockKind {
+    Paragraph,
+    /// Heading with its level, from 1 to 6
+    Heading(u8),
+    /// Bullet list item with its nesting depth
+    ListItem(u8),
+    /// Numbered list item with its nesting depth
+    OrderedListItem(u8),
+    /// Preformatted text, never wrapped
+    Code,
+    Quote,
+}
+
+/// Inline text with its emphasis.
+#[derive(Clone, Debug, Default)]
+pub struct Span {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+    pub link: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Block {
+    pub kind: BlockKind,
+    pub spans: Vec<Span>,
+}
+
+/// How a piece of text is displayed.
+#[derive(Clone, Debug, PartialEq)]
+struct Format {
+    style: TextStyle,
+    foreground: Color,
+    background: Color,
+    link: Option<String>,
+}
+
+/// Text laid out at a column of a line.
+#[derive(Clone, Debug)]
+struct Run {
+    column: usize,
+    text: String,
+    format: Format,
+}
+
+/// Article content extracted by the browser, rendered as reflowed text
+/// instead of the page layout.
+pub struct Reader {
+    title: String,
+    blocks: Vec<Block>,
+    theme: Theme,
+    lines: Vec<Vec<Run>>,
+    /// Terminal size used for the current layout
+    size: Size,
+    /// Index of the first visible line
+    scroll: usize,
+    done: bool,
+}
+
+impl Reader {
+    pub fn new(title: &str, blocks: Vec<Block>, theme: &Theme) -> Self {
+        Self {
+            title: title.to_owned(),
+            blocks,
+            theme: theme.clone(),
+            lines: Vec::new(),
+            size: Size::splat(0),
+            scroll: 0,
+            done: false,
+        }
+    }
+
+    /// Whether reader mode should be exited.
+    pub fn done(&self) -> bool {
+        self.done
+    }
+
+    pub fn set_size(&mut self, size: Size) {
+        if size.width != self.size.width {
+            self.layout(size.width as usize);
+        }
+
+        self.size = size;
+        self.scroll_by(0);
+    }
+
+    /// Scroll by a number of rows, positive values scroll up.
+    pub fn scroll_by(&mut self, rows: isize) {
+        let max = self.lines.len().saturating_sub(self.size.height as usize);
+
+        self.scroll = self.scroll.saturating_add_signed(-rows).min(max);
+    }
+
+    /// Handle a navigation key, returns `false` if the key isn't used.
+    pub fn keypress(&mut self, key: &Key) -> bool {
+        let page = (self.size.height as isize - 2).max(1);
+
+        match key.char {
+            // Up
+            0x11 | b'k' => self.scroll_by(1),
+            // Down
+            0x12 | b'j' => self.scroll_by(-1),
+            b' ' if key.modifiers.shift => self.scroll_by(page),
+            b' ' => self.scroll_by(-page),
+            b'b' => self.scroll_by(page),
+            // Home
+            0xf5 | b'g' => self.scroll = 0,
+            // End
+            0xf6 | b'G' => self.scroll_by(-isize::MAX),
+            // Escape
+            0x1b | b'q' => self.done = true,
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Follow links when clicked.
+    pub fn mouse_down(&mut self, origin: Point) -> NavigationAction {
+        let line = (origin.y - 1) as usize + self.scroll;
+        let column = origin.x as usize;
+        let link = self.lines.get(line).and_then(|runs| {
+            runs.iter().find_map(|run| match run.format.link {
+                Some(ref link)
+                    if column >= run.column && column < run.column + run.text.width() =>
+                {
+                    Some(link.clone())
+                }
+                _ => None,
+            })
+        });
+
+        match link {
+            Some(link) => NavigationAction::GoTo(link),
+            None => NavigationAction::Ignore,
+        }
+    }
+
+    pub fn render(&self) -> Vec<(Point, NavigationElement)> {
+        let width = self.size.width as usize;
+        let mut elements = Vec::new();
+
+        for row in 0..self.size.height as usize {
+            let y = row as i32 + 1;
+
+            elements.push((
+                Point::new(0, y),
+                NavigationElement {
+                    text: " ".repeat(width),
+                    background: self.theme.background,
+                    foreground: self.theme.foreground,
+                    style: TextStyle::default(),
+                },
+            ));
+
+            for run in self.lines.get(self.scroll + row).into_iter().flatten() {
+                elements.push((
+                    Point::new(run.column as i32, y),
+                    NavigationElement {
+                        text: run.text.clone(),
+                        background: run.format.background,
+                        foreground: run.format.foreground,
+                        style: run.format.style,
+                    },
+                ));
+            }
+        }
+
+        elements
+    }
+
+    /// Word-wrap the content for a terminal width.
+    fn layout(&mut self, width: usize) {
+        let text_width = width.saturating_sub(4).clamp(1, MAX_WIDTH);
+        let mut layout = Layout {
+            lines: Vec::new(),
+            line: Vec::new(),
+            margin: (width.saturating_sub(text_width)) / 2,
+            column: 0,
+            width: text_width,
+            theme: &self.theme,
+        };
+        let title = Block {
+            kind: BlockKind::Heading(1),
+            spans: vec![Span {
+                text: self.title.clone(),
+                ..Default::default()
+            }],
+        };
+        // Item numbers of the ordered lists, by depth
+        let mut counters = Vec::<usize>::new();
+        let mut previous = None;
+
+        for block in std::iter::once(&title).chain(self.blocks.iter()) {
+            if block.spans.iter().all(|span| span.text.trim().is_empty()) {
+                continue;
+            }
+
+            let (first, next) = match block.kind {
+                BlockKind::ListItem(depth) => {
+                    let indent = "  ".repeat(depth as usize);
+
+                    counters.truncate(depth as usize);
+
+                    (format!("{indent}• "), format!("{indent}  "))
+                }
+                BlockKind::OrderedListItem(depth) => {
+                    let (indent, depth) = ("   ".repeat(depth as usize), depth as usize);
+
+                    counters.resize(depth + 1, 0);
+                    counters[depth] += 1;
+
+                    let number = format!("{}. ", counters[depth]);
+
+                    (
+                        format!("{indent}{number}"),
+                        indent + &" ".repeat(number.len()),
+                    )
+                }
+                BlockKind::Quote => ("│ ".to_owned(), "│ ".to_owned()),
+                _ => {
+                    counters.clear();
+
+                    (String::new(), String::new())
+                }
+            };
+            let list = |kind| {
+                matches!(
+                    kind,
+                    Some(BlockKind::ListItem(_) | BlockKind::OrderedListItem(_))
+                )
+            };
+
+            // Separate blocks with an empty line, except for items of the same list
+            if previous.is_some() && !(list(previous) && list(Some(block.kind))) {
+                layout.lines.push(Vec::new());
+            }
+
+            previous = Some(block.kind);
+
+            if block.kind == BlockKind::Code {
+                layout.code(block);
+            } else {
+                layout.wrap(block, &first, &next);
+            }
+        }
+
+        self.lines = layout.lines;
+    }
+}
+
+/// Lines being laid out.
+struct Layout<'a> {
+    lines: Vec<Vec<Run>>,
+    line: Vec<Run>,
+    /// Column of the text area
+    margin: usize,
+    /// Current column in the text area
+    column: usize,
+    width: usize,
+    theme: &'a Theme,
+}
+
+impl Layout<'_> {
+    fn push(&mut self, text: &str, format: &Format) {
+        if text.is_empty() {
+            return;
+        }
+
+        match self.line.last_mut() {
+            Some(run) if &run.format == format => run.text.push_str(text),
+            _ => self.line.push(Run {
+                column: self.margin + self.column,
+                text: text.to_owned(),
+                format: format.clone(),
+            }),
+        }
+
+        self.column += text.width();
+    }
+
+    fn newline(&mut self) {
+        self.lines.push(std::mem::take(&mut self.line));
+        self.column = 0;
+    }
+
+    /// Preformatted text, clipped to the text area.
+    fn code(&mut self, block: &Block) {
+        let text = block
+            .spans
+            .iter()
+            .map(|span| span.text.as_str())
+            .collect::<String>();
+        let format = Format {
+            style: TextStyle::default(),
+            foreground: self.theme.foreground,
+            background: self.theme.code,
+            link: None,
+        };
+
+        for line in text.trim_matches('\n').lines() {
+            let mut clipped = String::from(" ");
+
+            for grapheme in line.replace('\t', "    ").graphemes(true) {
+                if clipped.width() + grapheme.width() >= self.width {
+                    break;
+                }
+
+                clipped.push_str(grapheme);
+            }
+
+            let padding = self.width.saturating_sub(clipped.width());
+
+            self.push(&clipped, &format);
+            self.push(&" ".repeat(padding), &format);
+            self.newline();
+        }
+    }
+
+    fn wrap(&mut self, block: &Block, first: &str, next: &str) {
+        let prefix = Format {
+            style: TextStyle::default(),
+            foreground: self.theme.muted,
+            background: self.theme.background,
+            link: None,
+        };
+        // Words are lists of pieces with different formats not separated by spaces
+        let mut words: Vec<Vec<(String, Format)>> = Vec::new();
+        let mut separated = false;
+
+        for span in &block.spans {
+            let format = span_format(block.kind, span, self.theme);
+
+            for (index, piece) in span.text.split(char::is_whitespace).enumerate() {
+                if index > 0 {
+                    separated = true;
+                }
+                if piece.is_empty() {
+                    continue;
+                }
+
+                match words.last_mut() {
+                    Some(pieces) if !separated => pieces.push((piece.to_owned(), format.clone())),
+                    _ => words.push(vec![(piece.to_owned(), format.clone())]),
+                }
+
+                separated = false;
+            }
+        }
+
+        self.push(first, &prefix);
+
+        for (index, pieces) in words.iter().enumerate() {
+            let width = pieces.iter().map(|(text, _)| text.width()).sum::<usize>();
+
+            if index > 0 {
+                if self.column + 1 + width > self.width {
+                    self.newline();
+                    self.push(next, &prefix);
+                } else {
+                    self.push(" ", &prefix);
+                }
+            }
+
+            for (text, format) in pieces {
+                // Break words longer than a line
+                for grapheme in text.graphemes(true) {
+                    if self.column + grapheme.width() > self.width && self.column > next.width() {
+                        self.newline();
+                        self.push(next, &prefix);
+                    }
+
+                    self.push(grapheme, format);
+                }
+            }
+        }
+
+        self.newline();
+    }
+}
+
+fn span_format(kind: BlockKind, span: &Span, theme: &Theme) -> Format {
+    let heading = matches!(kind, BlockKind::Heading(_));
+    let quote = kind == BlockKind::Quote;
+
+    Format {
+        style: TextStyle {
+            bold: span.bold || heading,
+            italic: span.italic || quote,
+            underline: span.link.is_some() || kind == BlockKind::Heading(1),
+            ..Default::default()
+        },
+        foreground: match span.link {
+            Some(_) => theme.link,
+            None if quote => theme.muted,
+            None => theme.foreground,
+        },
+        background: if span.code {
+            theme.code
+        } else {
+            theme.background
+        },
+        link: span.link.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paragraph(kind: BlockKind, text: &str) -> Block {
+        Block {
+            kind,
+            spans: vec![Span {
+                text: text.to_owned(),
+                ..Default::default()
+            }],
+        }
+    }
+
+    fn reader(blocks: Vec<Block>, width: u32, height: u32) -> Reader {
+        let mut reader = Reader::new("Title", blocks, &Theme::default());
+
+        reader.set_size(Size::new(width, height));
+        reader
+    }
+
+    fn lines(reader: &Reader) -> Vec<String> {
+        reader
+            .lines
+            .iter()
+            .map(|runs| {
+                let line = runs.iter().map(|run| run.text.as_str()).collect::<String>();
+
+                line.trim_end().to_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn wraps_words() {
+        let reader = reader(
+            vec![paragraph(
+                BlockKind::Paragraph,
+                "the quick brown fox jumps over the lazy dog",
+            )],
+            24,
+            10,
+        );
+
+        assert_eq!(
+            lines(&reader),
+            [
+                "Title",
+                "",
+                "the quick brown fox",
+                "jumps over the lazy",
+                "dog"
+            ]
+        );
+        assert_eq!(reader.lines[2][0].column, 2);
+    }
+
+    #[test]
+    fn breaks_long_words() {
+        let reader = reader(
+            vec![paragraph(
+                BlockKind::Paragraph,
+                "abcdefghijklmnopqrstuvwxyz",
+            )],
+            14,
+            10,
+        );
+
+        assert_eq!(lines(&reader)[2..], ["abcdefghij", "klmnopqrst", "uvwxyz"]);
+    }
+
+    #[test]
+    fn numbers_lists() {
+        let reader = reader(
+            vec![
+                paragraph(BlockKind::OrderedListItem(0), "one"),
+                paragraph(BlockKind::OrderedListItem(1), "nested"),
+                paragraph(BlockKind::OrderedListItem(0), "two three four"),
+                paragraph(BlockKind::ListItem(0), "bullet"),
+            ],
+            16,
+            10,
+        );
+
+        assert_eq!(
+            lines(&reader)[2..],
+            [
+                "1. one",
+                "   1. nested",
+                "2. two three",
+                "   four",
+                "• bullet"
+            ]
+        );
+    }
+
+    #[test]
+    fn scrolls_within_content() {
+        let blocks = (0..5)
+            .map(|index| paragraph(BlockKind::Paragraph, &index.to_string()))
+            .collect();
+        let mut reader = reader(blocks, 20, 4);
+        let max = reader.lines.len() - 4;
+
+        reader.keypress(&b'G'.into());
+        assert_eq!(reader.scroll, max);
+        reader.keypress(&b'j'.into());
+        assert_eq!(reader.scroll, max);
+        reader.keypress(&b'k'.into());
+        assert_eq!(reader.scroll, max - 1);
+        reader.keypress(&b'g'.into());
+        assert_eq!(reader.scroll, 0);
+        reader.keypress(&b'k'.into());
+        assert_eq!(reader.scroll, 0);
+
+        // Growing the terminal clamps the scroll position
+        reader.keypress(&b'G'.into());
+        reader.set_size(Size::new(20, 100));
+        assert_eq!(reader.scroll, 0);
+
+        assert!(!reader.keypress(&b'x'.into()));
+        assert!(reader.keypress(&b'q'.into()));
+        assert!(reader.done());
+    }
+
+    #[test]
+    fn follows_links() {
+        let mut reader = reader(
+            vec![Block {
+                kind: BlockKind::Paragraph,
+                spans: vec![
+                    Span {
+                        text: "see ".to_owned(),
+                        ..Default::default()
+                    },
+                    Span {
+                        text: "docs".to_owned(),
+                        link: Some("https://docs.rs".to_owned()),
+                        ..Default::default()
+                    },
+                ],
+            }],
+            24,
+            10,
+        );
+
+        // The paragraph is on the third line, below the navigation bar
+        assert!(matches!(
+            reader.mouse_down(Point::new(6, 3)),
+            NavigationAction::GoTo(url) if url == "https://docs.rs"
+        ));
+        assert!(matches!(
+            reader.mouse_down(Point::new(3, 3)),
+            NavigationAction::Ignore
+        ));
+    }
+}
@@ -0,0 +1,146 @@
+use unicode_width::UnicodeWidthStr;
+
+use super::{navigation::NavigationElement, theme::Theme};
+use crate::{
+    gfx::{Color, Point, Size},
+    output::TextStyle,
+};
+
+/// Security of the connection to the current page.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SecurityState {
+    /// Nothing to report, ie. `about:blank` or local files
+    #[default]
+    None,
+    /// Valid HTTPS connection
+    Secure,
+    /// Plain HTTP or mixed content
+    Insecure,
+    /// Invalid certificate or other certificate errors
+    Dangerous,
+}
+
+impl SecurityState {
+    pub fn from_code(code: u32) -> Self {
+        match code {
+            1 => SecurityState::Secure,
+            2 => SecurityState::Insecure,
+            3 => SecurityState::Dangerous,
+            _ => SecurityState::None,
+        }
+    }
+}
+
+/// Line at the bottom of the terminal with the page load progress,
+/// the URL of the hovered link, the security state and the zoom level.
+#[derive(Default)]
+pub struct StatusBar {
+    /// Load progress between 0 and 1, `None` once loaded
+    loading: Option<f32>,
+    hover_url: Option<String>,
+    security: SecurityState,
+}
+
+impl StatusBar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_loading(&mut self, loading: bool, progress: f32) {
+        self.loading = loading.then_some(progress.clamp(0.0, 1.0));
+    }
+
+    pub fn set_hover_url(&mut self, url: &str) {
+        self.hover_url = (!url.is_empty()).then(|| url.to_owned());
+    }
+
+    pub fn set_security_state(&mut self, security: SecurityState) {
+        self.security = security;
+    }
+
+    pub fn render(
+        &self,
+        size: Size,
+        row: i32,
+        zoom: f32,
+        theme: &Theme,
+    ) -> Vec<(Point, NavigationElement)> {
+        let background = theme.background;
+        let foreground = theme.muted;
+        let (security, color) = match self.security {
+            SecurityState::None => ("", foreground),
+            SecurityState::Secure => (" Secure ", Color::new(0, 120, 40)),
+            SecurityState::Insecure => (" Not secure ", Color::new(160, 90, 0)),
+            SecurityState::Dangerous => (" Certificate error ", Color::new(190, 0, 0)),
+        };
+        let message = match (&self.hover_url, self.loading) {
+            (Some(url), _) => url.clone(),
+            (None, Some(progress)) => format!("Loading {:.0}%", progress * 100.0),
+            (None, None) => String::new(),
+        };
+        let zoom = match (zoom * 100.0).round() as i32 {
+            100 => String::new(),
+            percent => format!(" {percent}% "),
+        };
+        let space = (size.width as usize).saturating_sub(security.width() + zoom.width() + 1);
+        let mut message = truncate(&message, space);
+
+        message.insert(0, ' ');
+        message.push_str(&" ".repeat(space.saturating_sub(message.width() - 1)));
+
+        let mut elements = Vec::new();
+        let mut point = Point::new(0, row);
+
+        for element in [
+            NavigationElement {
+                text: security.to_owned(),
+                background,
+                foreground: color,
+                style: TextStyle {
+                    bold: true,
+                    ..Default::default()
+                },
+            },
+            NavigationElement {
+                text: message,
+                background,
+                foreground,
+                style: TextStyle::default(),
+            },
+            NavigationElement {
+                text: zoom,
+                background,
+                foreground,
+                style: TextStyle::default(),
+            },
+        ] {
+            let width = element.text.width() as i32;
+
+            elements.push((point, element));
+
+            point = point + (width, 0);
+        }
+
+        elements
+    }
+}
+
+/// Shorten text to fit a number of columns, ending with an ellipsis.
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_owned();
+    }
+
+    let mut output = String::new();
+
+    for char in text.chars() {
+        if output.width() + 2 > width {
+            break;
+        }
+
+        output.push(char);
+    }
+
+    output.push('…');
+    output
+}
@@ -0,0 +1,553 @@
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::input::Key;
+
+/// A single-line text input operating on grapheme clusters.
+///
+/// Positions (`cursor`, `anchor`) are grapheme indices, never byte offsets,
+/// so editing non-ASCII text cannot split a character. The visible part of
+/// the text is scrolled horizontally to keep the cursor on screen.
+#[derive(Clone, Debug, Default)]
+pub struct TextField {
+    text: String,
+    /// Cursor position in graphemes
+    cursor: usize,
+    /// Other end of the selection in graphemes, if any
+    anchor: Option<usize>,
+    /// Number of columns available to display the text
+    width: usize,
+    /// First visible column
+    scroll: usize,
+    /// Bytes of an UTF-8 sequence we haven't fully received yet
+    pending: Vec<u8>,
+}
+
+/// Editing action of a key press.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Edit {
+    /// A byte of typed text, possibly part of an UTF-8 sequence
+    Byte(u8),
+    Home,
+    End,
+    Left,
+    Right,
+    Backspace,
+    Delete,
+    DeleteToEnd,
+    DeleteToStart,
+    DeleteWord,
+}
+
+impl Edit {
+    fn decode(key: &Key) -> Option<Self> {
+        Some(match key.char {
+            // Up, Home, Ctrl+A
+            0x11 | 0xf5 | 0x01 => Edit::Home,
+            // Down, End, Ctrl+E
+            0x12 | 0xf6 | 0x05 => Edit::End,
+            0x13 => Edit::Right,
+            0x14 => Edit::Left,
+            0x7f | 0x08 => Edit::Backspace,
+            // Delete, Ctrl+D
+            0xf7 | 0x04 => Edit::Delete,
+            // Ctrl+K
+            0x0b => Edit::DeleteToEnd,
+            // Ctrl+U
+            0x15 => Edit::DeleteToStart,
+            // Ctrl+W
+            0x17 => Edit::DeleteWord,
+            // Printable ASCII and UTF-8 bytes, 0xf5 and above never appear in UTF-8
+            0x20..=0x7e | 0x80..=0xf4 => Edit::Byte(key.char),
+            _ => return None,
+        })
+    }
+}
+
+/// Visible part of a text field, split around the selection.
+#[derive(Clone, Debug, Default)]
+pub struct TextFieldView {
+    pub before: String,
+    pub selected: String,
+    pub after: String,
+    /// Column of the cursor relative to the start of the field
+    pub cursor: usize,
+}
+
+impl TextField {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the content and move the cursor to the end.
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_owned();
+        self.anchor = None;
+        self.pending.clear();
+        self.cursor = self.len();
+        self.scroll = 0;
+        self.reveal();
+    }
+
+    pub fn set_width(&mut self, width: usize) {
+        self.width = width;
+        self.reveal();
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.cursor = self.len();
+        self.reveal();
+    }
+
+    /// Selected text, if any.
+    pub fn selection(&self) -> Option<&str> {
+        let range = self.byte_range(self.selected()?);
+
+        Some(&self.text[range])
+    }
+
+    /// Move the cursor to the grapheme displayed at a column of the field.
+    pub fn click(&mut self, column: usize) {
+        let target = self.scroll + column;
+        let mut x = 0;
+
+        self.anchor = None;
+        self.cursor = self.len();
+
+        for (index, grapheme) in self.text.graphemes(true).enumerate() {
+            let width = grapheme.width();
+
+            if target < x + width.max(1) {
+                self.cursor = index;
+
+                break;
+            }
+
+            x += width;
+        }
+
+        self.reveal();
+    }
+
+    /// Insert a string at the cursor, replacing the selection.
+    pub fn insert(&mut self, text: &str) {
+        self.delete_selection();
+
+        let offset = self.byte_offset(self.cursor);
+
+        self.text.insert_str(offset, text);
+        // Combining characters might have merged with the previous grapheme
+        self.cursor = self.text[..offset + text.len()].graphemes(true).count();
+        self.reveal();
+    }
+
+    /// Handle an editing key, returns `false` if the key isn't used by the field.
+    pub fn keypress(&mut self, key: &Key) -> bool {
+        let Some(edit) = Edit::decode(key) else {
+            return false;
+        };
+        let word = key.modifiers.control || key.modifiers.alt || key.modifiers.meta;
+        let select = key.modifiers.shift;
+
+        // A key typed in the middle of an UTF-8 sequence drops it
+        if !matches!(edit, Edit::Byte(_)) {
+            self.pending.clear()
+        }
+
+        match edit {
+            Edit::Byte(byte) => return self.push_byte(byte),
+            Edit::Home => self.move_to(0, select),
+            Edit::End => self.move_to(self.len(), select),
+            Edit::Right => {
+                let target = match (word, self.selected()) {
+                    (false, Some(range)) if !select => range.end,
+                    (false, _) => (self.cursor + 1).min(self.len()),
+                    (true, _) => self.next_word(),
+                };
+
+                self.move_to(target, select)
+            }
+            Edit::Left => {
+                let target = match (word, self.selected()) {
+                    (false, Some(range)) if !select => range.start,
+                    (false, _) => self.cursor.saturating_sub(1),
+                    (true, _) => self.previous_word(),
+                };
+
+                self.move_to(target, select)
+            }
+            Edit::Backspace => {
+                if !self.delete_selection() && self.cursor > 0 {
+                    let start = if word {
+                        self.previous_word()
+                    } else {
+                        self.cursor - 1
+                    };
+
+                    self.delete(start..self.cursor)
+                }
+            }
+            Edit::Delete => {
+                if !self.delete_selection() && self.cursor < self.len() {
+                    self.delete(self.cursor..self.cursor + 1)
+                }
+            }
+            Edit::DeleteToEnd => self.delete(self.cursor..self.len()),
+            Edit::DeleteToStart => self.delete(0..self.cursor),
+            Edit::DeleteWord => {
+                if !self.delete_selection() {
+                    self.delete(self.previous_word()..self.cursor)
+                }
+            }
+        }
+
+        self.reveal();
+
+        true
+    }
+
+    /// Compute what should be displayed in the field.
+    pub fn view(&self) -> TextFieldView {
+        let selected = self.selected().unwrap_or(self.cursor..self.cursor);
+        let mut view = TextFieldView::default();
+        let mut x = 0usize;
+
+        for (index, grapheme) in self.text.graphemes(true).enumerate() {
+            let width = grapheme.width();
+
+            if index == self.cursor {
+                view.cursor = x.saturating_sub(self.scroll);
+            }
+
+            if x >= self.scroll && x + width <= self.scroll + self.width {
+                if index < selected.start {
+                    view.before.push_str(grapheme)
+                } else if index < selected.end {
+                    view.selected.push_str(grapheme)
+                } else {
+                    view.after.push_str(grapheme)
+                }
+            }
+
+            x += width;
+        }
+
+        if self.cursor >= self.len() {
+            view.cursor = x.saturating_sub(self.scroll);
+        }
+
+        view
+    }
+
+    fn len(&self) -> usize {
+        self.text.graphemes(true).count()
+    }
+
+    /// Accumulate the bytes of a multi-byte UTF-8 character.
+    fn push_byte(&mut self, byte: u8) -> bool {
+        // Anything but a continuation byte starts a new character
+        if byte & 0xc0 != 0x80 {
+            self.pending.clear();
+        }
+
+        self.pending.push(byte);
+
+        match std::str::from_utf8(&self.pending) {
+            Ok(text) => {
+                let text = text.to_owned();
+
+                self.pending.clear();
+                self.insert(&text);
+            }
+            Err(error) if error.error_len().is_some() => self.pending.clear(),
+            // Incomplete sequence, wait for more bytes
+            Err(_) => (),
+        }
+
+        true
+    }
+
+    fn move_to(&mut self, cursor: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+
+        self.cursor = cursor;
+    }
+
+    fn selected(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor?;
+
+        match anchor.cmp(&self.cursor) {
+            std::cmp::Ordering::Less => Some(anchor..self.cursor),
+            std::cmp::Ordering::Greater => Some(self.cursor..anchor),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        match self.selected() {
+            Some(range) => {
+                self.delete(range);
+
+                true
+            }
+            None => {
+                self.anchor = None;
+
+                false
+            }
+        }
+    }
+
+    fn delete(&mut self, range: Range<usize>) {
+        let bytes = self.byte_range(range.clone());
+
+        self.text.replace_range(bytes, "");
+        self.cursor = range.start;
+        self.anchor = None;
+    }
+
+    fn byte_offset(&self, index: usize) -> usize {
+        self.text
+            .grapheme_indices(true)
+            .nth(index)
+            .map(|(offset, _)| offset)
+            .unwrap_or(self.text.len())
+    }
+
+    fn byte_range(&self, range: Range<usize>) -> Range<usize> {
+        self.byte_offset(range.start)..self.byte_offset(range.end)
+    }
+
+    fn is_word(grapheme: &str) -> bool {
+        grapheme.chars().any(char::is_alphanumeric)
+    }
+
+    fn previous_word(&self) -> usize {
+        let graphemes: Vec<&str> = self.text.graphemes(true).take(self.cursor).collect();
+        let mut index = graphemes.len();
+
+        while index > 0 && !Self::is_word(graphemes[index - 1]) {
+            index -= 1;
+        }
+        while index > 0 && Self::is_word(graphemes[index - 1]) {
+            index -= 1;
+        }
+
+        index
+    }
+
+    fn next_word(&self) -> usize {
+        let graphemes: Vec<&str> = self.text.graphemes(true).collect();
+        let mut index = self.cursor;
+
+        while index < graphemes.len() && !Self::is_word(graphemes[index]) {
+            index += 1;
+        }
+        while index < graphemes.len() && Self::is_word(graphemes[index]) {
+            index += 1;
+        }
+
+        index
+    }
+
+    /// Scroll horizontally so the cursor stays visible.
+    fn reveal(&mut self) {
+        let column = self
+            .text
+            .graphemes(true)
+            .take(self.cursor)
+            .map(|grapheme| grapheme.width())
+            .sum::<usize>();
+        // Keep a column for the cursor after the last character
+        let width = self.width.max(1);
+
+        if column < self.scroll {
+            self.scroll = column;
+        } else if column >= self.scroll + width {
+            self.scroll = column + 1 - width;
+        }
+
+        // Don't leave blank space on the right if we can show more text
+        let total = self.text.width() + 1;
+
+        if total <= width {
+            self.scroll = 0;
+        } else {
+            self.scroll = self.scroll.min(total - width);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::KeyModifiers;
+
+    fn field(text: &str) -> TextField {
+        let mut field = TextField::new();
+
+        field.set_width(20);
+        field.set_text(text);
+        field
+    }
+
+    fn press(field: &mut TextField, char: u8) -> bool {
+        field.keypress(&char.into())
+    }
+
+    #[test]
+    fn insert_moves_the_cursor() {
+        let mut field = field("ac");
+
+        press(&mut field, 0x14);
+        field.insert("b");
+
+        assert_eq!(field.text(), "abc");
+        assert_eq!(field.cursor, 2);
+    }
+
+    #[test]
+    fn insert_combining_character() {
+        let mut field = field("ec");
+
+        press(&mut field, 0x14);
+        // U+0301 merges with the `e` instead of adding a grapheme
+        field.insert("\u{301}");
+        field.insert("x");
+
+        assert_eq!(field.text(), "e\u{301}xc");
+        assert_eq!(field.cursor, 2);
+    }
+
+    #[test]
+    fn insert_utf8_bytes() {
+        let mut field = field("");
+
+        for byte in "é😀".bytes() {
+            assert!(press(&mut field, byte));
+        }
+
+        assert_eq!(field.text(), "é😀");
+        assert_eq!(field.cursor, 2);
+        assert_eq!(field.view().cursor, 3);
+    }
+
+    #[test]
+    fn home_end_and_delete() {
+        let mut field = field("abc");
+
+        press(&mut field, 0xf5);
+        assert_eq!(field.cursor, 0);
+
+        press(&mut field, 0xf7);
+        assert_eq!(field.text(), "bc");
+
+        press(&mut field, 0xf6);
+        assert_eq!(field.cursor, 2);
+
+        // Ctrl+A and Ctrl+D
+        press(&mut field, 0x01);
+        press(&mut field, 0x04);
+        assert_eq!((field.text(), field.cursor), ("c", 0));
+    }
+
+    #[test]
+    fn keys_cancel_pending_bytes() {
+        let mut field = field("bc");
+
+        // First byte of `é`, then Home and an ASCII character
+        press(&mut field, 0xc3);
+        press(&mut field, 0xf5);
+        assert_eq!(field.cursor, 0);
+
+        press(&mut field, 0xc3);
+        press(&mut field, b'a');
+        assert_eq!(field.text(), "abc");
+
+        // Keys the field doesn't use aren't swallowed either
+        press(&mut field, 0xc3);
+        assert!(!press(&mut field, 0x0d));
+    }
+
+    #[test]
+    fn backspace_graphemes() {
+        let mut field = field("a👍🏽");
+
+        press(&mut field, 0x7f);
+
+        assert_eq!(field.text(), "a");
+    }
+
+    #[test]
+    fn words() {
+        let mut field = field("hello big world");
+        let control = |char| Key {
+            char,
+            modifiers: KeyModifiers {
+                control: true,
+                ..Default::default()
+            },
+        };
+
+        field.keypress(&control(0x14));
+        assert_eq!(field.cursor, 10);
+
+        press(&mut field, 0x17);
+        assert_eq!(field.text(), "hello world");
+
+        field.keypress(&control(0x14));
+        field.keypress(&control(0x13));
+        assert_eq!(field.cursor, 5);
+    }
+
+    #[test]
+    fn selection() {
+        let mut field = field("hello");
+        let shift = Key {
+            char: 0x14,
+            modifiers: KeyModifiers {
+                shift: true,
+                ..Default::default()
+            },
+        };
+
+        field.keypress(&shift);
+        field.keypress(&shift);
+        assert_eq!(field.selection(), Some("lo"));
+
+        field.insert("p!");
+        assert_eq!((field.text(), field.cursor), ("help!", 5));
+
+        field.select_all();
+        press(&mut field, 0x7f);
+        assert_eq!(field.text(), "");
+    }
+
+    #[test]
+    fn scroll_to_the_cursor() {
+        let mut field = field("");
+
+        field.set_width(4);
+        field.set_text("abcdefgh");
+
+        let view = field.view();
+
+        assert_eq!(view.before, "fgh");
+        assert_eq!(view.cursor, 3);
+
+        press(&mut field, 0xf5);
+        assert_eq!(field.view().before, "");
+        assert_eq!(field.view().after, "abcd");
+    }
+}
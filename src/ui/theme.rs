@@ -0,0 +1,160 @@
+use crate::{config::Ini, gfx::Color, utils::log};
+
+/// Where the navigation bar is displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BarPosition {
+    #[default]
+    Top,
+    Bottom,
+    /// No navigation bar, ie. for kiosks
+    Hidden,
+}
+
+impl BarPosition {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "top" => Some(BarPosition::Top),
+            "bottom" => Some(BarPosition::Bottom),
+            "hidden" => Some(BarPosition::Hidden),
+            _ => None,
+        }
+    }
+}
+
+/// Colors, glyphs and placement of the browser UI.
+#[derive(Clone, Debug)]
+pub struct Theme {
+    pub background: Color,
+    pub foreground: Color,
+    /// Buttons that can't be used, ie. back without history
+    pub disabled: Color,
+    /// Labels and other secondary text
+    pub muted: Color,
+    /// Links in reader mode
+    pub link: Color,
+    /// Background of code in reader mode
+    pub code: Color,
+    /// Use ASCII characters for the buttons, some fonts lack the arrows
+    pub ascii: bool,
+    pub bar: BarPosition,
+}
+
+const LIGHT: Theme = Theme {
+    background: Color::splat(255),
+    foreground: Color::splat(0),
+    disabled: Color::splat(200),
+    muted: Color::splat(100),
+    link: Color::new(0, 80, 180),
+    code: Color::splat(235),
+    ascii: false,
+    bar: BarPosition::Top,
+};
+const DARK: Theme = Theme {
+    background: Color::splat(35),
+    foreground: Color::splat(220),
+    disabled: Color::splat(90),
+    muted: Color::splat(150),
+    link: Color::new(110, 170, 255),
+    code: Color::splat(55),
+    ascii: false,
+    bar: BarPosition::Top,
+};
+const HIGH_CONTRAST: Theme = Theme {
+    background: Color::splat(0),
+    foreground: Color::splat(255),
+    disabled: Color::splat(120),
+    muted: Color::new(255, 255, 0),
+    link: Color::new(0, 255, 255),
+    code: Color::splat(40),
+    ascii: false,
+    bar: BarPosition::Top,
+};
+
+impl Default for Theme {
+    fn default() -> Self {
+        LIGHT
+    }
+}
+
+impl Theme {
+    pub fn from_ini(ini: &Ini) -> Theme {
+        let preset = ini.get("theme", "preset").unwrap_or("light");
+        let mut theme = match preset {
+            "light" => LIGHT,
+            "dark" => DARK,
+            "high-contrast" => HIGH_CONTRAST,
+            name => {
+                log::warning!("unknown theme preset {name:?}, using light");
+
+                LIGHT
+            }
+        };
+
+        for (key, value) in ini.section("theme") {
+            let color = match key {
+                "preset" => continue,
+                "background" => &mut theme.background,
+                "foreground" => &mut theme.foreground,
+                "disabled" => &mut theme.disabled,
+                "muted" => &mut theme.muted,
+                "link" => &mut theme.link,
+                "code" => &mut theme.code,
+                "glyphs" => {
+                    match value {
+                        "unicode" => theme.ascii = false,
+                        "ascii" => theme.ascii = true,
+                        _ => log::warning!("unknown glyphs {value:?}, expected unicode or ascii"),
+                    }
+
+                    continue;
+                }
+                "bar" => {
+                    match BarPosition::parse(value) {
+                        Some(bar) => theme.bar = bar,
+                        None => log::warning!(
+                            "unknown bar position {value:?}, expected top, bottom or hidden"
+                        ),
+                    }
+
+                    continue;
+                }
+                key => {
+                    log::warning!("unknown theme option {key:?}");
+
+                    continue;
+                }
+            };
+
+            match Color::from_hex(value) {
+                Some(value) => *color = value,
+                None => log::warning!("invalid color {value:?} for {key}, expected #rrggbb"),
+            }
+        }
+
+        theme
+    }
+
+    pub fn back_glyph(&self) -> &'static str {
+        if self.ascii {
+            "<"
+        } else {
+            "\u{276e}"
+        }
+    }
+
+    pub fn forward_glyph(&self) -> &'static str {
+        if self.ascii {
+            ">"
+        } else {
+            "\u{276f}"
+        }
+    }
+
+    pub fn reload_glyph(&self) -> &'static str {
+        if self.ascii {
+            "R"
+        } else {
+            "↻"
+        }
+    }
+}
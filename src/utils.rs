@@ -1,7 +1,10 @@
 mod four_bits;
 mod try_block;
 
+pub mod base64;
+pub mod json;
 pub mod log;
+pub mod png;
 
 use try_block::*;
 
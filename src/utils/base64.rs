@@ -0,0 +1,100 @@
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode bytes using the standard base64 alphabet with padding.
+pub fn encode(input: &[u8]) -> String {
+    let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+
+    for chunk in input.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let triple = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                output.push(ALPHABET[(triple >> (18 - index * 6) & 0x3f) as usize] as char)
+            } else {
+                output.push('=')
+            }
+        }
+    }
+
+    output
+}
+
+/// Decode standard base64, ignoring whitespace and padding.
+pub fn decode(input: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for byte in input.bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' | b' ' | b'\t' | b'\r' | b'\n' => continue,
+            _ => return None,
+        };
+
+        buffer = buffer << 6 | value as u32;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vectors of RFC 4648
+    const VECTORS: [(&str, &str); 7] = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+
+    #[test]
+    fn encode_vectors() {
+        for (text, encoded) in VECTORS {
+            assert_eq!(encode(text.as_bytes()), encoded);
+        }
+    }
+
+    #[test]
+    fn decode_vectors() {
+        for (text, encoded) in VECTORS {
+            assert_eq!(decode(encoded).as_deref(), Some(text.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn decode_variants() {
+        // Wrapped lines, missing padding and the URL-safe alphabet
+        assert_eq!(decode("Zm9v\r\nYmFy").as_deref(), Some(&b"foobar"[..]));
+        assert_eq!(decode("Zm9vYg").as_deref(), Some(&b"foob"[..]));
+        assert_eq!(decode("-_8").as_deref(), Some(&[0xfb, 0xff][..]));
+        assert_eq!(decode("Zm9v!"), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        let bytes: Vec<u8> = (0..=255).collect();
+
+        assert_eq!(decode(&encode(&bytes)), Some(bytes));
+    }
+}
@@ -0,0 +1,447 @@
+use std::fmt::{self, Write};
+
+/// Maximum nesting of arrays and objects, deeper documents are rejected
+/// rather than overflowing the stack.
+const MAX_DEPTH: usize = 128;
+
+/// Encode text as a JSON string, with the quotes.
+pub fn quote(text: &str) -> String {
+    let mut output = String::with_capacity(text.len() + 2);
+
+    output.push('"');
+
+    for char in text.chars() {
+        match char {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            // Other control characters, ie. the escape of terminal sequences
+            char if char < ' ' => write!(output, "\\u{:04x}", char as u32).unwrap(),
+            char => output.push(char),
+        }
+    }
+
+    output.push('"');
+    output
+}
+
+/// Parsed JSON document.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// Members in the order of the document
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Parse a JSON document, `None` if it's invalid.
+    pub fn parse(text: &str) -> Option<Value> {
+        let mut parser = Parser {
+            bytes: text.as_bytes(),
+            index: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+
+        parser.whitespace();
+
+        (parser.index == parser.bytes.len()).then_some(value)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Number(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Object with the members in the given order.
+    pub fn object<const N: usize>(members: [(&str, Value); N]) -> Value {
+        Value::Object(
+            members
+                .into_iter()
+                .map(|(name, value)| (name.to_owned(), value))
+                .collect(),
+        )
+    }
+
+    /// Member of an object.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Number(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+/// Serialize on a single line.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(value) => write!(f, "{value}"),
+            // There's no infinity or NaN in JSON
+            Value::Number(value) if !value.is_finite() => write!(f, "null"),
+            Value::Number(value) => write!(f, "{value}"),
+            Value::String(value) => write!(f, "{}", quote(value)),
+            Value::Array(items) => {
+                write!(f, "[")?;
+
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write!(f, "{item}")?;
+                }
+
+                write!(f, "]")
+            }
+            Value::Object(members) => {
+                write!(f, "{{")?;
+
+                for (index, (name, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write!(f, "{}:{value}", quote(name))?;
+                }
+
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    index: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+
+        self.index += 1;
+
+        Some(byte)
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.index += 1
+        }
+    }
+
+    fn literal(&mut self, literal: &str, value: Value) -> Option<Value> {
+        if !self.bytes[self.index..].starts_with(literal.as_bytes()) {
+            return None;
+        }
+
+        self.index += literal.len();
+
+        Some(value)
+    }
+
+    fn value(&mut self) -> Option<Value> {
+        if self.depth == MAX_DEPTH {
+            return None;
+        }
+
+        self.depth += 1;
+
+        let value = self.element();
+
+        self.depth -= 1;
+
+        value
+    }
+
+    fn element(&mut self) -> Option<Value> {
+        self.whitespace();
+
+        match self.peek()? {
+            b'n' => self.literal("null", Value::Null),
+            b't' => self.literal("true", Value::Bool(true)),
+            b'f' => self.literal("false", Value::Bool(false)),
+            b'"' => Some(Value::String(self.string()?)),
+            b'[' => {
+                let mut items = Vec::new();
+
+                self.index += 1;
+                self.whitespace();
+
+                if self.peek()? == b']' {
+                    self.index += 1;
+
+                    return Some(Value::Array(items));
+                }
+
+                loop {
+                    items.push(self.value()?);
+                    self.whitespace();
+
+                    match self.next()? {
+                        b',' => continue,
+                        b']' => return Some(Value::Array(items)),
+                        _ => return None,
+                    }
+                }
+            }
+            b'{' => {
+                let mut members = Vec::new();
+
+                self.index += 1;
+                self.whitespace();
+
+                if self.peek()? == b'}' {
+                    self.index += 1;
+
+                    return Some(Value::Object(members));
+                }
+
+                loop {
+                    self.whitespace();
+
+                    if self.peek()? != b'"' {
+                        return None;
+                    }
+
+                    let key = self.string()?;
+
+                    self.whitespace();
+
+                    if self.next()? != b':' {
+                        return None;
+                    }
+
+                    members.push((key, self.value()?));
+                    self.whitespace();
+
+                    match self.next()? {
+                        b',' => continue,
+                        b'}' => return Some(Value::Object(members)),
+                        _ => return None,
+                    }
+                }
+            }
+            b'-' | b'0'..=b'9' => {
+                let start = self.index;
+
+                while matches!(
+                    self.peek(),
+                    Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+                ) {
+                    self.index += 1
+                }
+
+                let number = std::str::from_utf8(&self.bytes[start..self.index]).ok()?;
+
+                Some(Value::Number(number.parse().ok()?))
+            }
+            _ => None,
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let mut bytes = Vec::new();
+
+        // Opening quote
+        self.index += 1;
+
+        loop {
+            match self.next()? {
+                b'"' => return String::from_utf8(bytes).ok(),
+                b'\\' => {
+                    let char = match self.next()? {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\x08',
+                        b'f' => '\x0c',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let code = self.hex()?;
+
+                            // Characters outside of the BMP are encoded as surrogate pairs
+                            if (0xd800..0xdc00).contains(&code) {
+                                if self.next()? != b'\\' || self.next()? != b'u' {
+                                    return None;
+                                }
+
+                                let low = self.hex()?;
+
+                                if !(0xdc00..0xe000).contains(&low) {
+                                    return None;
+                                }
+
+                                char::from_u32(0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00))?
+                            } else {
+                                char::from_u32(code)?
+                            }
+                        }
+                        _ => return None,
+                    };
+
+                    bytes.extend_from_slice(char.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                byte => bytes.push(byte),
+            }
+        }
+    }
+
+    fn hex(&mut self) -> Option<u32> {
+        let digits = self.bytes.get(self.index..self.index + 4)?;
+
+        self.index += 4;
+
+        // `from_str_radix` would also accept a sign, ie. `\u+123`
+        if !digits.iter().all(u8::is_ascii_hexdigit) {
+            return None;
+        }
+
+        u32::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_scalars() {
+        assert_eq!(Value::parse("null"), Some(Value::Null));
+        assert_eq!(Value::parse(" true "), Some(Value::Bool(true)));
+        assert_eq!(Value::parse("-1.5e2"), Some(Value::Number(-150.0)));
+        assert_eq!(Value::parse("\"a\\nb\""), Some("a\nb".into()));
+        assert_eq!(Value::parse("\"\\ud83d\\ude00\""), Some("😀".into()));
+        assert_eq!(Value::parse("\"\\u+123\""), None);
+    }
+
+    #[test]
+    fn parse_request() {
+        let request =
+            Value::parse(r#"{"id": 1, "method": "scroll", "params": {"rows": -3}}"#).unwrap();
+
+        assert_eq!(request.get("id").and_then(Value::as_f64), Some(1.0));
+        assert_eq!(
+            request.get("method").and_then(Value::as_str),
+            Some("scroll")
+        );
+        assert_eq!(
+            request
+                .get("params")
+                .and_then(|params| params.get("rows"))
+                .and_then(Value::as_f64),
+            Some(-3.0)
+        );
+        assert_eq!(
+            Value::parse("[1, [], {}]")
+                .as_ref()
+                .and_then(Value::as_array),
+            Some(&[1.0.into(), Value::Array(vec![]), Value::Object(vec![])][..])
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for text in [
+            "",
+            "nul",
+            "[1,",
+            "{\"a\" 1}",
+            "{1: 2}",
+            "\"\\x\"",
+            "1 2",
+            "\"a",
+        ] {
+            assert_eq!(Value::parse(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn parse_deep() {
+        let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+
+        assert!(Value::parse(&nested(MAX_DEPTH)).is_some());
+        assert_eq!(Value::parse(&nested(MAX_DEPTH + 1)), None);
+        assert_eq!(Value::parse(&"[".repeat(100_000)), None);
+    }
+
+    #[test]
+    fn serialize() {
+        let value = Value::object([
+            ("text", "\"\x1b[0m\"".into()),
+            (
+                "items",
+                Value::Array(vec![Value::Null, true.into(), 2.0.into()]),
+            ),
+        ]);
+        let text = value.to_string();
+
+        assert_eq!(text, r#"{"text":"\"\u001b[0m\"","items":[null,true,2]}"#);
+        assert_eq!(Value::parse(&text), Some(value));
+    }
+}
@@ -0,0 +1,358 @@
+/// Window of the LZ77 matches, the maximum allowed by deflate.
+const WINDOW: usize = 32768;
+/// Shortest and longest repetition deflate can encode.
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const HASH_BITS: u32 = 15;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Encode an image with 4 bytes per pixel in BGRA order,
+/// as drawn by the browser, to an RGBA PNG file.
+pub fn encode_bgra(pixels: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let stride = width as usize * 4;
+    let mut previous = vec![0u8; stride];
+    let mut filtered = Vec::with_capacity((stride + 1) * height as usize);
+
+    for row in pixels.chunks_exact(stride).take(height as usize) {
+        let row = row
+            .chunks_exact(4)
+            .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+            .collect::<Vec<_>>();
+
+        filter(&row, &previous, &mut filtered);
+        previous = row;
+    }
+
+    let mut header = Vec::with_capacity(13);
+
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, truecolor with alpha, no interlacing
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut output = b"\x89PNG\r\n\x1a\n".to_vec();
+
+    chunk(&mut output, b"IHDR", &header);
+    chunk(&mut output, b"IDAT", &zlib(&filtered));
+    chunk(&mut output, b"IEND", &[]);
+    output
+}
+
+/// Append a row with the filter giving the smallest sum of absolute values,
+/// a cheap estimate of which one compresses best.
+fn filter(row: &[u8], previous: &[u8], output: &mut Vec<u8>) {
+    let sub = |index: usize| row[index].wrapping_sub(if index < 4 { 0 } else { row[index - 4] });
+    let up = |index: usize| row[index].wrapping_sub(previous[index]);
+    let cost = |apply: &dyn Fn(usize) -> u8| {
+        (0..row.len())
+            .map(|index| (apply(index) as i8).unsigned_abs() as u64)
+            .sum::<u64>()
+    };
+    let filters: [(u8, &dyn Fn(usize) -> u8); 3] = [(0, &|index| row[index]), (1, &sub), (2, &up)];
+    let mut best = (u64::MAX, filters[0]);
+
+    for filter in filters {
+        let cost = cost(filter.1);
+
+        if cost < best.0 {
+            best = (cost, filter);
+        }
+    }
+
+    let (_, (kind, apply)) = best;
+
+    output.push(kind);
+    output.extend((0..row.len()).map(apply));
+}
+
+fn chunk(output: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    let start = output.len() + 4;
+
+    output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    output.extend_from_slice(kind);
+    output.extend_from_slice(data);
+
+    let crc = crc32(&output[start..]);
+
+    output.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+
+    for (index, entry) in table.iter_mut().enumerate() {
+        *entry = (0..8).fold(index as u32, |crc, _| match crc & 1 {
+            1 => 0xedb88320 ^ (crc >> 1),
+            _ => crc >> 1,
+        });
+    }
+
+    !data.iter().fold(!0u32, |crc, &byte| {
+        table[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+
+    // Sums can't overflow before 5552 bytes
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+
+        a %= 65521;
+        b %= 65521;
+    }
+
+    b << 16 | a
+}
+
+/// Compress data in the zlib format, using a single deflate
+/// block with the fixed Huffman codes.
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    let mut table = vec![usize::MAX; 1 << HASH_BITS];
+    let hash = |index: usize| {
+        let value = u32::from_le_bytes([data[index], data[index + 1], data[index + 2], 0]);
+
+        (value.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+    };
+    let mut index = 0;
+
+    // Zlib header for deflate with a 32K window, then the final block header
+    bits.output.extend_from_slice(&[0x78, 0x01]);
+    bits.write(1, 1);
+    bits.write(1, 2);
+
+    while index < data.len() {
+        let mut length = 0;
+
+        if index + MIN_MATCH <= data.len() {
+            let key = hash(index);
+            let candidate = table[key];
+
+            table[key] = index;
+
+            if candidate != usize::MAX && index - candidate <= WINDOW {
+                let limit = (data.len() - index).min(MAX_MATCH);
+
+                while length < limit && data[candidate + length] == data[index + length] {
+                    length += 1;
+                }
+            }
+
+            if length >= MIN_MATCH {
+                bits.repetition(length, index - candidate);
+
+                // Index the skipped positions for the next matches
+                for skipped in index + 1..(index + length).min(data.len() - MIN_MATCH + 1) {
+                    table[hash(skipped)] = skipped;
+                }
+
+                index += length;
+
+                continue;
+            }
+        }
+
+        bits.literal(data[index] as u16);
+        index += 1;
+    }
+
+    // End of block
+    bits.literal(256);
+
+    let mut output = bits.finish();
+
+    output.extend_from_slice(&adler32(data).to_be_bytes());
+    output
+}
+
+#[derive(Default)]
+struct BitWriter {
+    output: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    /// Write the lowest `count` bits of a value, least significant first.
+    fn write(&mut self, value: u32, count: u32) {
+        self.buffer |= value << self.count;
+        self.count += count;
+
+        while self.count >= 8 {
+            self.output.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Write a Huffman code, most significant bit first.
+    fn code(&mut self, code: u32, count: u32) {
+        self.write(code.reverse_bits() >> (32 - count), count)
+    }
+
+    /// Write a literal byte or length symbol with the fixed codes.
+    fn literal(&mut self, symbol: u16) {
+        let symbol = symbol as u32;
+
+        match symbol {
+            0..=143 => self.code(0x30 + symbol, 8),
+            144..=255 => self.code(0x190 + symbol - 144, 9),
+            256..=279 => self.code(symbol - 256, 7),
+            _ => self.code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn repetition(&mut self, length: usize, distance: usize) {
+        // Matches are at least 3 bytes long and 1 byte away, the first codes
+        let code = LENGTH_BASE
+            .partition_point(|&base| base as usize <= length)
+            .saturating_sub(1);
+
+        self.literal(257 + code as u16);
+        self.write(
+            (length - LENGTH_BASE[code] as usize) as u32,
+            LENGTH_EXTRA[code] as u32,
+        );
+
+        let code = DISTANCE_BASE
+            .partition_point(|&base| base as usize <= distance)
+            .saturating_sub(1);
+
+        self.code(code as u32, 5);
+        self.write(
+            (distance - DISTANCE_BASE[code] as usize) as u32,
+            DISTANCE_EXTRA[code] as u32,
+        );
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.output.push(self.buffer as u8);
+        }
+
+        self.output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use miniz_oxide::inflate::decompress_to_vec_zlib;
+
+    use super::*;
+
+    #[test]
+    fn crc32_vectors() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(crc32(b"IEND"), 0xae426082);
+    }
+
+    #[test]
+    fn adler32_vectors() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+        // Long enough to reduce the sums modulo 65521
+        assert_eq!(adler32(&[0xff; 10000]), 0xb623eb2b);
+    }
+
+    #[test]
+    fn zlib_round_trip() {
+        let repeated = b"carbonyl ".repeat(1000);
+        let noise = (0..100_000u32)
+            .map(|index| (index.wrapping_mul(2654435761) >> 13) as u8)
+            .collect::<Vec<_>>();
+        // Long runs use the longest matches and distances of the window
+        let runs = [
+            vec![0; 70_000],
+            noise[..40_000].to_vec(),
+            noise[..40_000].to_vec(),
+        ]
+        .concat();
+
+        for data in [&b""[..], b"a", b"aaa", &repeated, &noise, &runs] {
+            assert_eq!(
+                decompress_to_vec_zlib(&zlib(data)).ok().as_deref(),
+                Some(data)
+            );
+        }
+
+        assert!(zlib(&repeated).len() < repeated.len() / 10);
+    }
+
+    #[test]
+    fn encode_image() {
+        let (width, height) = (3, 2);
+        let pixels = (0..width * height * 4)
+            .map(|index| index as u8 * 10)
+            .collect::<Vec<_>>();
+        let png = encode_bgra(&pixels, width, height);
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+        // Walk the chunks, checking their CRC
+        let mut chunks = Vec::new();
+        let mut rest = &png[8..];
+
+        while !rest.is_empty() {
+            let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (body, crc) = rest[4..].split_at(4 + length);
+
+            assert_eq!(crc32(body).to_be_bytes(), crc[..4]);
+            chunks.push((&body[..4], &body[4..]));
+            rest = &crc[4..];
+        }
+
+        let kinds = chunks.iter().map(|(kind, _)| *kind).collect::<Vec<_>>();
+
+        assert_eq!(kinds, [&b"IHDR"[..], b"IDAT", b"IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0, 3, 0, 0, 0, 2, 8, 6, 0, 0, 0]);
+
+        // Undo the filters and compare with the input converted to RGBA
+        let filtered = decompress_to_vec_zlib(chunks[1].1).unwrap();
+        let stride = width as usize * 4;
+        let mut previous = vec![0u8; stride];
+
+        for (row, input) in filtered.chunks(stride + 1).zip(pixels.chunks(stride)) {
+            let mut pixels = vec![0u8; stride];
+
+            for index in 0..stride {
+                let left = if index < 4 { 0 } else { pixels[index - 4] };
+
+                pixels[index] = match row[0] {
+                    0 => row[1 + index],
+                    1 => row[1 + index].wrapping_add(left),
+                    2 => row[1 + index].wrapping_add(previous[index]),
+                    filter => panic!("unexpected filter {filter}"),
+                };
+            }
+
+            let expected = input
+                .chunks(4)
+                .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+                .collect::<Vec<_>>();
+
+            assert_eq!(pixels, expected);
+            previous = pixels;
+        }
+    }
+}